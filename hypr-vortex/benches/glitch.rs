@@ -0,0 +1,52 @@
+//! CPU glitch benchmarks on a 2560x1440 window.
+//!
+//! Every pixel takes twelve trilinear samples, so this mostly measures the
+//! sampling layer. Early frames read fine mip levels, late ones coarse levels
+//! with two thirds of the window already cut out.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use hypr_vortex::damage::Rect;
+use hypr_vortex::pixel::{Image, PixelFormat};
+use hypr_vortex::render::{CpuRenderer, GlitchParams, GlitchRenderer, Target, Texture};
+
+const WIDTH: usize = 2560;
+const HEIGHT: usize = 1440;
+const SEED: u32 = 0x5EED;
+
+fn render_window(renderer: &GlitchRenderer, texture: &Texture, canvas: &mut [u8], progress: f32) {
+    let window = Rect::new(0, 0, WIDTH as i32, HEIGHT as i32);
+    let mut target = Target {
+        canvas,
+        width: WIDTH,
+        window,
+        bounds: renderer.bounds(&window, progress).intersect(&window),
+    };
+    renderer.render(&mut target, texture, progress);
+}
+
+fn render_frame(c: &mut Criterion) {
+    let renderer = GlitchRenderer::new(GlitchParams {
+        block: 32.0,
+        split: 0.015,
+        slices: 0.6,
+        seed: SEED,
+    });
+
+    let pixels: Vec<u8> = (0..WIDTH * HEIGHT * 4).map(|i| (i % 251) as u8).collect();
+    let texture = Texture::new(Image::new(pixels, WIDTH, HEIGHT, PixelFormat::Rgba8));
+    let mut canvas = vec![0u8; WIDTH * HEIGHT * 4];
+
+    let mut group = c.benchmark_group("glitch/2560x1440");
+    for progress in [0.3f32, 0.6, 0.9] {
+        group.bench_function(format!("render/{progress}"), |b| {
+            b.iter(|| render_window(&renderer, &texture, &mut canvas, black_box(progress)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, render_frame);
+criterion_main!(benches);
@@ -0,0 +1,145 @@
+//! CPU vortex benchmarks on a 2560x1440 window.
+//!
+//! `accretion_disk` compares the per-frame strand lookup against the original
+//! loop that re-derived all 32 strands with sin/cos/fract for every disk pixel.
+//! `render` compares the scalar and SIMD pixel loops; the unit tests check that
+//! they agree.
+
+use std::f32::consts::TAU;
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+
+use hypr_vortex::damage::Rect;
+use hypr_vortex::pixel::{Image, PixelFormat};
+use hypr_vortex::render::{
+    SimdLevel, StrandTable, Target, Texture, VortexParams, VortexRenderer,
+};
+
+const WIDTH: usize = 2560;
+const HEIGHT: usize = 1440;
+const SEED: u64 = 0x5EED;
+
+/// The original accretion-disk inner loop, kept here as the baseline.
+fn strand_intensity_recomputed(dist: f32, angle: f32, singularity_radius: f32, rotation: f32) -> f32 {
+    let num_strands = 32;
+    let mut total_intensity = 0.0f32;
+
+    for i in 0..num_strands {
+        let seed = i as f32 * 7.31;
+        let rand1 = (seed.sin() * 43_758.547).fract();
+        let rand2 = ((seed + 1.0).cos() * 22_578.146).fract();
+        let rand3 = ((seed * 2.3).sin() * 19_283.291).fract();
+
+        let strand_brightness = 0.3 + rand1 * 0.7;
+        let strand_length = 0.02 + rand2 * 0.04;
+        let strand_width = 0.008 + rand3 * 0.015;
+        let spiral_tight = 12.0 + rand1 * 8.0;
+
+        let strand_base = (i as f32 / num_strands as f32) * TAU;
+        let expected_angle = strand_base + spiral_tight * dist + rotation;
+
+        let mut angle_diff = (angle - expected_angle).rem_euclid(TAU);
+        if angle_diff > TAU / 2.0 {
+            angle_diff = TAU - angle_diff;
+        }
+
+        let strand_start = singularity_radius;
+        let strand_end = singularity_radius + strand_length;
+        if dist > strand_start && dist < strand_end && angle_diff < strand_width {
+            let core = 1.0 - (angle_diff / strand_width);
+            let tip_fade = 1.0 - ((dist - strand_start) / strand_length).powf(2.0);
+            total_intensity += core.powf(2.0) * tip_fade * strand_brightness;
+        }
+    }
+
+    total_intensity
+}
+
+/// Polar coordinates of every window pixel that falls inside the disk.
+fn disk_pixels(singularity_radius: f32) -> Vec<(f32, f32)> {
+    let disk_outer = singularity_radius + 0.06;
+    let mut pixels = Vec::new();
+    for y in 0..HEIGHT {
+        let dy = y as f32 / HEIGHT as f32 - 0.5;
+        for x in 0..WIDTH {
+            let dx = x as f32 / WIDTH as f32 - 0.5;
+            let dist = (dx * dx + dy * dy).sqrt();
+            if dist > singularity_radius && dist < disk_outer {
+                pixels.push((dist, dy.atan2(dx)));
+            }
+        }
+    }
+    pixels
+}
+
+fn accretion_disk(c: &mut Criterion) {
+    let table = StrandTable::new(SEED);
+    let mut group = c.benchmark_group("accretion_disk/2560x1440");
+
+    for progress in [0.5f32, 0.9] {
+        let singularity_radius = 0.03 + progress * 0.02;
+        let rotation = progress.powi(4) * TAU * 3.0;
+        let pixels = disk_pixels(singularity_radius);
+
+        group.bench_function(format!("recomputed/{progress}"), |b| {
+            b.iter(|| {
+                pixels
+                    .iter()
+                    .map(|&(dist, angle)| {
+                        strand_intensity_recomputed(dist, angle, singularity_radius, rotation)
+                    })
+                    .sum::<f32>()
+            })
+        });
+
+        // Includes building the per-frame lookup, as the renderer does
+        group.bench_function(format!("lookup/{progress}"), |b| {
+            b.iter(|| {
+                let frame = table.frame(black_box(singularity_radius), black_box(rotation));
+                pixels
+                    .iter()
+                    .map(|&(dist, angle)| frame.intensity(dist, angle))
+                    .sum::<f32>()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn render_window(renderer: &VortexRenderer, texture: &Texture, canvas: &mut [u8], progress: f32) {
+    let window = Rect::new(0, 0, WIDTH as i32, HEIGHT as i32);
+    let mut target = Target {
+        canvas,
+        width: WIDTH,
+        window,
+        bounds: renderer.bounds(&window, progress),
+    };
+    renderer.render(&mut target, texture, progress);
+}
+
+fn render_frame(c: &mut Criterion) {
+    let simd = SimdLevel::detect();
+    let params = VortexParams::default();
+    let scalar_renderer = VortexRenderer::new(params, SEED).with_simd(SimdLevel::Scalar);
+    let simd_renderer = VortexRenderer::new(params, SEED).with_simd(simd);
+
+    let pixels: Vec<u8> = (0..WIDTH * HEIGHT * 4).map(|i| (i % 251) as u8).collect();
+    let texture = Texture::new(Image::new(pixels, WIDTH, HEIGHT, PixelFormat::Rgba8));
+    let mut canvas = vec![0u8; WIDTH * HEIGHT * 4];
+
+    let mut group = c.benchmark_group("render/2560x1440");
+    for progress in [0.5f32, 0.9] {
+        group.bench_function(format!("scalar/{progress}"), |b| {
+            b.iter(|| render_window(&scalar_renderer, &texture, &mut canvas, black_box(progress)))
+        });
+        group.bench_function(format!("{simd:?}/{progress}"), |b| {
+            b.iter(|| render_window(&simd_renderer, &texture, &mut canvas, black_box(progress)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, accretion_disk, render_frame);
+criterion_main!(benches);
@@ -1,10 +1,25 @@
 //! Extensible animation system for window close effects.
 //!
 //! Add new animations by implementing the `Animation` trait and registering
-//! them in the `AnimationRegistry`.
+//! them in the `AnimationRegistry`. Every animation is written as a close and
+//! plays backwards, as `Direction::Reverse`, when a window opens.
 
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use anyhow::{Context, Result};
+use glam::Vec2;
+use serde_json::{Map, Value};
+use tracing::debug;
+
+use crate::damage::Rect;
+use crate::easing::{CubicBezier, Easing, EasingLibrary};
+use crate::params::{ParamError, ParamSpec, Params};
+use crate::render::{CpuRenderer, GpuRenderer};
+use crate::rng::Rng;
+use crate::shader::{self, ShaderStage};
 
 /// Window geometry for positioning the animation overlay.
 #[derive(Debug, Clone, Copy)]
@@ -15,32 +30,132 @@ pub struct WindowGeometry {
     pub height: u32,
 }
 
+/// Which way an animation plays.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// A close: the window goes from whole to gone
+    #[default]
+    Forward,
+    /// An open: the close played backwards, so the window emerges
+    Reverse,
+}
+
+/// Where a window is closing: its geometry and the monitor it's on.
+#[derive(Debug, Clone, Copy)]
+pub struct Placement {
+    pub window: WindowGeometry,
+    /// Logical bounds of the window's monitor, in the same layout pixels
+    pub monitor: Rect,
+}
+
+impl Placement {
+    /// The window relative to the monitor's top left.
+    pub fn window_in_monitor(&self) -> Rect {
+        Rect::new(
+            self.window.x - self.monitor.x,
+            self.window.y - self.monitor.y,
+            self.window.width as i32,
+            self.window.height as i32,
+        )
+    }
+
+    /// Shortest offset, in pixels, that carries the window clear of the
+    /// monitor: straight out through its nearest edge.
+    pub fn nearest_exit(&self) -> Vec2 {
+        let w = self.window_in_monitor();
+        let (width, height) = (self.monitor.width, self.monitor.height);
+        [
+            Vec2::new(-w.right() as f32, 0.0),
+            Vec2::new((width - w.x) as f32, 0.0),
+            Vec2::new(0.0, -w.bottom() as f32),
+            Vec2::new(0.0, (height - w.y) as f32),
+        ]
+        .into_iter()
+        .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()))
+        .unwrap_or_default()
+    }
+
+    /// Offset along `direction` (a unit vector) that carries the window clear
+    /// of the monitor.
+    pub fn exit_along(&self, direction: Vec2) -> Vec2 {
+        let w = self.window_in_monitor();
+        let travel = |d: f32, near: i32, far: i32, extent: i32| {
+            if d > f32::EPSILON {
+                (extent - near) as f32 / d
+            } else if d < -f32::EPSILON {
+                far as f32 / -d
+            } else {
+                f32::INFINITY
+            }
+        };
+        let tx = travel(direction.x, w.x, w.right(), self.monitor.width);
+        let ty = travel(direction.y, w.y, w.bottom(), self.monitor.height);
+        let t = tx.min(ty);
+        if t.is_finite() {
+            direction * t.max(0.0)
+        } else {
+            Vec2::ZERO
+        }
+    }
+}
+
 /// Animation progress from 0.0 (start) to 1.0 (complete).
 pub type Progress = f32;
 
 /// WGSL shader source code.
 pub type ShaderSource = &'static str;
 
+/// Number of f32 slots in `AnimationUniforms::params`.
+pub const PARAM_SLOTS: usize = 12;
+
+/// Longest duration an override may ask for.
+pub const MAX_DURATION_MS: u64 = 10_000;
+
+/// Selector that picks a weighted random animation for each close.
+pub const RANDOM: &str = "random";
+
+/// Selector that plays the animations in turn.
+pub const CYCLE: &str = "cycle";
+
 /// Animation configuration passed to shaders.
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
 #[repr(C)]
 pub struct AnimationUniforms {
     /// Animation progress 0.0 to 1.0
     pub progress: f32,
-    /// Time in seconds since animation started
+    /// Seconds since the first frame
     pub time: f32,
     /// Texture width
     pub width: f32,
     /// Texture height
     pub height: f32,
-    /// Animation-specific parameter 1
-    pub param1: f32,
-    /// Animation-specific parameter 2
-    pub param2: f32,
-    /// Animation-specific parameter 3
-    pub param3: f32,
-    /// Animation-specific parameter 4
-    pub param4: f32,
+    /// Per-close random value in `[0, 1)`, for effects that vary each time
+    pub seed: f32,
+    /// `Animation::exit` in window sizes
+    pub exit: [f32; 2],
+    /// Length of the animation in seconds
+    pub duration: f32,
+    /// Animation parameters packed in schema order (see `params`)
+    pub params: [f32; PARAM_SLOTS],
+}
+
+impl AnimationUniforms {
+    /// Uniforms for one close of a `width` x `height` texture.
+    pub fn new(width: f32, height: f32, seed: u64) -> Self {
+        Self {
+            width,
+            height,
+            seed: Rng::new(seed).next_f32(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the way off the monitor, `exit` in pixels, in window sizes.
+    pub fn with_exit(mut self, exit: Vec2) -> Self {
+        let size = Vec2::new(self.width, self.height);
+        self.exit = (exit / size.max(Vec2::ONE)).into();
+        self
+    }
 }
 
 impl Default for AnimationUniforms {
@@ -50,11 +165,90 @@ impl Default for AnimationUniforms {
             time: 0.0,
             width: 0.0,
             height: 0.0,
-            param1: 0.0,
-            param2: 0.0,
-            param3: 0.0,
-            param4: 0.0,
+            seed: 0.0,
+            exit: [0.0; 2],
+            duration: 0.0,
+            params: [0.0; PARAM_SLOTS],
+        }
+    }
+}
+
+/// Duration, easing and parameter values; everything an override can change.
+#[derive(Debug, Clone)]
+pub struct AnimationSettings {
+    pub duration_ms: u64,
+    pub easing: Easing,
+    pub params: Params,
+}
+
+impl AnimationSettings {
+    /// Defaults for an animation with this duration and schema, eased out cubic.
+    pub fn new(duration_ms: u64, schema: &'static [ParamSpec]) -> Self {
+        debug_assert!(Params::new(schema).slots() <= PARAM_SLOTS);
+        Self {
+            duration_ms,
+            easing: Easing::Out(3),
+            params: Params::new(schema),
+        }
+    }
+
+    pub fn with_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    /// Apply overrides from a JSON object: `duration_ms`, `easing` (a name
+    /// from `easings` or `[x0, y0, x1, y1]`) or any schema parameter.
+    pub fn apply(
+        &mut self,
+        overrides: &Map<String, Value>,
+        easings: &EasingLibrary,
+    ) -> Result<(), ParamError> {
+        for (name, value) in overrides {
+            if name == "easing" {
+                self.easing = parse_easing(value, easings).ok_or_else(|| ParamError::WrongType {
+                    name: name.clone(),
+                    expected: "a curve name (see `hypr-vortex list`) or [x0, y0, x1, y1] with x in 0..1",
+                    got: value.to_string(),
+                })?;
+            } else if name == "duration_ms" {
+                self.duration_ms = match value.as_u64() {
+                    Some(ms) if (1..=MAX_DURATION_MS).contains(&ms) => ms,
+                    Some(_) => {
+                        return Err(ParamError::OutOfRange {
+                            name: name.clone(),
+                            range: format!("1..{}", MAX_DURATION_MS),
+                            value: value.to_string(),
+                        })
+                    }
+                    None => {
+                        return Err(ParamError::WrongType {
+                            name: name.clone(),
+                            expected: "a positive integer",
+                            got: value.to_string(),
+                        })
+                    }
+                };
+            } else {
+                self.params.set_json(name, value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A named curve, or an inline bezier as four control point coordinates.
+fn parse_easing(value: &Value, easings: &EasingLibrary) -> Option<Easing> {
+    match value {
+        Value::String(name) => easings.get(name),
+        Value::Array(points) if points.len() == 4 => {
+            let points: Option<Vec<f32>> = points.iter().map(|v| v.as_f64().map(|v| v as f32)).collect();
+            let &[x0, y0, x1, y1] = points?.as_slice() else {
+                return None;
+            };
+            CubicBezier::new(x0, y0, x1, y1).map(Easing::Bezier)
         }
+        _ => None,
     }
 }
 
@@ -69,55 +263,191 @@ pub trait Animation: Send + Sync {
     /// Human-readable description.
     fn description(&self) -> &'static str;
 
+    /// Current duration, easing and parameter values.
+    fn settings(&self) -> &AnimationSettings;
+
+    /// A copy of this animation using `settings`.
+    fn with_settings(&self, settings: AnimationSettings) -> Arc<dyn Animation>;
+
     /// Duration of the animation in milliseconds.
-    fn duration_ms(&self) -> u64;
+    fn duration_ms(&self) -> u64 {
+        self.settings().duration_ms
+    }
+
+    /// Declared parameters, in uniform packing order.
+    fn param_schema(&self) -> &'static [ParamSpec] {
+        self.settings().params.schema()
+    }
+
+    /// A copy with `overrides` (from config or a request) applied on top of
+    /// the current settings; `easings` resolves curve names.
+    fn configured(
+        &self,
+        overrides: &Map<String, Value>,
+        easings: &EasingLibrary,
+    ) -> Result<Arc<dyn Animation>, ParamError> {
+        let mut settings = self.settings().clone();
+        settings.apply(overrides, easings)?;
+        Ok(self.with_settings(settings))
+    }
 
     /// WGSL fragment shader source.
     ///
     /// The shader receives:
     /// - `uniforms.progress`: 0.0 to 1.0
-    /// - `uniforms.time`: seconds since start
+    /// - `uniforms.time`: seconds since the first frame, which counts up
+    ///   whichever way the animation plays
     /// - `uniforms.width/height`: texture dimensions
-    /// - `uniforms.param1-4`: animation-specific parameters
-    /// - `texture`: the window screenshot
+    /// - `uniforms.seed`: random in `[0, 1)`, different for every close
+    /// - `uniforms.exit`: `exit` in window sizes, the offset that takes the
+    ///   window off its monitor
+    /// - `uniforms.duration`: length of the animation in seconds, so
+    ///   `progress * duration` is time along the close's own timeline
+    /// - `uniforms.params`: the parameters from `param_schema`, one f32 each
+    ///   (four for a color), in order
+    /// - `texture`: the window screenshot, premultiplied alpha
     /// - `sampler`: texture sampler
     ///
-    /// Output: vec4<f32> RGBA color for each fragment.
-    fn fragment_shader(&self) -> ShaderSource;
+    /// Output: vec4<f32> premultiplied RGBA color for each fragment, so
+    /// fading must scale all four channels, not just alpha.
+    fn fragment_shader(&self) -> &str;
+
+    /// Grid the window is cut into, as (columns, rows), for animations that
+    /// move vertices instead of warping pixels; `None` draws a single quad.
+    fn mesh(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    /// Whether the shaders draw outside the window, like pieces flying off
+    /// it. The GPU then draws the fragment shader over the whole monitor,
+    /// with `uv` running past 0..1 beyond the window's edges; meshes always
+    /// can leave the window.
+    fn leaves_window(&self) -> bool {
+        false
+    }
+
+    /// WGSL vertex shader for a `mesh` animation.
+    ///
+    /// `vs_main` gets each grid vertex's window uv (0..1) at `@location(0)`,
+    /// and may take the uv of the middle of its cell at `@location(1)` to
+    /// tell which side of a cut a vertex is on (cells don't share corners,
+    /// so pieces can move apart). It returns a clip-space
+    /// `@builtin(position)`, where -1..1 spans the window, plus the uv to
+    /// sample at `@location(0)`; anything else it hands the fragment shader
+    /// goes at later locations. It shares the fragment shader's uniforms.
+    /// `None` keeps vertices where they are.
+    fn vertex_shader(&self) -> Option<ShaderSource> {
+        None
+    }
 
-    /// Update animation-specific uniform parameters.
-    /// Called each frame with current progress.
+    /// Update uniforms for this frame: progress, the duration and the packed
+    /// parameters.
     fn update_uniforms(&self, uniforms: &mut AnimationUniforms, progress: Progress) {
         uniforms.progress = progress;
+        uniforms.duration = self.settings().duration_ms as f32 / 1000.0;
+        self.settings().params.pack(&mut uniforms.params);
     }
 
-    /// Easing function for animation progress.
-    /// Default: ease-out cubic for smooth deceleration.
+    /// Easing function for animation progress: the configured curve.
     fn ease(&self, t: f32) -> f32 {
-        // Ease-out cubic: 1 - (1 - t)^3
-        let inv = 1.0 - t;
-        1.0 - inv * inv * inv
+        self.settings().easing.apply(t)
+    }
+
+    /// Progress at time `t` (0..1 of the duration) playing in `direction`.
+    /// Reverse is the close's timeline run backwards, easing included.
+    fn progress(&self, t: f32, direction: Direction) -> Progress {
+        match direction {
+            Direction::Forward => self.ease(t),
+            Direction::Reverse => self.ease(1.0 - t),
+        }
+    }
+
+    /// Offset, in pixels, that carries the window at `placement` off its
+    /// monitor the way this animation leaves: through the nearest edge
+    /// unless it says otherwise.
+    fn exit(&self, placement: &Placement) -> Vec2 {
+        placement.nearest_exit()
+    }
+
+    /// CPU renderer for one close of the window at `placement`; `seed`
+    /// varies the randomized parts. `None` means the animation only exists
+    /// as shaders, which `renderer` then runs on the GPU.
+    fn cpu_renderer(&self, _placement: &Placement, _seed: u64) -> Option<Box<dyn CpuRenderer>> {
+        None
+    }
+
+    /// What the overlay draws one close with: the CPU renderer if there is
+    /// one, else the shaders on the GPU.
+    fn renderer(&self, placement: &Placement, seed: u64) -> Result<Box<dyn CpuRenderer>> {
+        if let Some(renderer) = self.cpu_renderer(placement, seed) {
+            return Ok(renderer);
+        }
+        let renderer = GpuRenderer::new(self, placement, seed)
+            .with_context(|| format!("'{}' only has a shader", self.name()))?;
+        Ok(Box::new(renderer))
     }
 }
 
 /// Registry of available animations.
+///
+/// Shaders are validated on the way in; an animation whose shader fails is
+/// quarantined instead of registered, with the diagnostics kept for `check`.
+///
+/// Besides animation names, `resolve` and the default accept the selectors
+/// `random` and `cycle`, which pick a registered animation on every call.
 pub struct AnimationRegistry {
     animations: HashMap<&'static str, Arc<dyn Animation>>,
+    quarantined: BTreeMap<&'static str, String>,
     default: &'static str,
+    /// Weighted pool for `random`; empty means every animation, equally likely
+    random: Vec<(&'static str, f32)>,
+    /// Order for `cycle`; empty means every animation by name
+    cycle: Vec<&'static str>,
+    cycle_next: AtomicUsize,
+    picker: Mutex<Rng>,
 }
 
 impl AnimationRegistry {
     pub fn new() -> Self {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default();
         Self {
             animations: HashMap::new(),
+            quarantined: BTreeMap::new(),
             default: "vortex",
+            random: Vec::new(),
+            cycle: Vec::new(),
+            cycle_next: AtomicUsize::new(0),
+            picker: Mutex::new(Rng::new(now.as_nanos() as u64)),
         }
     }
 
     /// Register an animation.
     pub fn register<A: Animation + 'static>(&mut self, animation: A) {
+        self.insert(Arc::new(animation));
+    }
+
+    /// Register an already shared animation, replacing any with the same name.
+    pub fn insert(&mut self, animation: Arc<dyn Animation>) {
         let name = animation.name();
-        self.animations.insert(name, Arc::new(animation));
+        let label = format!("<{} shader>", name);
+        let vertex_label = format!("<{} vertex shader>", name);
+        let validated =
+            shader::validate_once(animation.fragment_shader(), &label, ShaderStage::Fragment)
+                .and_then(|()| match animation.vertex_shader() {
+                    Some(source) => {
+                        shader::validate_once(source, &vertex_label, ShaderStage::Vertex)
+                    }
+                    None => Ok(()),
+                });
+        if let Err(e) = validated {
+            debug!("Quarantined animation '{}'", name);
+            self.animations.remove(name);
+            self.quarantined.insert(name, e);
+            return;
+        }
+        self.animations.insert(name, animation);
     }
 
     /// Get an animation by name.
@@ -125,20 +455,99 @@ impl AnimationRegistry {
         self.animations.get(name).cloned()
     }
 
-    /// Get the default animation.
+    /// Get an animation by name, picking one if `name` is a selector.
+    pub fn resolve(&self, name: &str) -> Option<Arc<dyn Animation>> {
+        match name {
+            RANDOM => self.pick_random(),
+            CYCLE => self.pick_next(),
+            _ => self.get(name),
+        }
+    }
+
+    /// Get the default animation, resolving a selector.
     pub fn default_animation(&self) -> Arc<dyn Animation> {
-        self.animations.get(self.default).cloned().expect("default animation must exist")
+        self.resolve(self.default).expect("default animation must exist")
+    }
+
+    /// Name of the default animation or selector, without resolving it.
+    pub fn default_name(&self) -> &'static str {
+        self.default
     }
 
-    /// Set the default animation name.
+    /// Set the default animation or selector name.
     pub fn set_default(&mut self, name: &'static str) {
         self.default = name;
     }
 
+    /// The registry's own copy of `name`, if it's a selector or a registered animation.
+    pub fn static_name(&self, name: &str) -> Option<&'static str> {
+        match name {
+            RANDOM => Some(RANDOM),
+            CYCLE => Some(CYCLE),
+            _ => self.animations.get_key_value(name).map(|(name, _)| *name),
+        }
+    }
+
+    /// Set the weighted pool `random` draws from; names must be registered.
+    pub fn set_random_pool(&mut self, pool: Vec<(&'static str, f32)>) {
+        self.random = pool;
+    }
+
+    /// Set the order `cycle` goes through; names must be registered.
+    pub fn set_cycle(&mut self, order: Vec<&'static str>) {
+        self.cycle = order;
+        self.cycle_next.store(0, Ordering::Relaxed);
+    }
+
+    fn pick_random(&self) -> Option<Arc<dyn Animation>> {
+        let pool = if self.random.is_empty() {
+            self.sorted_names().into_iter().map(|name| (name, 1.0)).collect()
+        } else {
+            self.random.clone()
+        };
+        let total: f32 = pool.iter().map(|(_, weight)| weight).sum();
+        let mut target = self.picker.lock().unwrap_or_else(|e| e.into_inner()).next_f32() * total;
+        let (name, _) = pool
+            .iter()
+            .find(|(_, weight)| {
+                target -= weight;
+                target < 0.0
+            })
+            // Rounding can leave a sliver past the last weight; never hand
+            // that to an entry weighted zero unless they all are
+            .or_else(|| pool.iter().rev().find(|(_, weight)| *weight > 0.0))
+            .or(pool.last())?;
+        self.get(name)
+    }
+
+    fn pick_next(&self) -> Option<Arc<dyn Animation>> {
+        let order = if self.cycle.is_empty() {
+            self.sorted_names()
+        } else {
+            self.cycle.clone()
+        };
+        if order.is_empty() {
+            return None;
+        }
+        let index = self.cycle_next.fetch_add(1, Ordering::Relaxed) % order.len();
+        self.get(order[index])
+    }
+
+    fn sorted_names(&self) -> Vec<&'static str> {
+        let mut names = self.list();
+        names.sort_unstable();
+        names
+    }
+
     /// List all registered animation names.
     pub fn list(&self) -> Vec<&'static str> {
         self.animations.keys().copied().collect()
     }
+
+    /// Animations refused because their shader failed validation, with the diagnostics.
+    pub fn quarantined(&self) -> impl Iterator<Item = (&'static str, &str)> {
+        self.quarantined.iter().map(|(name, e)| (*name, e.as_str()))
+    }
 }
 
 impl Default for AnimationRegistry {
@@ -146,3 +555,157 @@ impl Default for AnimationRegistry {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONITOR: Rect = Rect {
+        x: 0,
+        y: 0,
+        width: 1000,
+        height: 800,
+    };
+
+    fn placement(monitor: Rect, x: i32, y: i32, width: u32, height: u32) -> Placement {
+        Placement {
+            window: WindowGeometry {
+                x,
+                y,
+                width,
+                height,
+            },
+            monitor,
+        }
+    }
+
+    /// Whether the window, moved by `offset`, is entirely off its monitor.
+    fn clears(placement: &Placement, offset: Vec2) -> bool {
+        let w = &placement.window;
+        let moved = Rect::new(
+            w.x + offset.x.round() as i32,
+            w.y + offset.y.round() as i32,
+            w.width as i32,
+            w.height as i32,
+        );
+        moved.intersect(&placement.monitor).is_empty()
+    }
+
+    #[test]
+    fn nearest_exit_leaves_through_a_touching_edge() {
+        let cases = [
+            (placement(MONITOR, 0, 300, 200, 100), Vec2::new(-200.0, 0.0)),
+            (placement(MONITOR, 800, 300, 200, 100), Vec2::new(200.0, 0.0)),
+            (placement(MONITOR, 400, 0, 200, 100), Vec2::new(0.0, -100.0)),
+            (placement(MONITOR, 400, 700, 200, 100), Vec2::new(0.0, 100.0)),
+        ];
+        for (placement, expected) in cases {
+            let exit = placement.nearest_exit();
+            assert_eq!(exit, expected, "{:?}", placement.window);
+            assert!(clears(&placement, exit));
+        }
+    }
+
+    #[test]
+    fn nearest_exit_on_an_offset_monitor() {
+        // Centered on a monitor right of and below the origin
+        let monitor = Rect::new(1920, 200, 1000, 800);
+        let placement = placement(monitor, 2320, 550, 200, 100);
+        assert_eq!(placement.window_in_monitor(), Rect::new(400, 350, 200, 100));
+        // Up and down are equally near; the first one wins
+        let exit = placement.nearest_exit();
+        assert_eq!(exit, Vec2::new(0.0, -450.0));
+        assert!(clears(&placement, exit));
+    }
+
+    #[test]
+    fn exit_along_a_diagonal() {
+        let placement = placement(MONITOR, 100, 100, 200, 100);
+        // The bottom edge comes first: 700 down takes it clear
+        let exit = placement.exit_along(Vec2::ONE.normalize());
+        assert!(exit.distance(Vec2::new(700.0, 700.0)) < 1e-2, "{exit}");
+        assert!(clears(&placement, exit));
+
+        let exit = placement.exit_along(Vec2::new(-1.0, -1.0).normalize());
+        assert!(exit.distance(Vec2::new(-200.0, -200.0)) < 1e-2, "{exit}");
+        assert!(clears(&placement, exit));
+    }
+
+    #[test]
+    fn exit_along_from_a_touching_edge() {
+        let placement = placement(MONITOR, 0, 300, 200, 100);
+        assert_eq!(placement.exit_along(Vec2::NEG_X), Vec2::new(-200.0, 0.0));
+        assert_eq!(placement.exit_along(Vec2::X), Vec2::new(1000.0, 0.0));
+        assert!(clears(&placement, placement.exit_along(Vec2::Y)));
+    }
+
+    /// Registry with a few cheap animations: fade, shrink and slide.
+    fn registry() -> AnimationRegistry {
+        let mut registry = AnimationRegistry::new();
+        registry.register(crate::animations::FadeAnimation::new());
+        registry.register(crate::animations::ShrinkAnimation::new());
+        registry.register(crate::animations::SlideAnimation::new());
+        registry
+    }
+
+    fn resolved(registry: &AnimationRegistry, name: &str) -> Option<&'static str> {
+        registry.resolve(name).map(|animation| animation.name())
+    }
+
+    #[test]
+    fn cycle_goes_round_in_the_configured_order() {
+        let mut registry = registry();
+        registry.set_cycle(vec!["slide", "fade"]);
+        let picks: Vec<_> = (0..5).filter_map(|_| resolved(&registry, CYCLE)).collect();
+        assert_eq!(picks, ["slide", "fade", "slide", "fade", "slide"]);
+
+        // Unconfigured, it goes through everything by name
+        registry.set_cycle(Vec::new());
+        let picks: Vec<_> = (0..4).filter_map(|_| resolved(&registry, CYCLE)).collect();
+        assert_eq!(picks, ["fade", "shrink", "slide", "fade"]);
+    }
+
+    #[test]
+    fn random_never_picks_a_zero_weight() {
+        let mut registry = registry();
+        // Zero weights first, last and on their own side of the heavy one
+        registry.set_random_pool(vec![("fade", 0.0), ("slide", 3.0), ("shrink", 0.0)]);
+        for _ in 0..2000 {
+            assert_eq!(resolved(&registry, RANDOM), Some("slide"));
+        }
+
+        registry.set_random_pool(vec![("fade", 1.0), ("slide", 1.0), ("shrink", 0.0)]);
+        let mut seen = BTreeMap::new();
+        for _ in 0..2000 {
+            *seen.entry(resolved(&registry, RANDOM).unwrap()).or_insert(0) += 1;
+        }
+        assert_eq!(seen.keys().copied().collect::<Vec<_>>(), ["fade", "slide"]);
+    }
+
+    #[test]
+    fn unknown_names_are_rejected() {
+        let registry = registry();
+        assert!(registry.resolve("vortexx").is_none());
+        assert!(registry.static_name("vortexx").is_none());
+        assert_eq!(registry.static_name(RANDOM), Some(RANDOM));
+        assert_eq!(resolved(&registry, "fade"), Some("fade"));
+    }
+
+    #[test]
+    fn reverse_plays_the_eased_close_backwards() {
+        let settings = AnimationSettings::new(200, &[]).with_easing(Easing::In(3));
+        let animation = crate::animations::FadeAnimation::new().with_settings(settings);
+
+        assert_eq!(animation.progress(0.0, Direction::Reverse), 1.0);
+        assert_eq!(animation.progress(1.0, Direction::Reverse), 0.0);
+        for i in 0..=10 {
+            let t = i as f32 / 10.0;
+            let forward = animation.progress(1.0 - t, Direction::Forward);
+            assert_eq!(animation.progress(t, Direction::Reverse), forward, "t = {t}");
+        }
+        // Not the forward curve mirrored: the slow start of the close is the
+        // slow end of the open
+        assert!(animation.progress(0.9, Direction::Reverse) < 0.01);
+        assert!(animation.progress(0.1, Direction::Reverse) > 0.7);
+    }
+}
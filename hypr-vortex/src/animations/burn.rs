@@ -0,0 +1,152 @@
+//! Burn animation - window burns away behind a glowing ember front, or with
+//! `melt` set, sags and drips off the screen.
+
+use std::sync::Arc;
+
+use glam::Vec3;
+
+use crate::animation::{Animation, AnimationSettings, Placement, ShaderSource};
+use crate::easing::Easing;
+use crate::noise;
+use crate::params::{ParamSpec, ParamValue};
+use crate::render::{BurnParams, BurnRenderer, CpuRenderer};
+use crate::rng::Rng;
+
+/// Tunable parameters, packed into the uniforms in this order.
+const PARAMS: &[ParamSpec] = &[
+    ParamSpec::color("ember", [1.0, 0.45, 0.1, 1.0], "Color of the burning edge"),
+    ParamSpec::float("scale", 1.0, 64.0, 6.0, "Noise features across the window"),
+    ParamSpec::float("edge", 0.01, 0.3, 0.06, "Width of the glowing edge"),
+    ParamSpec::bool("melt", false, "Drip downward instead of burning in place"),
+];
+
+pub struct BurnAnimation {
+    settings: AnimationSettings,
+}
+
+impl BurnAnimation {
+    pub fn new() -> Self {
+        Self {
+            settings: AnimationSettings::new(1200, PARAMS).with_easing(Easing::Linear),
+        }
+    }
+
+    pub fn with_duration(mut self, ms: u64) -> Self {
+        self.settings.duration_ms = ms;
+        self
+    }
+}
+
+impl Default for BurnAnimation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animation for BurnAnimation {
+    fn name(&self) -> &'static str {
+        "burn"
+    }
+
+    fn description(&self) -> &'static str {
+        "Window burns away behind a glowing front, or melts and drips off"
+    }
+
+    fn settings(&self) -> &AnimationSettings {
+        &self.settings
+    }
+
+    fn with_settings(&self, settings: AnimationSettings) -> Arc<dyn Animation> {
+        Arc::new(Self { settings })
+    }
+
+    fn cpu_renderer(&self, _placement: &Placement, seed: u64) -> Option<Box<dyn CpuRenderer>> {
+        let params = &self.settings.params;
+        let Some(ParamValue::Color([r, g, b, _])) = params.get("ember") else {
+            unreachable!("ember is in the schema");
+        };
+        Some(Box::new(BurnRenderer::new(BurnParams {
+            ember: Vec3::new(r, g, b),
+            scale: params.float("scale"),
+            edge: params.float("edge"),
+            melt: params.float("melt") > 0.5,
+            // The same per-close value the shader gets as `u.seed`
+            seed: noise::seed(Rng::new(seed).next_f32()),
+        })))
+    }
+
+    fn fragment_shader(&self) -> ShaderSource {
+        concat!(
+            noise::wgsl!(),
+            r#"
+// Burn Animation Shader
+// An fBm front eats the window with an ember border; melting columns sag
+// and the front rises from the bottom instead
+
+struct Uniforms {
+    progress: f32,
+    time: f32,
+    width: f32,
+    height: f32,
+    seed: f32,
+    _pad0: f32,
+    _pad1: f32,
+    _pad2: f32,
+    ember: vec4<f32>,
+    scale: f32,
+    edge: f32,
+    melt: f32,
+    // Unused parameter slots
+    _pad3: f32,
+    _pad4: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> u: Uniforms;
+@group(0) @binding(1) var tex: texture_2d<f32>;
+@group(0) @binding(2) var tex_sampler: sampler;
+
+const OCTAVES: u32 = 4u;
+const CONTRAST: f32 = 1.8;
+const SCORCH: f32 = 3.0;
+const SOFTNESS: f32 = 0.01;
+const MELT_DISTANCE: f32 = 0.6;
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let seed = noise_seed(u.seed);
+    let cells = vec2<f32>(u.scale, u.scale * u.height / u.width);
+    let edge = max(u.edge, 0.001);
+    let melt = u.melt > 0.5;
+
+    // Melting columns each sag by their own amount
+    var source = uv;
+    if melt {
+        let column = noise_value(vec2<f32>(uv.x * cells.x * 2.0, 0.5), seed ^ 0x5eedu);
+        source.y -= MELT_DISTANCE * u.progress * u.progress * (0.7 + 0.3 * column);
+        if source.y < 0.0 || source.y > 1.0 {
+            return vec4<f32>(0.0);
+        }
+    }
+
+    var level = clamp(noise_fbm(source * cells, OCTAVES, seed) * CONTRAST * 0.5 + 0.5, 0.0, 1.0);
+    if melt {
+        // The bottom goes first
+        level = 0.5 * level + 0.5 * (1.0 - source.y);
+    }
+    let d = level - (-edge + u.progress * (1.0 + 2.0 * edge));
+    if d <= 0.0 {
+        return vec4<f32>(0.0);
+    }
+
+    let color = textureSampleLevel(tex, tex_sampler, source, 0.0);
+    let ember = 1.0 - smoothstep(0.0, edge, d);
+    let scorch = 1.0 - smoothstep(0.0, edge * SCORCH, d);
+    // Hottest, nearly white, right at the front
+    let glow = mix(u.ember.rgb, vec3<f32>(1.0), ember * ember * 0.6) * color.a;
+    let rgb = mix(color.rgb * (1.0 - 0.7 * scorch), glow, ember);
+    return vec4<f32>(rgb, color.a) * smoothstep(0.0, SOFTNESS, d);
+}
+"#
+        )
+    }
+}
@@ -0,0 +1,152 @@
+//! CRT animation - window switches off like an old television.
+
+use std::sync::Arc;
+
+use crate::animation::{Animation, AnimationSettings, Placement, ShaderSource};
+use crate::easing::Easing;
+use crate::params::ParamSpec;
+use crate::render::{CpuRenderer, CrtParams, CrtRenderer};
+
+/// Tunable parameters, packed into the uniforms in this order.
+const PARAMS: &[ParamSpec] = &[
+    ParamSpec::float("line_brightness", 0.0, 4.0, 1.5, "Brightening as the picture becomes a line"),
+    ParamSpec::float("line_at", 0.05, 0.9, 0.4, "Progress at which the picture is a line"),
+    ParamSpec::float("dot_at", 0.1, 0.95, 0.7, "Progress at which the line is a dot"),
+    ParamSpec::float("scanlines", 0.0, 1.0, 0.3, "Darkening of every other row"),
+    ParamSpec::float("aberration", 0.0, 0.05, 0.008, "Red/blue fringe offset, in window widths"),
+];
+
+pub struct CrtAnimation {
+    settings: AnimationSettings,
+}
+
+impl CrtAnimation {
+    pub fn new() -> Self {
+        Self {
+            // Linear: each phase eases on its own
+            settings: AnimationSettings::new(650, PARAMS).with_easing(Easing::Linear),
+        }
+    }
+
+    pub fn with_duration(mut self, ms: u64) -> Self {
+        self.settings.duration_ms = ms;
+        self
+    }
+}
+
+impl Default for CrtAnimation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animation for CrtAnimation {
+    fn name(&self) -> &'static str {
+        "crt"
+    }
+
+    fn description(&self) -> &'static str {
+        "Window collapses to a bright line, then a glowing dot, like a CRT switching off"
+    }
+
+    fn settings(&self) -> &AnimationSettings {
+        &self.settings
+    }
+
+    fn with_settings(&self, settings: AnimationSettings) -> Arc<dyn Animation> {
+        Arc::new(Self { settings })
+    }
+
+    fn cpu_renderer(&self, _placement: &Placement, _seed: u64) -> Option<Box<dyn CpuRenderer>> {
+        let params = &self.settings.params;
+        Some(Box::new(CrtRenderer::new(CrtParams {
+            line_brightness: params.float("line_brightness"),
+            line_at: params.float("line_at"),
+            dot_at: params.float("dot_at"),
+            scanlines: params.float("scanlines"),
+            aberration: params.float("aberration"),
+        })))
+    }
+
+    fn fragment_shader(&self) -> ShaderSource {
+        r#"
+// CRT Animation Shader
+// The picture squeezes to a white-hot line, the line to a dot, and the dot
+// fades, with scanlines, red/blue fringing and a bloom halo
+
+struct Uniforms {
+    progress: f32,
+    time: f32,
+    width: f32,
+    height: f32,
+    seed: f32,
+    _pad0: f32,
+    _pad1: f32,
+    _pad2: f32,
+    line_brightness: f32,
+    line_at: f32,
+    dot_at: f32,
+    scanlines: f32,
+    aberration: f32,
+    // Unused parameter slots
+    _pad3: f32,
+    _pad4: f32,
+    _pad5: f32,
+    _pad6: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> u: Uniforms;
+@group(0) @binding(1) var tex: texture_2d<f32>;
+@group(0) @binding(2) var tex_sampler: sampler;
+
+const LINE_PX: f32 = 2.0;
+const DOT_PX: f32 = 4.0;
+const BLOOM_RADIUS: f32 = 12.0;
+const BLOOM_STRENGTH: f32 = 0.6;
+
+fn sample_fringed(uv: vec2<f32>, fringe: f32, lod: f32) -> vec4<f32> {
+    let center = textureSampleLevel(tex, tex_sampler, uv, lod);
+    let red = textureSampleLevel(tex, tex_sampler, clamp(uv + vec2<f32>(fringe, 0.0), vec2<f32>(0.0), vec2<f32>(1.0)), lod);
+    let blue = textureSampleLevel(tex, tex_sampler, clamp(uv - vec2<f32>(fringe, 0.0), vec2<f32>(0.0), vec2<f32>(1.0)), lod);
+    return vec4<f32>(red.r, center.g, blue.b, max(center.a, max(red.a, blue.a)));
+}
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(u.width, u.height);
+    let line_at = clamp(u.line_at, 0.01, 0.98);
+    let dot_at = clamp(u.dot_at, line_at + 0.01, 0.99);
+    let collapse = pow(clamp(u.progress / line_at, 0.0, 1.0), 2.0);
+    let shrink = pow(clamp((u.progress - line_at) / (dot_at - line_at), 0.0, 1.0), 2.0);
+    let fade = clamp((u.progress - dot_at) / (1.0 - dot_at), 0.0, 1.0);
+
+    let scale = vec2<f32>(
+        mix(1.0, min(DOT_PX / size.x, 1.0), shrink),
+        mix(1.0, min(LINE_PX / size.y, 1.0), collapse),
+    );
+    let half = scale * size * 0.5;
+    let offset = uv * size - size * 0.5;
+
+    var color = vec4<f32>(0.0);
+    if all(abs(offset) <= half) {
+        let lod = max(log2(1.0 / min(scale.x, scale.y)), 0.0);
+        let fringe = u.aberration * sqrt(collapse * (1.0 - shrink));
+        color = sample_fringed(offset / half * 0.5 + 0.5, fringe, lod);
+        // Premultiplied white is the alpha itself
+        var rgb = mix(color.rgb * (1.0 + u.line_brightness * collapse), vec3<f32>(color.a), collapse);
+        if u32(floor(uv.y * size.y)) % 2u == 1u {
+            rgb *= 1.0 - u.scanlines * sqrt(collapse * (1.0 - shrink));
+        }
+        color = vec4<f32>(min(rgb, vec3<f32>(color.a)), color.a);
+    }
+
+    // Bloom: white glow falling off with distance from the picture
+    let distance = length(max(abs(offset) - half, vec2<f32>(0.0)));
+    let halo = BLOOM_STRENGTH * collapse * exp(-distance / BLOOM_RADIUS);
+    color += vec4<f32>(halo) * (1.0 - color.a);
+
+    return color * (1.0 - fade * fade);
+}
+"#
+    }
+}
@@ -0,0 +1,282 @@
+//! Animations defined by a WGSL file instead of Rust code.
+//!
+//! Files in `$XDG_CONFIG_HOME/hypr-vortex/shaders/*.wgsl` start with a TOML
+//! header in `//!` comments; everything but `params` is optional, and the
+//! name defaults to the file name:
+//!
+//! ```wgsl
+//! //! name = "ripple"
+//! //! description = "Concentric ripples"
+//! //! duration_ms = 400
+//! //! easing = "easeOutCubic"
+//! //!
+//! //! [[params]]
+//! //! name = "amplitude"
+//! //! type = "float"            # float, int, bool or color
+//! //! min = 0.0
+//! //! max = 0.2
+//! //! default = 0.05
+//! //! description = "Wave height"
+//!
+//! struct Uniforms {
+//!     progress: f32,
+//!     time: f32,
+//!     width: f32,
+//!     height: f32,
+//!     seed: f32,                     // random in [0, 1), new for every close
+//!     _pad0: f32,                    // exit.x
+//!     _pad1: f32,                    // exit.y
+//!     duration: f32,                 // seconds
+//!     params: array<vec4<f32>, 3>,   // amplitude is params[0].x
+//! }
+//! ```
+//!
+//! The rest of the file is a fragment shader with the same bindings and
+//! `fs_main` entry point as the built-in ones; it's validated with naga
+//! before the animation is registered, and runs on the GPU (see
+//! `render::GpuRenderer`).
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::animation::{Animation, AnimationSettings, PARAM_SLOTS};
+use crate::easing::EasingLibrary;
+use crate::params::{ParamKind, ParamSpec, ParamValue};
+use crate::shader;
+
+/// Duration when the header doesn't set one.
+const DEFAULT_DURATION_MS: u64 = 500;
+
+/// Header comment prefix.
+const HEADER_PREFIX: &str = "//!";
+
+/// The `//!` header of a shader file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Header {
+    name: Option<String>,
+    #[serde(default)]
+    description: String,
+    duration_ms: Option<toml::Value>,
+    easing: Option<toml::Value>,
+    #[serde(default)]
+    params: Vec<ParamHeader>,
+}
+
+/// One `[[params]]` entry.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ParamHeader {
+    name: String,
+    #[serde(rename = "type")]
+    kind: ParamType,
+    min: Option<f64>,
+    max: Option<f64>,
+    default: toml::Value,
+    #[serde(default)]
+    description: String,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ParamType {
+    Float,
+    Int,
+    Bool,
+    Color,
+}
+
+/// An animation loaded from a WGSL file.
+#[derive(Debug)]
+pub struct CustomAnimation {
+    name: &'static str,
+    description: &'static str,
+    source: Arc<str>,
+    settings: AnimationSettings,
+}
+
+impl CustomAnimation {
+    /// Load, check and validate one shader file; `easings` resolves the
+    /// header's `easing`.
+    pub fn load(path: &Path, easings: &EasingLibrary) -> Result<Self> {
+        let source = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let label = path.display().to_string();
+
+        // The header starts on line 1, so toml's line numbers are the file's
+        let header: String = source
+            .lines()
+            .map_while(|line| line.strip_prefix(HEADER_PREFIX))
+            .map(|line| format!("{}\n", line.strip_prefix(' ').unwrap_or(line)))
+            .collect();
+        let header: Header =
+            toml::from_str(&header).with_context(|| format!("{}: invalid header", label))?;
+
+        let name = match header.name {
+            Some(name) => name,
+            None => path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .with_context(|| format!("{}: no name in the header or file name", label))?
+                .to_string(),
+        };
+        if name.is_empty() || name.contains([',', ' ']) {
+            anyhow::bail!("{}: name '{}' can't be empty or contain commas or spaces", label, name);
+        }
+
+        let schema = header
+            .params
+            .iter()
+            .map(ParamHeader::spec)
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("{}: params", label))?;
+        let slots: usize = schema.iter().map(|spec| spec.kind.slots()).sum();
+        if slots > PARAM_SLOTS {
+            anyhow::bail!(
+                "{}: params need {} uniform slots, only {} are available",
+                label,
+                slots,
+                PARAM_SLOTS
+            );
+        }
+
+        let mut overrides = Map::new();
+        for (key, value) in [("duration_ms", header.duration_ms), ("easing", header.easing)] {
+            if let Some(value) = value.and_then(|v| serde_json::to_value(v).ok()) {
+                overrides.insert(key.to_string(), value);
+            }
+        }
+        let mut settings = AnimationSettings::new(DEFAULT_DURATION_MS, intern_schema(schema));
+        settings
+            .apply(&overrides, easings)
+            .with_context(|| format!("{}: header", label))?;
+
+        shader::validate(&source, &label)?;
+
+        Ok(Self {
+            name: intern(&name),
+            description: intern(&header.description),
+            source: source.into(),
+            settings,
+        })
+    }
+}
+
+impl ParamHeader {
+    fn spec(&self) -> Result<ParamSpec> {
+        let range = || match (self.min, self.max) {
+            (Some(min), Some(max)) if min <= max => Ok((min, max)),
+            (Some(_), Some(_)) => anyhow::bail!("{}: min is greater than max", self.name),
+            _ => anyhow::bail!("{}: numeric parameters need min and max", self.name),
+        };
+        let kind = match self.kind {
+            ParamType::Float => {
+                let (min, max) = range()?;
+                ParamKind::Float {
+                    min: min as f32,
+                    max: max as f32,
+                }
+            }
+            ParamType::Int => {
+                let (min, max) = range()?;
+                ParamKind::Int {
+                    min: min as i64,
+                    max: max as i64,
+                }
+            }
+            ParamType::Bool => ParamKind::Bool,
+            ParamType::Color => ParamKind::Color,
+        };
+
+        // Check the default against the finished spec, as for any override
+        let mut spec = ParamSpec {
+            name: intern(&self.name),
+            kind,
+            default: ParamValue::Bool(false),
+            description: intern(&self.description),
+        };
+        let default = serde_json::to_value(&self.default).unwrap_or(Value::Null);
+        spec.default = spec.parse_json(&default).context("default")?;
+        Ok(spec)
+    }
+}
+
+impl Animation for CustomAnimation {
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn description(&self) -> &'static str {
+        self.description
+    }
+
+    fn settings(&self) -> &AnimationSettings {
+        &self.settings
+    }
+
+    fn with_settings(&self, settings: AnimationSettings) -> Arc<dyn Animation> {
+        Arc::new(Self {
+            name: self.name,
+            description: self.description,
+            source: Arc::clone(&self.source),
+            settings,
+        })
+    }
+
+    fn fragment_shader(&self) -> &str {
+        &self.source
+    }
+}
+
+/// Every `*.wgsl` file in `dir`, loaded in name order; a missing directory
+/// has none. Each failure carries the file it came from.
+pub fn load_dir(dir: &Path, easings: &EasingLibrary) -> Vec<Result<CustomAnimation>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(e) => {
+            return vec![Err(e).with_context(|| format!("Failed to read {}", dir.display()))];
+        }
+    };
+
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "wgsl"))
+        .collect();
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| CustomAnimation::load(path, easings))
+        .collect()
+}
+
+/// The trait hands out `&'static` names and schemas, so loaded ones are
+/// leaked. Identical values are leaked once, so reloading unchanged files
+/// costs nothing and an edit only costs the names and descriptions it
+/// changes; sources are owned.
+fn intern(s: &str) -> &'static str {
+    static STRINGS: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+    let mut strings = STRINGS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(interned) = strings.get(s) {
+        return interned;
+    }
+    let leaked: &'static str = Box::leak(s.to_owned().into_boxed_str());
+    strings.insert(leaked);
+    leaked
+}
+
+fn intern_schema(schema: Vec<ParamSpec>) -> &'static [ParamSpec] {
+    static SCHEMAS: Mutex<Vec<&'static [ParamSpec]>> = Mutex::new(Vec::new());
+    let mut schemas = SCHEMAS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(interned) = schemas.iter().find(|interned| **interned == schema.as_slice()) {
+        return interned;
+    }
+    let leaked: &'static [ParamSpec] = Box::leak(schema.into_boxed_slice());
+    schemas.push(leaked);
+    leaked
+}
@@ -0,0 +1,170 @@
+//! Dust animation - window crumbles into specks that drift away on the wind.
+
+use std::sync::Arc;
+
+use crate::animation::{Animation, AnimationSettings, Placement, ShaderSource};
+use crate::easing::Easing;
+use crate::noise;
+use crate::params::ParamSpec;
+use crate::render::{CpuRenderer, DustParams, DustRenderer};
+
+/// Tunable parameters, packed into the uniforms in this order.
+const PARAMS: &[ParamSpec] = &[
+    ParamSpec::int("grain", 1, 16, 4, "Speck size in pixels"),
+    ParamSpec::float("angle", 0.0, 360.0, 0.0, "Sweep direction, degrees clockwise from rightward"),
+    ParamSpec::float("drift", 0.0, 2.0, 0.25, "Drift speed, window diagonals per second"),
+    ParamSpec::float("turbulence", 0.0, 1.0, 0.15, "Swirl speed, window diagonals per second"),
+];
+
+pub struct DustAnimation {
+    settings: AnimationSettings,
+}
+
+impl DustAnimation {
+    pub fn new() -> Self {
+        Self {
+            // Linear: the specks follow real time
+            settings: AnimationSettings::new(1400, PARAMS).with_easing(Easing::Linear),
+        }
+    }
+
+    pub fn with_duration(mut self, ms: u64) -> Self {
+        self.settings.duration_ms = ms;
+        self
+    }
+}
+
+impl Default for DustAnimation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animation for DustAnimation {
+    fn name(&self) -> &'static str {
+        "dust"
+    }
+
+    fn description(&self) -> &'static str {
+        "Window crumbles to dust from one edge to the other and blows away"
+    }
+
+    fn settings(&self) -> &AnimationSettings {
+        &self.settings
+    }
+
+    fn with_settings(&self, settings: AnimationSettings) -> Arc<dyn Animation> {
+        Arc::new(Self { settings })
+    }
+
+    fn leaves_window(&self) -> bool {
+        true
+    }
+
+    fn cpu_renderer(&self, placement: &Placement, seed: u64) -> Option<Box<dyn CpuRenderer>> {
+        let window = &placement.window;
+        let params = &self.settings.params;
+        let params = DustParams {
+            grain: params.float("grain") as u32,
+            angle: params.float("angle").to_radians(),
+            drift: params.float("drift"),
+            turbulence: params.float("turbulence"),
+            duration: self.settings.duration_ms as f32 / 1000.0,
+        };
+        Some(Box::new(DustRenderer::new(window.width, window.height, params, seed)))
+    }
+
+    fn fragment_shader(&self) -> ShaderSource {
+        concat!(
+            noise::wgsl!(),
+            r#"
+// Dust Animation Shader
+// A front sweeps across the window; each grain-sized cluster it passes
+// breaks off and rides a curl-noise breeze while shrinking and fading
+
+struct Uniforms {
+    progress: f32,
+    time: f32,
+    width: f32,
+    height: f32,
+    seed: f32,
+    _pad0: f32,
+    _pad1: f32,
+    duration: f32,
+    grain: f32,
+    angle: f32,
+    drift: f32,
+    turbulence: f32,
+    // Unused parameter slots
+    _pad3: array<vec4<f32>, 2>,
+}
+
+@group(0) @binding(0) var<uniform> u: Uniforms;
+@group(0) @binding(1) var tex: texture_2d<f32>;
+@group(0) @binding(2) var tex_sampler: sampler;
+
+const SWEEP: f32 = 0.55;
+const RAGGEDNESS: f32 = 0.06;
+
+fn hash(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, vec2<f32>(127.1, 311.7)) + u.seed * 91.7) * 43758.5453);
+}
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(u.width, u.height);
+    let diagonal = length(size);
+    let grain = max(u.grain, 1.0);
+    let direction = vec2<f32>(cos(radians(u.angle)), sin(radians(u.angle)));
+    let duration = u.duration;
+    // Progress is linear, so this is seconds into the close
+    let time = u.progress * duration;
+    let p = uv * size;
+
+    // Where the front starts and ends, measured along the sweep
+    let span = abs(direction.x) * size.x + abs(direction.y) * size.y;
+    let start = min(0.0, direction.x * size.x) + min(0.0, direction.y * size.y);
+
+    // Still attached: the screenshot itself, which ends at the window's edges
+    let inside = all(uv >= vec2<f32>(0.0)) && all(uv < vec2<f32>(1.0));
+    let own = floor(p / grain);
+    let own_front = (dot((own + 0.5) * grain, direction) - start) / span;
+    let own_release = SWEEP * duration * clamp(own_front + (hash(own) * 2.0 - 1.0) * RAGGEDNESS, 0.0, 1.0);
+    if inside && time < own_release {
+        return textureSampleLevel(tex, tex_sampler, uv, 0.0);
+    }
+
+    // Trace back along the drift to the cluster that is probably here now
+    var source = p;
+    for (var i = 0; i < 2; i++) {
+        let front = (dot(source, direction) - start) / span;
+        let age = max(time - SWEEP * duration * clamp(front, 0.0, 1.0), 0.0);
+        let curl = noise_value_grad(source / (diagonal / 10.0) + vec2<f32>(0.0, 0.4 * time), noise_seed(u.seed)).yx * vec2<f32>(1.0, -1.0);
+        let wind = direction * u.drift * diagonal + curl * u.turbulence * diagonal;
+        source = p - wind * age - 0.5 * vec2<f32>(0.0, -0.3 * diagonal) * age * age;
+    }
+    if any(source < vec2<f32>(0.0)) || any(source >= size) {
+        return vec4<f32>(0.0);
+    }
+
+    let cluster = floor(source / grain);
+    let front = (dot((cluster + 0.5) * grain, direction) - start) / span;
+    let release = SWEEP * duration * clamp(front + (hash(cluster) * 2.0 - 1.0) * RAGGEDNESS, 0.0, 1.0);
+    let life = (duration - release) * (0.6 + 0.4 * hash(cluster + 7.0));
+    let age = clamp((time - release) / max(life, 0.0001), 0.0, 1.0);
+
+    // Shrunken speck around where the cluster has travelled to
+    let side = grain * (1.0 - 0.5 * age);
+    let offset = source - (cluster + 0.5) * grain;
+    if any(abs(offset) > vec2<f32>(side * 0.5)) {
+        return vec4<f32>(0.0);
+    }
+
+    // Premultiplied: fading scales color along with alpha
+    let color = textureSampleLevel(tex, tex_sampler, (cluster + 0.5) * grain / size, log2(grain));
+    return color * (1.0 - age);
+}
+"#
+        )
+    }
+}
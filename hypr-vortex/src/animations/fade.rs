@@ -1,14 +1,24 @@
 //! Simple fade-out animation (for testing/fallback).
 
-use crate::animation::{Animation, AnimationUniforms, Progress, ShaderSource};
+use std::sync::Arc;
+
+use crate::animation::{Animation, AnimationSettings, Placement, ShaderSource};
+use crate::render::{CpuRenderer, FadeRenderer};
 
 pub struct FadeAnimation {
-    duration_ms: u64,
+    settings: AnimationSettings,
 }
 
 impl FadeAnimation {
     pub fn new() -> Self {
-        Self { duration_ms: 200 }
+        Self {
+            settings: AnimationSettings::new(200, &[]),
+        }
+    }
+
+    pub fn with_duration(mut self, ms: u64) -> Self {
+        self.settings.duration_ms = ms;
+        self
     }
 }
 
@@ -27,12 +37,16 @@ impl Animation for FadeAnimation {
         "Simple fade to transparent"
     }
 
-    fn duration_ms(&self) -> u64 {
-        self.duration_ms
+    fn settings(&self) -> &AnimationSettings {
+        &self.settings
+    }
+
+    fn with_settings(&self, settings: AnimationSettings) -> Arc<dyn Animation> {
+        Arc::new(Self { settings })
     }
 
-    fn update_uniforms(&self, uniforms: &mut AnimationUniforms, progress: Progress) {
-        uniforms.progress = progress;
+    fn cpu_renderer(&self, _placement: &Placement, _seed: u64) -> Option<Box<dyn CpuRenderer>> {
+        Some(Box::new(FadeRenderer::new()))
     }
 
     fn fragment_shader(&self) -> ShaderSource {
@@ -42,10 +56,12 @@ struct Uniforms {
     time: f32,
     width: f32,
     height: f32,
+    seed: f32,
+    _pad0: f32,
     _pad1: f32,
     _pad2: f32,
-    _pad3: f32,
-    _pad4: f32,
+    // No parameters; fills the rest of AnimationUniforms
+    _params: array<vec4<f32>, 3>,
 }
 
 @group(0) @binding(0) var<uniform> u: Uniforms;
@@ -55,7 +71,8 @@ struct Uniforms {
 @fragment
 fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
     var color = textureSample(tex, tex_sampler, uv);
-    color.a = color.a * (1.0 - u.progress);
+    // Premultiplied: fading scales color along with alpha
+    color = color * (1.0 - u.progress);
     return color;
 }
 "#
@@ -0,0 +1,234 @@
+//! Fold animation - window folds in half twice like a sheet of paper, then
+//! flies off.
+
+use std::sync::Arc;
+
+use glam::{Vec2, Vec4};
+
+use crate::animation::{Animation, AnimationSettings, Placement, ShaderSource};
+use crate::easing::Easing;
+use crate::params::{ParamSpec, ParamValue};
+use crate::render::{CpuRenderer, FoldDeform, MeshRenderer};
+
+/// Tunable parameters, packed into the uniforms in this order.
+const PARAMS: &[ParamSpec] = &[
+    ParamSpec::color("back", [0.93, 0.92, 0.89, 1.0], "Color of the paper's back"),
+    ParamSpec::float(
+        "perspective",
+        0.5,
+        10.0,
+        2.0,
+        "Camera distance in window sizes; closer exaggerates the depth",
+    ),
+];
+
+/// One cell per quarter: every quarter stays flat and the creases fall on
+/// the grid lines.
+const GRID: (u32, u32) = (2, 2);
+
+pub struct FoldAnimation {
+    settings: AnimationSettings,
+}
+
+impl FoldAnimation {
+    pub fn new() -> Self {
+        Self {
+            // Linear: each fold eases on its own
+            settings: AnimationSettings::new(900, PARAMS).with_easing(Easing::Linear),
+        }
+    }
+
+    pub fn with_duration(mut self, ms: u64) -> Self {
+        self.settings.duration_ms = ms;
+        self
+    }
+}
+
+impl Default for FoldAnimation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animation for FoldAnimation {
+    fn name(&self) -> &'static str {
+        "fold"
+    }
+
+    fn description(&self) -> &'static str {
+        "Window folds in half twice like a sheet of paper, then flies off"
+    }
+
+    fn settings(&self) -> &AnimationSettings {
+        &self.settings
+    }
+
+    fn with_settings(&self, settings: AnimationSettings) -> Arc<dyn Animation> {
+        Arc::new(Self { settings })
+    }
+
+    fn mesh(&self) -> Option<(u32, u32)> {
+        Some(GRID)
+    }
+
+    fn cpu_renderer(&self, placement: &Placement, _seed: u64) -> Option<Box<dyn CpuRenderer>> {
+        let window = &placement.window;
+        let params = &self.settings.params;
+        let Some(ParamValue::Color([r, g, b, a])) = params.get("back") else {
+            unreachable!("back is in the schema");
+        };
+        let size = Vec2::new(window.width as f32, window.height as f32);
+        Some(Box::new(
+            MeshRenderer::new(FoldDeform::new(size), GRID)
+                .with_perspective(params.float("perspective"))
+                // Premultiplied, as the renderer expects
+                .with_back(Vec4::new(r * a, g * a, b * a, a)),
+        ))
+    }
+
+    fn vertex_shader(&self) -> Option<ShaderSource> {
+        Some(
+            r#"
+// Fold Animation Vertex Shader
+// Swings the right half, then the bottom half, over around their creases,
+// sends the folded sheet off, and projects it with perspective
+
+struct Uniforms {
+    progress: f32,
+    time: f32,
+    width: f32,
+    height: f32,
+    seed: f32,
+    _pad0: f32,
+    _pad1: f32,
+    _pad2: f32,
+    back: vec4<f32>,
+    perspective: f32,
+    // Unused parameter slots
+    _pad3: f32,
+    _pad4: f32,
+    _pad5: f32,
+    _pad6: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> u: Uniforms;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    // Window-pixel position before projection, z toward the viewer
+    @location(1) world: vec3<f32>,
+}
+
+const PI: f32 = 3.14159265;
+const FIRST_END: f32 = 0.35;
+const SECOND_END: f32 = 0.7;
+const THICKNESS: f32 = 1.0;
+const FLY_RISE: f32 = 0.6;
+const FLY_BACK: f32 = 3.0;
+const NEAR: f32 = 0.05;
+// Just under 1, so depth stays in 0..1 out to NEAR
+const DEPTH_FAR: f32 = 0.97;
+
+@vertex
+fn vs_main(@location(0) uv: vec2<f32>, @location(1) cell: vec2<f32>) -> VertexOutput {
+    let size = vec2<f32>(u.width, u.height);
+    let crease = size * 0.5;
+    var p = vec3<f32>(uv * size, 0.0);
+
+    // Right half over the left, lifted one sheet clear of it; vertices on
+    // a crease go with their cell, so each sheet stays flat
+    if cell.x > 0.5 {
+        let angle = PI * smoothstep(0.0, 1.0, u.progress / FIRST_END);
+        let dx = p.x - crease.x;
+        p = vec3<f32>(crease.x + dx * cos(angle), p.y, dx * sin(angle) + THICKNESS * (1.0 - cos(angle)) * 0.5);
+    }
+
+    // Bottom half, already two sheets thick, over the top two
+    if cell.y > 0.5 {
+        let angle = PI * smoothstep(0.0, 1.0, (u.progress - FIRST_END) / (SECOND_END - FIRST_END));
+        let dy = p.y - crease.y;
+        p = vec3<f32>(
+            p.x,
+            crease.y + dy * cos(angle) - p.z * sin(angle),
+            dy * sin(angle) + p.z * cos(angle) + 3.0 * THICKNESS * (1.0 - cos(angle)) * 0.5,
+        );
+    }
+
+    let fly = pow(clamp((u.progress - SECOND_END) / (1.0 - SECOND_END), 0.0, 1.0), 2.0);
+    p += vec3<f32>(0.0, -FLY_RISE * size.y, -FLY_BACK * max(size.x, size.y)) * fly;
+
+    // Camera in front of the window's middle; w carries the perspective divide
+    let focal = u.perspective * max(size.x, size.y);
+    let w = max(focal - p.z, focal * NEAR) / focal;
+    let projected = crease + (p.xy - crease) / w;
+
+    var out: VertexOutput;
+    let ndc = (projected / size * 2.0 - 1.0) * vec2<f32>(1.0, -1.0);
+    // Affine in p, so depth interpolates straight across each triangle: after
+    // the divide it runs from 1 far away down toward the camera
+    out.position = vec4<f32>(ndc * w, DEPTH_FAR - p.z / focal, w);
+    out.uv = uv;
+    out.world = p;
+    return out;
+}
+"#,
+        )
+    }
+
+    fn fragment_shader(&self) -> ShaderSource {
+        r#"
+// Fold Animation Fragment Shader
+// Lights each face by its normal and shows the paper's back on faces that
+// have turned over
+
+struct Uniforms {
+    progress: f32,
+    time: f32,
+    width: f32,
+    height: f32,
+    seed: f32,
+    _pad0: f32,
+    _pad1: f32,
+    _pad2: f32,
+    back: vec4<f32>,
+    perspective: f32,
+    // Unused parameter slots
+    _pad3: f32,
+    _pad4: f32,
+    _pad5: f32,
+    _pad6: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> u: Uniforms;
+@group(0) @binding(1) var tex: texture_2d<f32>;
+@group(0) @binding(2) var tex_sampler: sampler;
+
+const LIGHT: vec3<f32> = vec3<f32>(-0.36, -0.45, 0.82);
+const AMBIENT: f32 = 0.35;
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>, @location(1) world: vec3<f32>) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(u.width, u.height);
+    let eye = vec3<f32>(size * 0.5, u.perspective * max(size.x, size.y));
+
+    // A turned-over face shows the texture mirrored
+    let flipped = determinant(mat2x2<f32>(dpdx(uv), dpdy(uv))) < 0.0;
+
+    var normal = normalize(cross(dpdx(world), dpdy(world)));
+    if dot(normal, eye - world) < 0.0 {
+        normal = -normal;
+    }
+    let light = AMBIENT + (1.0 - AMBIENT) * max(dot(normal, LIGHT), 0.0) / LIGHT.z;
+
+    var color = textureSample(tex, tex_sampler, uv);
+    if flipped {
+        color = vec4<f32>(u.back.rgb * u.back.a, u.back.a) * color.a;
+    }
+    // Premultiplied: fading scales color along with alpha
+    let fade = 1.0 - smoothstep(0.85, 1.0, u.progress);
+    return vec4<f32>(min(color.rgb * light, vec3<f32>(color.a)), color.a) * fade;
+}
+"#
+    }
+}
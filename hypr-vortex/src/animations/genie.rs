@@ -0,0 +1,201 @@
+//! Genie animation - window pinches and pours into a target, like minimizing
+//! into a dock icon.
+//!
+//! The target is given in window sizes relative to the window's top left, so
+//! the defaults work for any window. A request can instead pass
+//! `"target": [x, y, width, height]` in layout pixels, or `"target": "cursor"`,
+//! and the daemon converts it.
+
+use std::sync::Arc;
+
+use glam::Vec2;
+
+use crate::animation::{Animation, AnimationSettings, Placement, ShaderSource};
+use crate::easing::Easing;
+use crate::params::ParamSpec;
+use crate::render::{CpuRenderer, GenieDeform, MeshRenderer};
+
+/// Tunable parameters, packed into the uniforms in this order.
+const PARAMS: &[ParamSpec] = &[
+    ParamSpec::float("target_x", -64.0, 64.0, 0.45, "Target left edge, in window widths"),
+    ParamSpec::float("target_y", -64.0, 64.0, 1.25, "Target top edge, in window heights"),
+    ParamSpec::float("target_width", 0.0, 64.0, 0.1, "Target width, in window widths"),
+    ParamSpec::float("target_height", 0.0, 64.0, 0.04, "Target height, in window heights"),
+];
+
+/// Mesh resolution; fine enough that the pinched sides curve smoothly.
+const GRID: (u32, u32) = (24, 24);
+
+pub struct GenieAnimation {
+    settings: AnimationSettings,
+}
+
+impl GenieAnimation {
+    pub fn new() -> Self {
+        Self {
+            settings: AnimationSettings::new(600, PARAMS).with_easing(Easing::InOut(2)),
+        }
+    }
+
+    pub fn with_duration(mut self, ms: u64) -> Self {
+        self.settings.duration_ms = ms;
+        self
+    }
+}
+
+impl Default for GenieAnimation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animation for GenieAnimation {
+    fn name(&self) -> &'static str {
+        "genie"
+    }
+
+    fn description(&self) -> &'static str {
+        "Window pinches and pours into a target such as a dock icon or the cursor"
+    }
+
+    fn settings(&self) -> &AnimationSettings {
+        &self.settings
+    }
+
+    fn with_settings(&self, settings: AnimationSettings) -> Arc<dyn Animation> {
+        Arc::new(Self { settings })
+    }
+
+    fn mesh(&self) -> Option<(u32, u32)> {
+        Some(GRID)
+    }
+
+    fn cpu_renderer(&self, placement: &Placement, _seed: u64) -> Option<Box<dyn CpuRenderer>> {
+        let window = &placement.window;
+        let params = &self.settings.params;
+        let size = Vec2::new(window.width as f32, window.height as f32);
+        let target_min = Vec2::new(params.float("target_x"), params.float("target_y")) * size;
+        let target_size =
+            Vec2::new(params.float("target_width"), params.float("target_height")) * size;
+        let deform = GenieDeform::new(size, target_min, target_min + target_size);
+        Some(Box::new(MeshRenderer::new(deform, GRID)))
+    }
+
+    fn vertex_shader(&self) -> Option<ShaderSource> {
+        Some(
+            r#"
+// Genie Animation Vertex Shader
+// Works in a frame where the window flows toward +y, then maps back
+
+struct Uniforms {
+    progress: f32,
+    time: f32,
+    width: f32,
+    height: f32,
+    seed: f32,
+    _pad0: f32,
+    _pad1: f32,
+    _pad2: f32,
+    target_x: f32,
+    target_y: f32,
+    target_width: f32,
+    target_height: f32,
+    // Unused parameter slots
+    _pad3: array<vec4<f32>, 2>,
+}
+
+@group(0) @binding(0) var<uniform> u: Uniforms;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+const SQUEEZE_END: f32 = 0.45;
+const SLIDE_START: f32 = 0.25;
+
+// Swap axes to flow sideways, flip the axis to flow up or left
+fn to_frame(p: vec2<f32>, swap: bool, flip: bool, along: f32) -> vec2<f32> {
+    let q = select(p, p.yx, swap);
+    return select(q, vec2<f32>(q.x, along - q.y), flip);
+}
+
+fn to_window(q: vec2<f32>, swap: bool, flip: bool, along: f32) -> vec2<f32> {
+    let p = select(q, vec2<f32>(q.x, along - q.y), flip);
+    return select(p, p.yx, swap);
+}
+
+@vertex
+fn vs_main(@location(0) uv: vec2<f32>) -> VertexOutput {
+    let size = vec2<f32>(u.width, u.height);
+    let target_a = vec2<f32>(u.target_x, u.target_y) * size;
+    let target_b = target_a + vec2<f32>(u.target_width, u.target_height) * size;
+
+    let offset = ((target_a + target_b) * 0.5 - size * 0.5) / size;
+    let swap = abs(offset.x) > abs(offset.y);
+    let flip = select(offset.y < 0.0, offset.x < 0.0, swap);
+    let along = select(size.y, size.x, swap);
+    let across = select(size.x, size.y, swap);
+
+    let a = to_frame(target_a, swap, flip, along);
+    let b = to_frame(target_b, swap, flip, along);
+    let target_min = min(a, b);
+    let target_max = max(a, b);
+
+    let p = to_frame(uv * size, swap, flip, along);
+    let squeeze = smoothstep(0.0, 1.0, u.progress / SQUEEZE_END);
+    let slide = smoothstep(0.0, 1.0, (u.progress - SLIDE_START) / (1.0 - SLIDE_START));
+
+    // Each row slides to its place in the target, keeping its order
+    let landing = mix(target_min.y, target_max.y, p.y / along);
+    let y = mix(p.y, landing, slide);
+
+    // Rows narrow toward the target's width the closer they get to it
+    let closeness = smoothstep(0.0, 1.0, y / max(target_min.y, 1.0));
+    let narrowed = mix(target_min.x, target_max.x, p.x / across);
+    let x = mix(p.x, narrowed, max(squeeze * closeness, slide * slide));
+
+    let moved = to_window(vec2<f32>(x, y), swap, flip, along);
+    var out: VertexOutput;
+    out.position = vec4<f32>((moved / size * 2.0 - 1.0) * vec2<f32>(1.0, -1.0), 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+"#,
+        )
+    }
+
+    fn fragment_shader(&self) -> ShaderSource {
+        r#"
+// Genie Animation Fragment Shader
+// The mesh does the moving; this only fades out at the end
+
+struct Uniforms {
+    progress: f32,
+    time: f32,
+    width: f32,
+    height: f32,
+    seed: f32,
+    _pad0: f32,
+    _pad1: f32,
+    _pad2: f32,
+    target_x: f32,
+    target_y: f32,
+    target_width: f32,
+    target_height: f32,
+    // Unused parameter slots
+    _pad3: array<vec4<f32>, 2>,
+}
+
+@group(0) @binding(0) var<uniform> u: Uniforms;
+@group(0) @binding(1) var tex: texture_2d<f32>;
+@group(0) @binding(2) var tex_sampler: sampler;
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    // Premultiplied: fading scales color along with alpha
+    return textureSample(tex, tex_sampler, uv) * (1.0 - smoothstep(0.85, 1.0, u.progress));
+}
+"#
+    }
+}
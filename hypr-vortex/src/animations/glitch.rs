@@ -0,0 +1,157 @@
+//! Glitch animation - window decays like a corrupted video signal.
+
+use std::sync::Arc;
+
+use crate::animation::{Animation, AnimationSettings, Placement, ShaderSource};
+use crate::easing::Easing;
+use crate::noise;
+use crate::params::ParamSpec;
+use crate::render::{CpuRenderer, GlitchParams, GlitchRenderer};
+use crate::rng::Rng;
+
+/// Tunable parameters, packed into the uniforms in this order.
+const PARAMS: &[ParamSpec] = &[
+    ParamSpec::float("block", 1.0, 128.0, 32.0, "Largest pixel block, in pixels"),
+    ParamSpec::float("split", 0.0, 0.1, 0.015, "Red/blue split at its widest, in window widths"),
+    ParamSpec::float("slices", 0.0, 1.0, 0.6, "Share of horizontal slices knocked sideways"),
+];
+
+pub struct GlitchAnimation {
+    settings: AnimationSettings,
+}
+
+impl GlitchAnimation {
+    pub fn new() -> Self {
+        Self {
+            // Linear: each effect ramps up on its own
+            settings: AnimationSettings::new(600, PARAMS).with_easing(Easing::Linear),
+        }
+    }
+
+    pub fn with_duration(mut self, ms: u64) -> Self {
+        self.settings.duration_ms = ms;
+        self
+    }
+}
+
+impl Default for GlitchAnimation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animation for GlitchAnimation {
+    fn name(&self) -> &'static str {
+        "glitch"
+    }
+
+    fn description(&self) -> &'static str {
+        "Window pixelates, splits its colors and tears into slices before cutting out"
+    }
+
+    fn settings(&self) -> &AnimationSettings {
+        &self.settings
+    }
+
+    fn with_settings(&self, settings: AnimationSettings) -> Arc<dyn Animation> {
+        Arc::new(Self { settings })
+    }
+
+    fn cpu_renderer(&self, _placement: &Placement, seed: u64) -> Option<Box<dyn CpuRenderer>> {
+        let params = &self.settings.params;
+        Some(Box::new(GlitchRenderer::new(GlitchParams {
+            block: params.float("block"),
+            split: params.float("split"),
+            slices: params.float("slices"),
+            // The same per-close value the shader gets as `u.seed`
+            seed: noise::seed(Rng::new(seed).next_f32()),
+        })))
+    }
+
+    fn fragment_shader(&self) -> ShaderSource {
+        concat!(
+            noise::wgsl!(),
+            r#"
+// Glitch Animation Shader
+// Blocks grow, red and blue drift apart, slices jump sideways and blocks
+// drop out; each block is a 2x2 box filter of channel-split samples
+
+struct Uniforms {
+    progress: f32,
+    time: f32,
+    width: f32,
+    height: f32,
+    seed: f32,
+    _pad0: f32,
+    _pad1: f32,
+    _pad2: f32,
+    block: f32,
+    split: f32,
+    slices: f32,
+    // Unused parameter slots
+    _pad3: f32,
+    _pad4: vec4<f32>,
+    _pad5: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> u: Uniforms;
+@group(0) @binding(1) var tex: texture_2d<f32>;
+@group(0) @binding(2) var tex_sampler: sampler;
+
+const TICKS: f32 = 16.0;
+const SLICE_PX: f32 = 24.0;
+const SHIFT: f32 = 0.12;
+const CUTOFF_AT: f32 = 0.7;
+const SLICE_SEED: u32 = 1u;
+const SHIFT_SEED: u32 = 2u;
+const CUTOFF_SEED: u32 = 3u;
+
+fn texture_at(p: vec2<f32>, size: vec2<f32>, lod: f32) -> vec4<f32> {
+    return textureSampleLevel(tex, tex_sampler, clamp(p / size, vec2<f32>(0.0), vec2<f32>(1.0)), lod);
+}
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let seed = noise_seed(u.seed);
+    let size = vec2<f32>(u.width, u.height);
+    let p = uv * size;
+    let block = round(1.0 + max(u.block - 1.0, 0.0) * u.progress * u.progress);
+    let tick = i32(u.progress * TICKS);
+    let cutoff = clamp((u.progress - CUTOFF_AT) / (1.0 - CUTOFF_AT), 0.0, 1.0);
+
+    // Knock the slice sideways, re-rolled every tick
+    let slice = i32(floor(p.y / SLICE_PX));
+    var q = p;
+    if noise_random(vec2<i32>(slice, tick), seed ^ SLICE_SEED) < u.slices * u.progress {
+        let shift = noise_random(vec2<i32>(slice, tick), seed ^ SHIFT_SEED) * 2.0 - 1.0;
+        q.x -= shift * SHIFT * u.progress * size.x;
+    }
+    if q.x < 0.0 || q.x >= size.x {
+        return vec4<f32>(0.0);
+    }
+
+    let cell = floor(q / block);
+    if noise_random(vec2<i32>(cell), seed ^ CUTOFF_SEED) < cutoff {
+        return vec4<f32>(0.0);
+    }
+
+    let center = (cell + 0.5) * block;
+    let quarter = block * 0.25;
+    let fringe = vec2<f32>(u.split * u.progress * size.x, 0.0);
+    let lod = max(log2(block * 0.5), 0.0);
+    var color = vec4<f32>(0.0);
+    for (var i = 0; i < 4; i++) {
+        let at = center + quarter * vec2<f32>(f32(i % 2) * 2.0 - 1.0, f32(i / 2) * 2.0 - 1.0);
+        let red = texture_at(at + fringe, size, lod);
+        let green = texture_at(at, size, lod);
+        let blue = texture_at(at - fringe, size, lod);
+        color += vec4<f32>(red.r, green.g, blue.b, max(red.a, max(green.a, blue.a)));
+    }
+    // Split channels can leave a color brighter than its alpha allows
+    color *= 0.25;
+    return vec4<f32>(min(color.rgb, vec3<f32>(color.a)), color.a);
+}
+"#
+        )
+    }
+}
@@ -1,11 +1,31 @@
 //! Built-in animation implementations.
 
+mod burn;
+mod crt;
+mod custom;
+mod dust;
 mod fade;
+mod fold;
+mod genie;
+mod glitch;
+mod portal;
+mod shatter;
 mod shrink;
+mod slide;
 mod vortex;
 
+pub use burn::BurnAnimation;
+pub use crt::CrtAnimation;
+pub use custom::{load_dir, CustomAnimation};
+pub use dust::DustAnimation;
 pub use fade::FadeAnimation;
+pub use fold::FoldAnimation;
+pub use genie::GenieAnimation;
+pub use glitch::GlitchAnimation;
+pub use portal::PortalAnimation;
+pub use shatter::ShatterAnimation;
 pub use shrink::ShrinkAnimation;
+pub use slide::SlideAnimation;
 pub use vortex::VortexAnimation;
 
 use crate::animation::AnimationRegistry;
@@ -15,5 +35,14 @@ pub fn register_all(registry: &mut AnimationRegistry) {
     registry.register(VortexAnimation::new());
     registry.register(FadeAnimation::new());
     registry.register(ShrinkAnimation::new());
+    registry.register(ShatterAnimation::new());
+    registry.register(DustAnimation::new());
+    registry.register(GenieAnimation::new());
+    registry.register(PortalAnimation::new());
+    registry.register(CrtAnimation::new());
+    registry.register(BurnAnimation::new());
+    registry.register(SlideAnimation::new());
+    registry.register(GlitchAnimation::new());
+    registry.register(FoldAnimation::new());
     registry.set_default("vortex");
 }
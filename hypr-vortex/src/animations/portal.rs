@@ -0,0 +1,267 @@
+//! Portal animation - window is sucked into a swirling portal that then
+//! snaps shut.
+//!
+//! Replaces the hypr-portal-effect Hyprland plugin, with the same settings:
+//! its `plugin:hypr-portal-effect:{duration,rotation_speed,whirling,color}`
+//! become `duration_ms` and the parameters below.
+
+use std::sync::Arc;
+
+use glam::Vec3;
+
+use crate::animation::{Animation, AnimationSettings, Placement, ShaderSource};
+use crate::easing::Easing;
+use crate::noise;
+use crate::params::{ParamSpec, ParamValue};
+use crate::render::{CpuRenderer, PortalParams, PortalRenderer};
+use crate::rng::Rng;
+
+/// Tunable parameters, packed into the uniforms in this order.
+const PARAMS: &[ParamSpec] = &[
+    ParamSpec::color("color", [0.616, 0.306, 0.867, 1.0], "Portal color"),
+    ParamSpec::float("rotation_speed", 0.0, 20.0, 2.0, "Swirl speed, radians per second"),
+    ParamSpec::float("whirling", 0.0, 5.0, 1.0, "How strongly the swirl twists toward the center"),
+    ParamSpec::float("seed", -1.0, 1000.0, -1.0, "Noise pattern; -1 picks a new one every close"),
+];
+
+pub struct PortalAnimation {
+    settings: AnimationSettings,
+}
+
+impl PortalAnimation {
+    pub fn new() -> Self {
+        Self {
+            // Linear: the portal's own easing is built into the effect
+            settings: AnimationSettings::new(500, PARAMS).with_easing(Easing::Linear),
+        }
+    }
+
+    pub fn with_duration(mut self, ms: u64) -> Self {
+        self.settings.duration_ms = ms;
+        self
+    }
+}
+
+impl Default for PortalAnimation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animation for PortalAnimation {
+    fn name(&self) -> &'static str {
+        "portal"
+    }
+
+    fn description(&self) -> &'static str {
+        "Window is sucked into a swirling portal that snaps shut"
+    }
+
+    fn settings(&self) -> &AnimationSettings {
+        &self.settings
+    }
+
+    fn with_settings(&self, settings: AnimationSettings) -> Arc<dyn Animation> {
+        Arc::new(Self { settings })
+    }
+
+    fn cpu_renderer(&self, _placement: &Placement, seed: u64) -> Option<Box<dyn CpuRenderer>> {
+        let params = &self.settings.params;
+        let Some(ParamValue::Color([r, g, b, _])) = params.get("color") else {
+            unreachable!("color is in the schema");
+        };
+        // Derived from the same per-close value the shader gets as `u.seed`
+        let pattern = match params.float("seed") {
+            s if s < 0.0 => noise::seed(Rng::new(seed).next_f32()),
+            s => s as u32,
+        };
+        let params = PortalParams {
+            color: Vec3::new(r, g, b),
+            rotation_speed: params.float("rotation_speed"),
+            whirling: params.float("whirling"),
+            seed: pattern,
+            duration: self.settings.duration_ms as f32 / 1000.0,
+        };
+        Some(Box::new(PortalRenderer::new(params)))
+    }
+
+    fn fragment_shader(&self) -> ShaderSource {
+        concat!(
+            noise::wgsl!(),
+            r#"
+// Portal Animation Shader
+// Adapted from Burn My Windows by Simon Schneegans (GPL-3.0-or-later),
+// by way of the hypr-portal-effect plugin
+
+struct Uniforms {
+    progress: f32,
+    time: f32,
+    width: f32,
+    height: f32,
+    seed: f32,
+    _pad0: f32,
+    _pad1: f32,
+    duration: f32,
+    color: vec4<f32>,
+    rotation_speed: f32,
+    whirling: f32,
+    pattern: f32,
+    // Unused parameter slots
+    _pad3: f32,
+    _pad4: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> u: Uniforms;
+@group(0) @binding(1) var tex: texture_2d<f32>;
+@group(0) @binding(2) var tex_sampler: sampler;
+
+const PORTAL_WOBBLE_TIME: f32 = 0.8;
+const PORTAL_WOBBLE_STRENGTH: f32 = 1.2;
+const GLOW_EDGE_WIDTH: f32 = 5.0;
+const WINDOW_SCALE: f32 = 0.3;
+const WINDOW_SQUISH: f32 = 1.0;
+const WINDOW_TILT: f32 = -1.0;
+const PORTAL_OPEN_TIME: f32 = 0.4;
+const PORTAL_CLOSE_TIME: f32 = 0.4;
+const WINDOW_OPEN_TIME: f32 = 0.35;
+
+// In about 0..1, the range Burn My Windows' effect expects
+fn simplex01(p: vec2<f32>, seed: u32) -> f32 {
+    return noise_simplex(p, seed) * 0.5 + 0.5;
+}
+
+fn ease_in_back(x: f32, s: f32) -> f32 {
+    return (s + 1.0) * x * x * x - s * x * x;
+}
+
+fn ease_out_back(x: f32, s: f32) -> f32 {
+    let t = x - 1.0;
+    return 1.0 + (s + 1.0) * t * t * t + s * t * t;
+}
+
+fn darken(color: vec3<f32>, amount: f32) -> vec3<f32> {
+    return color * (1.0 - amount);
+}
+
+fn lighten(color: vec3<f32>, amount: f32) -> vec3<f32> {
+    return color + (vec3<f32>(1.0) - color) * amount;
+}
+
+// Straight-alpha "over"
+fn alpha_over(background: vec4<f32>, foreground: vec4<f32>) -> vec4<f32> {
+    let alpha = foreground.a + background.a * (1.0 - foreground.a);
+    let color = (foreground.rgb * foreground.a + background.rgb * background.a * (1.0 - foreground.a)) / max(alpha, 0.001);
+    return vec4<f32>(color, alpha);
+}
+
+fn whirl(coords: vec2<f32>, warping: f32, rotation: f32) -> vec2<f32> {
+    let dist = length(coords);
+    let angle = atan2(coords.y, coords.x) + rotation + warping * exp(-dist);
+    return vec2<f32>(cos(angle), sin(angle)) * dist;
+}
+
+fn portal_scale() -> f32 {
+    if u.progress < PORTAL_OPEN_TIME {
+        return ease_out_back(u.progress / PORTAL_OPEN_TIME, 1.5);
+    }
+    if u.progress > 1.0 - PORTAL_CLOSE_TIME {
+        return ease_out_back(1.0 - (u.progress - 1.0 + PORTAL_CLOSE_TIME) / PORTAL_CLOSE_TIME, 1.5);
+    }
+    return 1.0;
+}
+
+fn portal_wobble(coords: vec2<f32>) -> vec2<f32> {
+    let t = u.progress / WINDOW_OPEN_TIME;
+    let wobble = ease_in_back(clamp(1.0 - abs((t - 1.0) / PORTAL_WOBBLE_TIME), 0.0, 1.0), 1.7);
+    let dist = length(coords);
+    return coords * (1.0 - dist) * exp(-dist) * wobble * PORTAL_WOBBLE_STRENGTH;
+}
+
+// A fixed seed, or the per-close one
+fn pattern_seed() -> u32 {
+    if u.pattern < 0.0 {
+        return noise_seed(u.seed);
+    }
+    return u32(u.pattern);
+}
+
+fn displace(coords: vec2<f32>, scale: f32) -> vec2<f32> {
+    let s = pattern_seed();
+    return vec2<f32>(
+        simplex01(coords * scale, s) - 0.5,
+        simplex01(coords * scale + vec2<f32>(7.89, 123.0), s) - 0.5,
+    );
+}
+
+fn whirled(coords: vec2<f32>, speed: f32, warp: f32) -> vec2<f32> {
+    let rotation = u.rotation_speed * u.progress * u.duration * speed;
+    let warping = u.whirling * (6.0 + 1.5 * u.progress) * warp;
+    return whirl(coords, warping, rotation);
+}
+
+fn portal_color(uv: vec2<f32>) -> vec4<f32> {
+    let base = u.color.rgb;
+    let s = pattern_seed();
+    let scale = portal_scale();
+    let coords = (uv - 0.5) * 2.0 * 1.5 / max(scale * 0.5 + 0.5, 0.01);
+    let wobble = portal_wobble(coords);
+    let detail_scale = 10000.0 / (u.width + u.height) * 0.5;
+
+    // Background gradient
+    var layer_coords = whirled(coords - wobble, 0.25, 1.0);
+    let offset = displace(layer_coords, 2.1);
+    layer_coords += offset * 0.1;
+    let dist = length(layer_coords);
+    let alpha = select(1.0, 0.0, dist > 1.0);
+    var color = vec4<f32>(mix(darken(base, 0.8), darken(base, 0.2), pow(dist, 5.0)), alpha);
+    let rand = dot(offset, offset);
+
+    // First whirled band
+    var noise = simplex01(layer_coords / detail_scale + vec2<f32>(12.3, 56.4), s);
+    color = alpha_over(color, vec4<f32>(darken(base, 0.3), select(0.0, alpha, noise > 0.6)));
+
+    // Second, faster band
+    layer_coords = whirled(coords - wobble * 1.5, 0.75, 0.5);
+    layer_coords += displace(layer_coords, 12.2) * 0.1;
+    noise = simplex01(layer_coords / detail_scale * 1.3, s);
+    color = alpha_over(color, vec4<f32>(base, select(0.0, alpha, noise > 0.6)));
+    color = clamp(color, vec4<f32>(0.0), vec4<f32>(1.0));
+
+    // Glowing rim
+    let edge = mix(1.0, 5.0, rand) * GLOW_EDGE_WIDTH * detail_scale - 150.0 * abs(dist - 1.0);
+    color = alpha_over(color, vec4<f32>(base, clamp(edge, 0.0, 1.0)));
+
+    // Sparkles
+    layer_coords = whirled(coords - wobble * 1.8, 1.25, 0.0);
+    noise = simplex01(layer_coords / detail_scale * 3.0, s);
+    let sparkle = clamp(pow(max(noise * rand + 0.9, 0.0), 50.0), 0.0, 1.0);
+    color = vec4<f32>(color.rgb + lighten(base, 0.8) * sparkle, color.a);
+
+    color.a *= pow(clamp(scale, 0.0, 1.0), 2.0);
+    return clamp(color, vec4<f32>(0.0), vec4<f32>(1.0));
+}
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let portal = portal_color(uv);
+
+    // The window tilting and shrinking into the portal
+    let fall = ease_in_back(clamp(u.progress / WINDOW_OPEN_TIME, 0.0, 1.0), 1.2);
+    var coords = uv * 2.0 - 1.0;
+    coords /= mix(1.0, WINDOW_SCALE, fall);
+    coords.y /= mix(1.0, 1.0 - 0.2 * WINDOW_SQUISH, fall);
+    coords.x /= mix(1.0, 1.0 - 0.1 * WINDOW_TILT * coords.y, fall);
+    let window_uv = coords * 0.5 + 0.5;
+
+    // Premultiplied output: the portal under the window
+    let under = vec4<f32>(portal.rgb * portal.a, portal.a);
+    if any(window_uv < vec2<f32>(0.0)) || any(window_uv > vec2<f32>(1.0)) {
+        return under;
+    }
+    let window = textureSampleLevel(tex, tex_sampler, window_uv, 0.0) * clamp((1.0 - fall) * 3.0, 0.0, 1.0);
+    return window + under * (1.0 - window.a);
+}
+"#
+        )
+    }
+}
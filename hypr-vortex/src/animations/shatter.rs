@@ -0,0 +1,191 @@
+//! Shatter animation - window cracks into shards that burst apart and fall.
+
+use std::sync::Arc;
+
+use crate::animation::{Animation, AnimationSettings, Placement, ShaderSource};
+use crate::easing::Easing;
+use crate::params::ParamSpec;
+use crate::render::{CpuRenderer, ShatterParams, ShatterRenderer};
+
+/// Tunable parameters, packed into the uniforms in this order.
+const PARAMS: &[ParamSpec] = &[
+    ParamSpec::int("shards", 4, 200, 48, "Number of pieces"),
+    ParamSpec::float("burst", 0.0, 3.0, 0.6, "Outward speed, window diagonals per second"),
+    ParamSpec::float("gravity", 0.0, 10.0, 2.5, "Fall acceleration, window diagonals per second²"),
+    ParamSpec::float("spin", 0.0, 20.0, 4.0, "Tumbling speed, radians per second"),
+];
+
+pub struct ShatterAnimation {
+    settings: AnimationSettings,
+}
+
+impl ShatterAnimation {
+    pub fn new() -> Self {
+        Self {
+            // Linear: the shards follow real time, physics supplies the curve
+            settings: AnimationSettings::new(1000, PARAMS).with_easing(Easing::Linear),
+        }
+    }
+
+    pub fn with_duration(mut self, ms: u64) -> Self {
+        self.settings.duration_ms = ms;
+        self
+    }
+}
+
+impl Default for ShatterAnimation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animation for ShatterAnimation {
+    fn name(&self) -> &'static str {
+        "shatter"
+    }
+
+    fn description(&self) -> &'static str {
+        "Window cracks like glass and the shards fly apart and fall"
+    }
+
+    fn settings(&self) -> &AnimationSettings {
+        &self.settings
+    }
+
+    fn with_settings(&self, settings: AnimationSettings) -> Arc<dyn Animation> {
+        Arc::new(Self { settings })
+    }
+
+    fn leaves_window(&self) -> bool {
+        true
+    }
+
+    fn cpu_renderer(&self, placement: &Placement, seed: u64) -> Option<Box<dyn CpuRenderer>> {
+        let window = &placement.window;
+        let params = &self.settings.params;
+        let params = ShatterParams {
+            shards: params.float("shards") as usize,
+            burst: params.float("burst"),
+            gravity: params.float("gravity"),
+            spin: params.float("spin"),
+            duration: self.settings.duration_ms as f32 / 1000.0,
+        };
+        Some(Box::new(ShatterRenderer::new(window.width, window.height, params, seed)))
+    }
+
+    fn fragment_shader(&self) -> ShaderSource {
+        r#"
+// Shatter Animation Shader
+// Voronoi shards on a jittered grid, each flying ballistically from an impact point
+
+struct Uniforms {
+    progress: f32,
+    time: f32,
+    width: f32,
+    height: f32,
+    seed: f32,
+    _pad0: f32,
+    _pad1: f32,
+    duration: f32,
+    shards: f32,
+    burst: f32,
+    gravity: f32,
+    spin: f32,
+    // Unused parameter slots
+    _pad3: array<vec4<f32>, 2>,
+}
+
+@group(0) @binding(0) var<uniform> u: Uniforms;
+@group(0) @binding(1) var tex: texture_2d<f32>;
+@group(0) @binding(2) var tex_sampler: sampler;
+
+const JITTER: f32 = 0.4;
+const CRACK_DELAY: f32 = 0.12;
+
+// Two pseudo-random values in [0, 1) for a grid cell
+fn hash2(p: vec2<f32>) -> vec2<f32> {
+    let q = vec2<f32>(dot(p, vec2<f32>(127.1, 311.7)), dot(p, vec2<f32>(269.5, 183.3)));
+    return fract(sin(q + u.seed * 91.7) * 43758.5453);
+}
+
+// Site of a grid cell, in pixels
+fn site(c: vec2<f32>, cell: vec2<f32>) -> vec2<f32> {
+    return (c + 0.5 + (hash2(c) * 2.0 - 1.0) * JITTER) * cell;
+}
+
+// Grid cell whose site is nearest to p
+fn owner(p: vec2<f32>, cell: vec2<f32>, grid: vec2<f32>) -> vec2<f32> {
+    let c = floor(p / cell);
+    var best = c;
+    var best_dist = 1e9;
+    for (var dy = -1; dy <= 1; dy++) {
+        for (var dx = -1; dx <= 1; dx++) {
+            let n = c + vec2<f32>(f32(dx), f32(dy));
+            if any(n < vec2<f32>(0.0)) || any(n >= grid) {
+                continue;
+            }
+            let d = distance(p, site(n, cell));
+            if d < best_dist {
+                best_dist = d;
+                best = n;
+            }
+        }
+    }
+    return best;
+}
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(u.width, u.height);
+    let diagonal = length(size);
+    let p = uv * size;
+
+    let cols = max(round(sqrt(u.shards * size.x / size.y)), 1.0);
+    let rows = max(ceil(u.shards / cols), 1.0);
+    let grid = vec2<f32>(cols, rows);
+    let cell = size / grid;
+
+    let impact = size * (0.35 + 0.3 * hash2(vec2<f32>(-1.0, -1.0)));
+    let reach = length(max(size - impact, impact));
+    let gravity = vec2<f32>(0.0, u.gravity * diagonal);
+    // Progress is linear, so this is seconds into the close
+    let time = u.progress * u.duration;
+
+    for (var i = 0; i < i32(cols * rows); i++) {
+        let c = vec2<f32>(f32(i % i32(cols)), f32(i / i32(cols)));
+        let center = site(c, cell);
+        let away = center - impact;
+        let closeness = 1.0 - length(away) / reach;
+        let r = hash2(c + 17.0);
+
+        // Same motion model as the CPU renderer
+        var direction = vec2<f32>(0.0, -1.0);
+        if length(away) > 0.0 {
+            direction = normalize(away);
+        }
+        let speed = u.burst * diagonal * (0.4 + 0.8 * closeness) * (0.7 + 0.6 * r.x);
+        let velocity = direction * speed - vec2<f32>(0.0, 0.3 * u.burst * diagonal * r.y);
+        let t = max(time - CRACK_DELAY * (1.0 - closeness), 0.0);
+        let offset = velocity * t + 0.5 * gravity * t * t;
+        let angle = u.spin * (r.x * 2.0 - 1.0) * t;
+
+        // Map this pixel back to where it sat before the shard moved
+        let d = p - center - offset;
+        let cs = cos(angle);
+        let sn = sin(angle);
+        let rest = center + vec2<f32>(cs * d.x + sn * d.y, -sn * d.x + cs * d.y);
+        if any(rest < vec2<f32>(0.0)) || any(rest >= size) {
+            continue;
+        }
+        if all(owner(rest, cell, grid) == c) {
+            // Premultiplied: fading scales color along with alpha
+            let fade = 1.0 - smoothstep(0.7, 1.0, u.progress);
+            return textureSampleLevel(tex, tex_sampler, rest / size, 0.0) * fade;
+        }
+    }
+
+    return vec4<f32>(0.0, 0.0, 0.0, 0.0);
+}
+"#
+    }
+}
@@ -1,14 +1,26 @@
 //! Shrink animation - window shrinks to center point.
 
-use crate::animation::{Animation, AnimationUniforms, Progress, ShaderSource};
+use std::sync::Arc;
+
+use crate::animation::{Animation, AnimationSettings, Placement, ShaderSource};
+use crate::easing::Easing;
+use crate::render::{CpuRenderer, ShrinkRenderer};
 
 pub struct ShrinkAnimation {
-    duration_ms: u64,
+    settings: AnimationSettings,
 }
 
 impl ShrinkAnimation {
     pub fn new() -> Self {
-        Self { duration_ms: 300 }
+        Self {
+            // Ease-in: starts slow, accelerates
+            settings: AnimationSettings::new(300, &[]).with_easing(Easing::In(3)),
+        }
+    }
+
+    pub fn with_duration(mut self, ms: u64) -> Self {
+        self.settings.duration_ms = ms;
+        self
     }
 }
 
@@ -27,17 +39,16 @@ impl Animation for ShrinkAnimation {
         "Shrink window to center point"
     }
 
-    fn duration_ms(&self) -> u64 {
-        self.duration_ms
+    fn settings(&self) -> &AnimationSettings {
+        &self.settings
     }
 
-    fn update_uniforms(&self, uniforms: &mut AnimationUniforms, progress: Progress) {
-        uniforms.progress = progress;
+    fn with_settings(&self, settings: AnimationSettings) -> Arc<dyn Animation> {
+        Arc::new(Self { settings })
     }
 
-    fn ease(&self, t: f32) -> f32 {
-        // Ease-in: starts slow, accelerates
-        t * t * t
+    fn cpu_renderer(&self, _placement: &Placement, _seed: u64) -> Option<Box<dyn CpuRenderer>> {
+        Some(Box::new(ShrinkRenderer::new()))
     }
 
     fn fragment_shader(&self) -> ShaderSource {
@@ -47,10 +58,12 @@ struct Uniforms {
     time: f32,
     width: f32,
     height: f32,
+    seed: f32,
+    _pad0: f32,
     _pad1: f32,
     _pad2: f32,
-    _pad3: f32,
-    _pad4: f32,
+    // No parameters; fills the rest of AnimationUniforms
+    _params: array<vec4<f32>, 3>,
 }
 
 @group(0) @binding(0) var<uniform> u: Uniforms;
@@ -76,7 +89,8 @@ fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
     var color = textureSample(tex, tex_sampler, scaled_uv);
 
     // Fade out near the end
-    color.a = color.a * (1.0 - smoothstep(0.7, 1.0, progress));
+    // Premultiplied: fading scales color along with alpha
+    color = color * (1.0 - smoothstep(0.7, 1.0, progress));
 
     return color;
 }
@@ -0,0 +1,135 @@
+//! Slide animation - window slides off the screen through its nearest edge,
+//! or in a set direction.
+
+use std::sync::Arc;
+
+use glam::Vec2;
+
+use crate::animation::{Animation, AnimationSettings, Placement, ShaderSource};
+use crate::easing::Easing;
+use crate::params::ParamSpec;
+use crate::render::{CpuRenderer, SlideRenderer};
+
+/// Tunable parameters, packed into the uniforms in this order.
+const PARAMS: &[ParamSpec] = &[
+    ParamSpec::float(
+        "direction",
+        -1.0,
+        360.0,
+        -1.0,
+        "Degrees clockwise from rightward; -1 leaves by the nearest monitor edge",
+    ),
+    ParamSpec::float("blur", 0.0, 1.0, 0.5, "Motion blur along the path"),
+];
+
+pub struct SlideAnimation {
+    settings: AnimationSettings,
+}
+
+impl SlideAnimation {
+    pub fn new() -> Self {
+        Self {
+            // Ease-in: picks up speed on its way out
+            settings: AnimationSettings::new(450, PARAMS).with_easing(Easing::In(2)),
+        }
+    }
+
+    pub fn with_duration(mut self, ms: u64) -> Self {
+        self.settings.duration_ms = ms;
+        self
+    }
+}
+
+impl Default for SlideAnimation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Animation for SlideAnimation {
+    fn name(&self) -> &'static str {
+        "slide"
+    }
+
+    fn description(&self) -> &'static str {
+        "Window slides off the screen through its nearest edge, or in a set direction"
+    }
+
+    fn settings(&self) -> &AnimationSettings {
+        &self.settings
+    }
+
+    fn with_settings(&self, settings: AnimationSettings) -> Arc<dyn Animation> {
+        Arc::new(Self { settings })
+    }
+
+    fn leaves_window(&self) -> bool {
+        true
+    }
+
+    fn exit(&self, placement: &Placement) -> Vec2 {
+        let direction = self.settings.params.float("direction");
+        if direction < 0.0 {
+            placement.nearest_exit()
+        } else {
+            // Screen y points down, so increasing angles turn clockwise
+            placement.exit_along(Vec2::from_angle(direction.to_radians()))
+        }
+    }
+
+    fn cpu_renderer(&self, placement: &Placement, _seed: u64) -> Option<Box<dyn CpuRenderer>> {
+        let blur = self.settings.params.float("blur");
+        Some(Box::new(SlideRenderer::new(self.exit(placement), blur)))
+    }
+
+    fn fragment_shader(&self) -> ShaderSource {
+        r#"
+// Slide Animation Shader
+// The window travels off the monitor with a motion-blurred trail
+
+struct Uniforms {
+    progress: f32,
+    time: f32,
+    width: f32,
+    height: f32,
+    seed: f32,
+    exit_x: f32,
+    exit_y: f32,
+    _pad0: f32,
+    direction: f32,
+    blur: f32,
+    // Unused parameter slots
+    _pad1: f32,
+    _pad2: f32,
+    _pad3: vec4<f32>,
+    _pad4: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> u: Uniforms;
+@group(0) @binding(1) var tex: texture_2d<f32>;
+@group(0) @binding(2) var tex_sampler: sampler;
+
+const STREAK: f32 = 0.1;
+const SAMPLES: i32 = 16;
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    // The whole trip in window sizes: off through the nearest edge, or
+    // along the set angle
+    let offset = vec2<f32>(u.exit_x, u.exit_y) * u.progress;
+    let streak = offset * u.blur * STREAK * sqrt(u.progress);
+
+    var color = vec4<f32>(0.0);
+    for (var i = 0; i < SAMPLES; i++) {
+        // Copies trail behind the window, back toward where it was
+        let t = (f32(i) + 0.5) / f32(SAMPLES);
+        let source = uv - offset + streak * t;
+        if all(source >= vec2<f32>(0.0)) && all(source < vec2<f32>(1.0)) {
+            color += textureSampleLevel(tex, tex_sampler, source, 0.0);
+        }
+    }
+    return color / f32(SAMPLES);
+}
+"#
+    }
+}
@@ -1,37 +1,32 @@
 //! Vortex/Black Hole animation - sucks window into a spinning void.
 
-use crate::animation::{Animation, AnimationUniforms, Progress, ShaderSource};
+use std::sync::Arc;
+
+use crate::animation::{Animation, AnimationSettings, Placement, ShaderSource};
+use crate::easing::Easing;
+use crate::params::ParamSpec;
+use crate::render::{CpuRenderer, VortexParams, VortexRenderer};
+
+/// Tunable parameters, packed into the uniforms in this order.
+const PARAMS: &[ParamSpec] = &[
+    ParamSpec::float("spin_speed", 0.0, 20.0, 3.0, "Rotation speed multiplier"),
+    ParamSpec::float("pull_strength", 0.0, 10.0, 2.0, "How quickly it shrinks to center"),
+];
 
 pub struct VortexAnimation {
-    /// Animation duration in ms
-    duration_ms: u64,
-    /// Rotation speed multiplier
-    spin_speed: f32,
-    /// How quickly it shrinks to center
-    pull_strength: f32,
+    settings: AnimationSettings,
 }
 
 impl VortexAnimation {
     pub fn new() -> Self {
         Self {
-            duration_ms: 900,  // Slow start, violent acceleration
-            spin_speed: 3.0,
-            pull_strength: 2.0,
+            // Slow start, violent acceleration
+            settings: AnimationSettings::new(900, PARAMS).with_easing(Easing::InOut(3)),
         }
     }
 
     pub fn with_duration(mut self, ms: u64) -> Self {
-        self.duration_ms = ms;
-        self
-    }
-
-    pub fn with_spin_speed(mut self, speed: f32) -> Self {
-        self.spin_speed = speed;
-        self
-    }
-
-    pub fn with_pull_strength(mut self, strength: f32) -> Self {
-        self.pull_strength = strength;
+        self.settings.duration_ms = ms;
         self
     }
 }
@@ -51,23 +46,21 @@ impl Animation for VortexAnimation {
         "Black hole effect that sucks and spins the window into the void"
     }
 
-    fn duration_ms(&self) -> u64 {
-        self.duration_ms
+    fn settings(&self) -> &AnimationSettings {
+        &self.settings
     }
 
-    fn update_uniforms(&self, uniforms: &mut AnimationUniforms, progress: Progress) {
-        uniforms.progress = progress;
-        uniforms.param1 = self.spin_speed;
-        uniforms.param2 = self.pull_strength;
+    fn with_settings(&self, settings: AnimationSettings) -> Arc<dyn Animation> {
+        Arc::new(Self { settings })
     }
 
-    fn ease(&self, t: f32) -> f32 {
-        // Ease-in-out for vortex - slow start, fast middle, slow end
-        if t < 0.5 {
-            4.0 * t * t * t
-        } else {
-            1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
-        }
+    fn cpu_renderer(&self, _placement: &Placement, seed: u64) -> Option<Box<dyn CpuRenderer>> {
+        let params = &self.settings.params;
+        let params = VortexParams {
+            spin_speed: params.float("spin_speed"),
+            pull_strength: params.float("pull_strength"),
+        };
+        Some(Box::new(VortexRenderer::new(params, seed)))
     }
 
     fn fragment_shader(&self) -> ShaderSource {
@@ -80,10 +73,15 @@ struct Uniforms {
     time: f32,
     width: f32,
     height: f32,
-    spin_speed: f32,
-    pull_strength: f32,
+    seed: f32,
+    _pad0: f32,
     _pad1: f32,
     _pad2: f32,
+    spin_speed: f32,
+    pull_strength: f32,
+    // Unused parameter slots
+    _pad3: vec2<f32>,
+    _pad4: array<vec4<f32>, 2>,
 }
 
 @group(0) @binding(0) var<uniform> u: Uniforms;
@@ -136,12 +134,12 @@ fn fs_main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
     let fade_progress = progress * 1.5; // Fade starts at ~66% progress
     let fade = 1.0 - smoothstep(0.0, 1.0, fade_progress - center_dist);
 
-    // Final alpha combines texture alpha with fade
-    color.a = color.a * fade;
+    // Final alpha combines texture alpha with fade (premultiplied, so color fades too)
+    color = color * fade;
 
     // Clamp UVs - pixels outside original texture are transparent
     if new_uv.x < 0.0 || new_uv.x > 1.0 || new_uv.y < 0.0 || new_uv.y > 1.0 {
-        color.a = 0.0;
+        color = vec4<f32>(0.0);
     }
 
     return color;
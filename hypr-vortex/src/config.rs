@@ -0,0 +1,443 @@
+//! User configuration from `$XDG_CONFIG_HOME/hypr-vortex/config.toml`.
+//!
+//! Every key is optional; a missing file means built-in defaults. Unknown
+//! keys are rejected so typos don't silently do nothing. `[animations.*]`
+//! sections take `duration_ms`, `easing` and the parameters the animation
+//! declares (`hypr-vortex list` shows them). An easing is a preset, one of
+//! Hyprland's `bezier` names, one from `[beziers]`, or an inline
+//! `[x0, y0, x1, y1]`. Animations written in WGSL are loaded from the
+//! `shaders` directory next to this file (see `animations::CustomAnimation`).
+//! Wherever an animation is named, `random` and `cycle` pick one per close.
+//! Opening windows play the open animation backwards; window rules pick for
+//! opens as they do for closes.
+//!
+//! ```toml
+//! default_animation = "vortex"
+//! open_animation = "fade"    # unset: default_animation, "none": Hyprland's own
+//! socket_path = "/tmp/hypr-vortex.sock"
+//! capture = "ppm"            # opaque; "png" for translucent windows
+//! hyprland_beziers = true    # import Hyprland's bezier definitions
+//! random = { vortex = 3, fade = 1 }      # weights for "random"
+//! cycle = ["vortex", "shrink", "fade"]   # order for "cycle"
+//!
+//! [beziers]
+//! overshot = [0.05, 0.9, 0.1, 1.05]
+//!
+//! [animations.vortex]
+//! duration_ms = 900
+//! easing = "overshot"
+//! spin_speed = 3.0
+//! pull_strength = 2.0
+//!
+//! [animations.fade]
+//! duration_ms = 200
+//!
+//! [[rules]]
+//! match = "class:^(kitty)$"
+//! animation = "fade"
+//! params = { duration_ms = 120 }
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use tracing::{error, info, warn};
+
+use crate::animation::{Animation, AnimationRegistry, CYCLE, RANDOM};
+use crate::animations::{self, CustomAnimation};
+use crate::easing::{CubicBezier, Easing, EasingLibrary};
+use crate::rules::{Rule, RuleAction, Rules, WindowMatcher, NO_ANIMATION};
+
+/// Socket path when the config doesn't set one.
+pub const DEFAULT_SOCKET_PATH: &str = "/tmp/hypr-vortex.sock";
+
+/// How screenshots are taken.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureBackend {
+    /// grim raw PPM on stdout, with PNG as the fallback. Always opaque: a
+    /// translucent window comes with the wallpaper behind it baked in
+    #[default]
+    Ppm,
+    /// grim PNG through a temp file (keeps alpha, slower)
+    Png,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Animation used when a request doesn't name one, or `random` / `cycle`
+    pub default_animation: Option<String>,
+    /// Animation played backwards when a window opens, or `random` / `cycle`;
+    /// unset means `default_animation`, and `none` leaves opening to Hyprland
+    pub open_animation: Option<String>,
+    /// Unix socket the close script talks to (read at startup only)
+    pub socket_path: PathBuf,
+    pub capture: CaptureBackend,
+    /// Whether Hyprland's `bezier` curves can be used as easings
+    pub hyprland_beziers: bool,
+    /// Extra named curves as `[x0, y0, x1, y1]`, replacing Hyprland's of the same name
+    pub beziers: BTreeMap<String, [f32; 4]>,
+    /// Weights for the `random` selector; empty means every animation equally
+    pub random: BTreeMap<String, f32>,
+    /// Order for the `cycle` selector; empty means every animation by name
+    pub cycle: Vec<String>,
+    /// Overrides per animation name: `duration_ms`, `easing` or any parameter in its schema
+    pub animations: BTreeMap<String, toml::Table>,
+    /// Per-window overrides, see `rules`
+    pub rules: Vec<RuleConfig>,
+    /// Curves `easing` can name, filled in by `load`
+    #[serde(skip)]
+    easings: EasingLibrary,
+    /// Valid animations from the shaders directory, filled in by `load`
+    #[serde(skip)]
+    shaders: Vec<Arc<CustomAnimation>>,
+}
+
+/// One `[[rules]]` entry.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RuleConfig {
+    /// `windowrulev2`-style conditions, e.g. `"class:^(kitty)$, floating:1"`
+    #[serde(rename = "match")]
+    pub matches: String,
+    /// Animation to play, `"random"` / `"cycle"`, or `"none"` to close without one
+    pub animation: String,
+    /// Overrides layered over the `[animations.*]` section, same keys
+    #[serde(default)]
+    pub params: toml::Table,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            default_animation: None,
+            open_animation: None,
+            socket_path: PathBuf::from(DEFAULT_SOCKET_PATH),
+            capture: CaptureBackend::default(),
+            hyprland_beziers: true,
+            beziers: BTreeMap::new(),
+            random: BTreeMap::new(),
+            cycle: Vec::new(),
+            animations: BTreeMap::new(),
+            rules: Vec::new(),
+            easings: EasingLibrary::new(),
+            shaders: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// `$XDG_CONFIG_HOME/hypr-vortex`, falling back to `~/.config/hypr-vortex`.
+    pub fn dir() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(base.join("hypr-vortex"))
+    }
+
+    /// Path of the config file.
+    pub fn path() -> Option<PathBuf> {
+        Self::dir().map(|dir| dir.join("config.toml"))
+    }
+
+    /// Directory of user WGSL animations.
+    pub fn shader_dir() -> Option<PathBuf> {
+        Self::dir().map(|dir| dir.join("shaders"))
+    }
+
+    /// Load and validate the config at `path`; a missing file gives the defaults.
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut config: Self = match std::fs::read_to_string(path) {
+            // toml's error already points at the line and column
+            Ok(text) => toml::from_str(&text)
+                .with_context(|| format!("Invalid config {}", path.display()))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+
+        if config.hyprland_beziers {
+            config.easings.import_hyprland();
+        }
+        for (name, [x0, y0, x1, y1]) in &config.beziers {
+            if let Some(curve) = CubicBezier::new(*x0, *y0, *x1, *y1) {
+                config.easings.insert(name.clone(), Easing::Bezier(curve));
+            }
+        }
+        if let Some(dir) = Self::shader_dir() {
+            config.load_shaders(&dir);
+        }
+
+        config
+            .validate()
+            .with_context(|| format!("Invalid config {}", path.display()))?;
+        Ok(config)
+    }
+
+    /// Load every shader in `dir`. A broken shader is logged and left out
+    /// rather than failing the whole config.
+    fn load_shaders(&mut self, dir: &Path) {
+        let mut builtin = AnimationRegistry::new();
+        animations::register_all(&mut builtin);
+
+        self.shaders.clear();
+        for result in animations::load_dir(dir, &self.easings) {
+            let animation = match result {
+                Ok(animation) => animation,
+                Err(e) => {
+                    error!("Skipping shader: {:#}", e);
+                    continue;
+                }
+            };
+            let name = animation.name();
+            if builtin.get(name).is_some() || [NO_ANIMATION, RANDOM, CYCLE].contains(&name) {
+                error!("Skipping shader '{}': the name is taken by a built-in animation", name);
+                continue;
+            }
+            if self.shaders.iter().any(|shader| shader.name() == name) {
+                warn!("Shader '{}' is defined twice, using the later file", name);
+                self.shaders.retain(|shader| shader.name() != name);
+            }
+            self.shaders.push(Arc::new(animation));
+        }
+        if !self.shaders.is_empty() {
+            info!("Loaded {} shader animation(s) from {}", self.shaders.len(), dir.display());
+        }
+    }
+
+    /// Curves an `easing` setting can name.
+    pub fn easings(&self) -> &EasingLibrary {
+        &self.easings
+    }
+
+    /// Check values the TOML types alone can't rule out, reporting all of them at once.
+    pub fn validate(&self) -> Result<()> {
+        let mut problems = Vec::new();
+
+        for (name, [x0, y0, x1, y1]) in &self.beziers {
+            if CubicBezier::new(*x0, *y0, *x1, *y1).is_none() {
+                problems.push(format!("beziers.{}: x0 and x1 must be within 0..1", name));
+            }
+        }
+
+        let registry = self.configured_registry(&mut problems);
+        if let Some(name) = &self.default_animation {
+            if registry.static_name(name).is_none() {
+                let problem = check_animation_name(&registry, name).err().unwrap_or_default();
+                problems.push(format!("default_animation: {} (or random, cycle)", problem));
+            }
+        }
+        if let Some(name) = &self.open_animation {
+            if name != NO_ANIMATION && registry.static_name(name).is_none() {
+                let problem = check_animation_name(&registry, name).err().unwrap_or_default();
+                problems.push(format!("open_animation: {} (or random, cycle, none)", problem));
+            }
+        }
+        for (name, weight) in &self.random {
+            if let Err(problem) = check_animation_name(&registry, name) {
+                problems.push(format!("random.{}: {}", name, problem));
+            } else if !(weight.is_finite() && *weight > 0.0) {
+                problems.push(format!("random.{}: weight must be positive, got {}", name, weight));
+            }
+        }
+        for (index, name) in self.cycle.iter().enumerate() {
+            if let Err(problem) = check_animation_name(&registry, name) {
+                problems.push(format!("cycle[{}]: {}", index, problem));
+            }
+        }
+        for (index, rule) in self.rules.iter().enumerate() {
+            if let Err(e) = compile_rule(&registry, &self.easings, rule) {
+                problems.push(format!("rules[{}]: {:#}", index, e));
+            }
+        }
+
+        if problems.is_empty() {
+            return Ok(());
+        }
+        let mut message = String::new();
+        for problem in &problems {
+            let _ = write!(message, "\n  - {}", problem);
+        }
+        anyhow::bail!("{} problem(s):{}", problems.len(), message)
+    }
+
+    /// Registry with every built-in and shader animation, configured from this file.
+    pub fn build_registry(&self) -> Result<AnimationRegistry> {
+        self.validate()?;
+
+        let mut registry = self.configured_registry(&mut Vec::new());
+
+        // Names handed out by the registry are 'static, config strings are not
+        let pool = self
+            .random
+            .iter()
+            .filter_map(|(name, weight)| Some((registry.static_name(name)?, *weight)))
+            .collect();
+        registry.set_random_pool(pool);
+        let order = self.cycle.iter().filter_map(|name| registry.static_name(name)).collect();
+        registry.set_cycle(order);
+        if let Some(name) = self.default_animation.as_deref() {
+            if let Some(name) = registry.static_name(name) {
+                registry.set_default(name);
+            }
+        }
+        Ok(registry)
+    }
+
+    /// Compiled window rules, in file order.
+    pub fn build_rules(&self) -> Result<Rules> {
+        let registry = self.configured_registry(&mut Vec::new());
+        self.rules
+            .iter()
+            .map(|rule| compile_rule(&registry, &self.easings, rule))
+            .collect::<Result<Vec<_>>>()
+            .map(Rules::new)
+    }
+
+    /// Built-in and shader animations with every valid `[animations.*]`
+    /// section applied; invalid sections are reported to `problems` and left
+    /// at their defaults.
+    fn configured_registry(&self, problems: &mut Vec<String>) -> AnimationRegistry {
+        let mut registry = AnimationRegistry::new();
+        animations::register_all(&mut registry);
+        for shader in &self.shaders {
+            registry.insert(shader.clone());
+        }
+
+        for (name, table) in &self.animations {
+            let Some(animation) = registry.get(name) else {
+                if let Err(problem) = check_animation_name(&registry, name) {
+                    problems.push(format!("animations.{}: {}", name, problem));
+                }
+                continue;
+            };
+            match animation.configured(&to_json(table), &self.easings) {
+                Ok(configured) => registry.insert(configured),
+                Err(e) => problems.push(format!("animations.{}: {}", name, e)),
+            }
+        }
+        registry
+    }
+}
+
+/// Parse a rule's conditions and build its animation with the rule's
+/// params layered over the configured one.
+fn compile_rule(
+    registry: &AnimationRegistry,
+    easings: &EasingLibrary,
+    rule: &RuleConfig,
+) -> Result<Rule> {
+    let matcher = WindowMatcher::parse(&rule.matches).context("match")?;
+
+    if rule.animation == NO_ANIMATION {
+        if !rule.params.is_empty() {
+            anyhow::bail!("params: animation 'none' takes no parameters");
+        }
+        return Ok(Rule {
+            matcher,
+            action: RuleAction::Skip,
+        });
+    }
+
+    if let Some(selector) = [RANDOM, CYCLE].into_iter().find(|s| *s == rule.animation) {
+        if !rule.params.is_empty() {
+            anyhow::bail!(
+                "params: '{}' picks different animations, so it takes no parameters",
+                selector
+            );
+        }
+        return Ok(Rule {
+            matcher,
+            action: RuleAction::Pick(selector),
+        });
+    }
+
+    let animation = match registry.get(&rule.animation) {
+        Some(animation) => animation,
+        None => {
+            let problem = check_animation_name(registry, &rule.animation).err().unwrap_or_default();
+            anyhow::bail!("animation: {}", problem);
+        }
+    };
+    let animation = animation
+        .configured(&to_json(&rule.params), easings)
+        .context("params")?;
+    Ok(Rule {
+        matcher,
+        action: RuleAction::Animate(animation),
+    })
+}
+
+/// TOML table as a JSON object, so config and request overrides share one parser.
+fn to_json(table: &toml::Table) -> Map<String, Value> {
+    table
+        .iter()
+        .filter_map(|(k, v)| serde_json::to_value(v).ok().map(|v| (k.clone(), v)))
+        .collect()
+}
+
+/// `Ok` if `known` has an animation called `name`, else a message listing them.
+fn check_animation_name(known: &AnimationRegistry, name: &str) -> std::result::Result<(), String> {
+    if known.get(name).is_some() {
+        return Ok(());
+    }
+    let mut available = known.list();
+    available.sort_unstable();
+    Err(format!(
+        "unknown animation '{}' (available: {})",
+        name,
+        available.join(", ")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_reports_every_problem_at_once() {
+        let config: Config = toml::from_str(
+            r#"
+            default_animation = "vortexx"
+            random = { fade = 0 }
+            cycle = ["fade", "nope"]
+
+            [beziers]
+            wild = [1.5, 0.0, 0.2, 1.0]
+
+            [animations.fade]
+            duration_ms = "slow"
+
+            [[rules]]
+            match = "class:^(kitty)$"
+            animation = "missing"
+            "#,
+        )
+        .unwrap();
+
+        let message = config.validate().unwrap_err().to_string();
+        assert!(message.starts_with("6 problem(s):"), "{message}");
+        for key in [
+            "beziers.wild",
+            "default_animation",
+            "random.fade",
+            "cycle[1]",
+            "animations.fade",
+            "rules[0]",
+        ] {
+            assert!(message.contains(&format!("  - {}:", key)), "{key} missing from {message}");
+        }
+        assert!(!message.contains("cycle[0]"), "{message}");
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        Config::default().validate().unwrap();
+    }
+}
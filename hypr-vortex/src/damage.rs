@@ -0,0 +1,130 @@
+//! Rectangles for damage tracking in the SHM path.
+
+/// Axis-aligned pixel rectangle in surface coordinates.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Rect {
+    pub const EMPTY: Rect = Rect {
+        x: 0,
+        y: 0,
+        width: 0,
+        height: 0,
+    };
+
+    pub fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0 || self.height <= 0
+    }
+
+    pub fn right(&self) -> i32 {
+        self.x + self.width
+    }
+
+    pub fn bottom(&self) -> i32 {
+        self.y + self.height
+    }
+
+    /// Smallest rectangle containing both.
+    pub fn union(&self, other: &Rect) -> Rect {
+        if self.is_empty() {
+            return *other;
+        }
+        if other.is_empty() {
+            return *self;
+        }
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        Rect::new(
+            x,
+            y,
+            self.right().max(other.right()) - x,
+            self.bottom().max(other.bottom()) - y,
+        )
+    }
+
+    /// Overlapping area, or `Rect::EMPTY` if they don't overlap.
+    pub fn intersect(&self, other: &Rect) -> Rect {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+        if right <= x || bottom <= y {
+            return Rect::EMPTY;
+        }
+        Rect::new(x, y, right - x, bottom - y)
+    }
+}
+
+/// Zero the pixels of `rect` in a 4-bytes-per-pixel canvas.
+pub fn clear_rect(canvas: &mut [u8], stride: usize, rect: &Rect) {
+    if rect.is_empty() {
+        return;
+    }
+    let start = rect.x as usize * 4;
+    let end = rect.right() as usize * 4;
+    for y in rect.y as usize..rect.bottom() as usize {
+        canvas[y * stride + start..y * stride + end].fill(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn union_covers_both() {
+        let a = Rect::new(0, 0, 10, 10);
+        let b = Rect::new(20, 5, 10, 20);
+        assert_eq!(a.union(&b), Rect::new(0, 0, 30, 25));
+        assert_eq!(b.union(&a), a.union(&b));
+        assert_eq!(a.union(&Rect::new(2, 2, 3, 3)), a);
+    }
+
+    #[test]
+    fn union_ignores_empty_rects() {
+        let a = Rect::new(5, 5, 10, 10);
+        // Empty rects anywhere, even far away, don't stretch the union
+        for empty in [Rect::EMPTY, Rect::new(100, 100, 0, 5), Rect::new(-50, 0, 5, -1)] {
+            assert_eq!(a.union(&empty), a);
+            assert_eq!(empty.union(&a), a);
+        }
+    }
+
+    #[test]
+    fn intersect_is_the_overlap() {
+        let a = Rect::new(0, 0, 10, 10);
+        assert_eq!(a.intersect(&Rect::new(5, -5, 10, 10)), Rect::new(5, 0, 5, 5));
+        assert_eq!(a.intersect(&Rect::new(2, 2, 3, 3)), Rect::new(2, 2, 3, 3));
+        // Touching edges share no pixels
+        assert_eq!(a.intersect(&Rect::new(10, 0, 5, 5)), Rect::EMPTY);
+        assert_eq!(a.intersect(&Rect::new(30, 30, 5, 5)), Rect::EMPTY);
+        assert_eq!(a.intersect(&Rect::EMPTY), Rect::EMPTY);
+    }
+
+    #[test]
+    fn clear_rect_zeroes_only_the_rect() {
+        let mut canvas = vec![255u8; 4 * 4 * 4];
+        clear_rect(&mut canvas, 16, &Rect::new(1, 1, 2, 2));
+        for y in 0..4 {
+            for x in 0..4 {
+                let inside = (1..3).contains(&x) && (1..3).contains(&y);
+                assert_eq!(canvas[y * 16 + x * 4], if inside { 0 } else { 255 }, "{x},{y}");
+            }
+        }
+        clear_rect(&mut canvas, 16, &Rect::EMPTY);
+    }
+}
@@ -0,0 +1,266 @@
+//! Window decorations (rounded corners, border, shadow) around the snapshot.
+//!
+//! The client only reports the window's content rectangle. Hyprland draws the
+//! border and shadow outside it and clips the corners, so the capture is
+//! expanded to cover them and then masked: corners become transparent and the
+//! shadow band (which has the wallpaper baked in) is redrawn from the
+//! configured shadow color, so nothing from behind the window gets animated.
+
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::animation::WindowGeometry;
+use crate::pixel::{Image, PixelFormat};
+
+/// Hyprland's decoration settings, in logical pixels.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Decoration {
+    /// Corner radius of the window content (`decoration:rounding`)
+    pub rounding: u32,
+    /// Border width (`general:border_size`)
+    pub border_size: u32,
+    /// How far the shadow reaches past the border; 0 when shadows are off
+    pub shadow_range: u32,
+    /// Falloff exponent (`render_power`, 1..=4)
+    pub shadow_power: u32,
+    /// Shadow color as Hyprland stores it: 0xAARRGGBB, straight alpha
+    pub shadow_color: u32,
+}
+
+/// `hyprctl -j getoption` reply; only the fields we read.
+#[derive(Deserialize)]
+struct OptionValue {
+    int: Option<i64>,
+}
+
+impl Decoration {
+    /// Read the current settings from Hyprland.
+    ///
+    /// Shadow options moved under `decoration:shadow:` in Hyprland 0.45; the
+    /// older flat names are tried when the new ones don't exist.
+    pub fn query() -> Result<Self> {
+        // Independent round trips, so run them side by side
+        let names: [&[&str]; 6] = [
+            &["decoration:rounding"],
+            &["general:border_size"],
+            &["decoration:shadow:enabled", "decoration:drop_shadow"],
+            &["decoration:shadow:range", "decoration:shadow_range"],
+            &[
+                "decoration:shadow:render_power",
+                "decoration:shadow_render_power",
+            ],
+            &["decoration:shadow:color", "decoration:col.shadow"],
+        ];
+        let values: Vec<Option<i64>> = std::thread::scope(|scope| {
+            let handles: Vec<_> = names
+                .iter()
+                .map(|candidates| {
+                    scope.spawn(move || candidates.iter().find_map(|name| get_option(name)))
+                })
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().ok().flatten())
+                .collect()
+        });
+
+        let rounding = values[0].context("Failed to read decoration:rounding")?;
+        let border_size = values[1].context("Failed to read general:border_size")?;
+        let shadow_enabled = values[2].unwrap_or(0) != 0;
+
+        let decoration = Self {
+            rounding: rounding.max(0) as u32,
+            border_size: border_size.max(0) as u32,
+            shadow_range: if shadow_enabled {
+                values[3].unwrap_or(0).max(0) as u32
+            } else {
+                0
+            },
+            shadow_power: values[4].unwrap_or(3).clamp(1, 4) as u32,
+            shadow_color: values[5].unwrap_or(0xee1a_1a1a) as u32,
+        };
+        debug!("Decoration: {:?}", decoration);
+        Ok(decoration)
+    }
+
+    /// Logical pixels the decorations reach past the content rectangle.
+    pub fn margin(&self) -> u32 {
+        self.border_size + self.shadow_range
+    }
+
+    /// Content rectangle grown to cover the border and shadow.
+    pub fn expand(&self, geometry: &WindowGeometry) -> WindowGeometry {
+        let margin = self.margin();
+        WindowGeometry {
+            x: geometry.x - margin as i32,
+            y: geometry.y - margin as i32,
+            width: geometry.width + margin * 2,
+            height: geometry.height + margin * 2,
+        }
+    }
+
+    /// Pad a capture of the bare content rectangle, like a toplevel export, out
+    /// to the expanded rectangle so `apply` can draw the shadow around it.
+    ///
+    /// The border stays transparent: its colors can't be read back as numbers.
+    pub fn surround(&self, image: Image, content: &WindowGeometry) -> Image {
+        let image = image.into_format(PixelFormat::Rgba8Premultiplied);
+        if self.margin() == 0 {
+            return image;
+        }
+
+        let scale = image.width as f32 / content.width.max(1) as f32;
+        let pad = (self.margin() as f32 * scale).round() as usize;
+        let width = image.width + pad * 2;
+        let height = image.height + pad * 2;
+        let row_len = image.width * PixelFormat::BYTES_PER_PIXEL;
+
+        let mut pixels = vec![0; width * height * PixelFormat::BYTES_PER_PIXEL];
+        for (y, row) in image.pixels.chunks_exact(row_len).enumerate() {
+            let start = ((y + pad) * width + pad) * PixelFormat::BYTES_PER_PIXEL;
+            pixels[start..start + row_len].copy_from_slice(row);
+        }
+        Image::new(pixels, width, height, PixelFormat::Rgba8Premultiplied)
+    }
+
+    /// Clip a capture of the expanded rectangle to the window's shape.
+    ///
+    /// Works at the image's own resolution, which is higher than the logical
+    /// size on scaled outputs.
+    pub fn apply(&self, image: Image, expanded: &WindowGeometry) -> Image {
+        let mut image = image.into_format(PixelFormat::Rgba8Premultiplied);
+        if self.rounding == 0 && self.margin() == 0 {
+            return image;
+        }
+
+        let scale = image.width as f32 / expanded.width.max(1) as f32;
+        let margin = self.margin() as f32;
+
+        // The border follows the content's corners, so its radius grows with it
+        let half_w = (expanded.width as f32 - margin * 2.0) / 2.0 + self.border_size as f32;
+        let half_h = (expanded.height as f32 - margin * 2.0) / 2.0 + self.border_size as f32;
+        let radius = if self.rounding > 0 {
+            (self.rounding + self.border_size) as f32
+        } else {
+            0.0
+        }
+        .min(half_w)
+        .min(half_h);
+        let center_x = expanded.width as f32 / 2.0;
+        let center_y = expanded.height as f32 / 2.0;
+
+        let shadow = premultiplied_color(self.shadow_color);
+        let shadow_range = self.shadow_range as f32;
+        let shadow_power = self.shadow_power as i32;
+        let width = image.width;
+
+        image
+            .pixels
+            .par_chunks_mut(width * PixelFormat::BYTES_PER_PIXEL)
+            .enumerate()
+            .for_each(|(y, row)| {
+                let py = (y as f32 + 0.5) / scale - center_y;
+                for (x, px) in row
+                    .chunks_exact_mut(PixelFormat::BYTES_PER_PIXEL)
+                    .enumerate()
+                {
+                    let px_x = (x as f32 + 0.5) / scale - center_x;
+                    let dist = rounded_rect_distance(px_x, py, half_w, half_h, radius);
+
+                    // Antialiased over one image pixel
+                    let coverage = (0.5 - dist * scale).clamp(0.0, 1.0);
+                    if coverage >= 1.0 {
+                        continue;
+                    }
+
+                    let shadow_alpha = if shadow_range > 0.0 {
+                        (1.0 - dist.max(0.0) / shadow_range)
+                            .clamp(0.0, 1.0)
+                            .powi(shadow_power)
+                    } else {
+                        0.0
+                    };
+
+                    // Window over its shadow, premultiplied
+                    for c in 0..4 {
+                        let window = px[c] as f32 * coverage;
+                        let behind = shadow[c] * shadow_alpha * (1.0 - coverage);
+                        px[c] = (window + behind).round().min(255.0) as u8;
+                    }
+                }
+            });
+
+        image
+    }
+}
+
+/// Signed distance from a point to a rounded rectangle centered on the origin.
+#[inline]
+fn rounded_rect_distance(x: f32, y: f32, half_w: f32, half_h: f32, radius: f32) -> f32 {
+    let qx = x.abs() - (half_w - radius);
+    let qy = y.abs() - (half_h - radius);
+    let outside = qx.max(0.0).hypot(qy.max(0.0));
+    let inside = qx.max(qy).min(0.0);
+    outside + inside - radius
+}
+
+/// 0xAARRGGBB to premultiplied RGBA in `0.0..=255.0`.
+fn premultiplied_color(argb: u32) -> [f32; 4] {
+    let [a, r, g, b] = argb.to_be_bytes();
+    let alpha = a as f32 / 255.0;
+    [
+        r as f32 * alpha,
+        g as f32 * alpha,
+        b as f32 * alpha,
+        a as f32,
+    ]
+}
+
+/// Integer value of a Hyprland option, or `None` if it doesn't exist.
+fn get_option(name: &str) -> Option<i64> {
+    let output = Command::new("hyprctl")
+        .args(["-j", "getoption", name])
+        .output()
+        .ok()?;
+    // Unknown options reply with plain text, which fails to parse
+    serde_json::from_slice::<OptionValue>(&output.stdout)
+        .ok()?
+        .int
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surround_centers_the_content_at_the_image_scale() {
+        let decoration = Decoration {
+            border_size: 1,
+            shadow_range: 1,
+            ..Decoration::default()
+        };
+        // A 2x1 window captured at scale 2
+        let content = WindowGeometry {
+            x: 10,
+            y: 10,
+            width: 2,
+            height: 1,
+        };
+        let image = Image::new(vec![255; 4 * 2 * 4], 4, 2, PixelFormat::Rgba8Premultiplied);
+        let image = decoration.surround(image, &content);
+
+        let expanded = decoration.expand(&content);
+        assert_eq!(image.width, expanded.width as usize * 2);
+        assert_eq!(image.height, expanded.height as usize * 2);
+        let alpha = |x: usize, y: usize| image.pixels[(y * image.width + x) * 4 + 3];
+        assert_eq!(alpha(3, 3), 0);
+        assert_eq!(alpha(4, 4), 255);
+        assert_eq!(alpha(7, 5), 255);
+        assert_eq!(alpha(8, 5), 0);
+        assert_eq!(alpha(4, 6), 0);
+    }
+}
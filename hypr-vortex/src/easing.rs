@@ -0,0 +1,547 @@
+//! Easing curves: Hyprland-style cubic beziers plus a few named presets.
+//!
+//! Curves are looked up by name in an `EasingLibrary`. It starts with the
+//! built-in presets, then picks up the `bezier = name, x0, y0, x1, y1`
+//! definitions Hyprland knows about (over IPC, falling back to parsing
+//! `hyprland.conf`), then the `[beziers]` table of our own config, so a name
+//! defined later replaces an earlier one.
+
+use std::collections::BTreeMap;
+use std::f32::consts::TAU;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use tracing::debug;
+
+/// `source =` nesting depth at which `hyprland.conf` parsing gives up.
+const MAX_SOURCE_DEPTH: usize = 8;
+
+/// A CSS / Hyprland cubic bezier from (0, 0) to (1, 1).
+///
+/// The x coordinates of the control points must be in `0..=1` so the curve
+/// is a function of time; y is free, which is what allows overshoot.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CubicBezier {
+    x0: f32,
+    y0: f32,
+    x1: f32,
+    y1: f32,
+}
+
+impl CubicBezier {
+    /// Curve through control points (x0, y0) and (x1, y1), or `None` if an x
+    /// is outside `0..=1` or a coordinate isn't finite.
+    pub fn new(x0: f32, y0: f32, x1: f32, y1: f32) -> Option<Self> {
+        let valid = [x0, y0, x1, y1].iter().all(|v| v.is_finite())
+            && (0.0..=1.0).contains(&x0)
+            && (0.0..=1.0).contains(&x1);
+        valid.then_some(Self { x0, y0, x1, y1 })
+    }
+
+    /// Control points as `[x0, y0, x1, y1]`.
+    pub fn points(&self) -> [f32; 4] {
+        [self.x0, self.y0, self.x1, self.y1]
+    }
+
+    /// y at time `t`: solve x(s) = t for the curve parameter s, then evaluate y(s).
+    pub fn apply(&self, t: f32) -> f32 {
+        if t <= 0.0 {
+            return 0.0;
+        }
+        if t >= 1.0 {
+            return 1.0;
+        }
+        Self::component(self.y0, self.y1, self.solve_x(t))
+    }
+
+    /// One coordinate of the curve at parameter `s`, endpoints at 0 and 1.
+    fn component(p0: f32, p1: f32, s: f32) -> f32 {
+        let c = 3.0 * p0;
+        let b = 3.0 * (p1 - p0) - c;
+        let a = 1.0 - c - b;
+        ((a * s + b) * s + c) * s
+    }
+
+    fn component_slope(p0: f32, p1: f32, s: f32) -> f32 {
+        let c = 3.0 * p0;
+        let b = 3.0 * (p1 - p0) - c;
+        let a = 1.0 - c - b;
+        (3.0 * a * s + 2.0 * b) * s + c
+    }
+
+    /// Parameter s with x(s) = t. Newton's method converges in a few steps
+    /// almost everywhere; flat spots fall back to bisection, which always
+    /// works because x is monotonic for control x in `0..=1`.
+    fn solve_x(&self, t: f32) -> f32 {
+        const EPSILON: f32 = 1e-6;
+
+        let mut s = t;
+        for _ in 0..8 {
+            let error = Self::component(self.x0, self.x1, s) - t;
+            if error.abs() < EPSILON {
+                return s;
+            }
+            let slope = Self::component_slope(self.x0, self.x1, s);
+            if slope.abs() < EPSILON {
+                break;
+            }
+            s -= error / slope;
+        }
+
+        let (mut low, mut high) = (0.0, 1.0);
+        s = t;
+        for _ in 0..32 {
+            let x = Self::component(self.x0, self.x1, s);
+            if (x - t).abs() < EPSILON {
+                break;
+            }
+            if x < t {
+                low = s;
+            } else {
+                high = s;
+            }
+            s = (low + high) / 2.0;
+        }
+        s
+    }
+}
+
+/// Maps linear progress in `0..=1` to eased progress. Every curve starts at
+/// 0 and ends at 1; some overshoot in between.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    Linear,
+    /// `t^n`: slow start
+    In(i32),
+    /// `1 - (1 - t)^n`: slow end
+    Out(i32),
+    /// `In(n)` for the first half, `Out(n)` for the second
+    InOut(i32),
+    Bezier(CubicBezier),
+    /// Underdamped spring: overshoots, then settles
+    Spring,
+    /// Decaying sine wave around the end value
+    Elastic,
+    /// Falls onto the end value and bounces off it
+    Bounce,
+}
+
+impl Easing {
+    /// Hyprland's built-in `default` curve.
+    pub const HYPRLAND_DEFAULT: Easing = Easing::Bezier(CubicBezier {
+        x0: 0.0,
+        y0: 0.75,
+        x1: 0.15,
+        y1: 1.0,
+    });
+
+    pub fn apply(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match *self {
+            Easing::Linear => t,
+            Easing::In(n) => t.powi(n),
+            Easing::Out(n) => 1.0 - (1.0 - t).powi(n),
+            Easing::InOut(n) => {
+                if t < 0.5 {
+                    (2.0 * t).powi(n) / 2.0
+                } else {
+                    1.0 - (2.0 - 2.0 * t).powi(n) / 2.0
+                }
+            }
+            Easing::Bezier(curve) => curve.apply(t),
+            Easing::Spring => {
+                if t >= 1.0 {
+                    1.0
+                } else {
+                    1.0 - (-6.0 * t).exp() * (1.25 * TAU * t).cos()
+                }
+            }
+            Easing::Elastic => {
+                if t <= 0.0 || t >= 1.0 {
+                    t
+                } else {
+                    1.0 + 2f32.powf(-10.0 * t) * ((10.0 * t - 0.75) * TAU / 3.0).sin()
+                }
+            }
+            Easing::Bounce => {
+                const N: f32 = 7.5625;
+                const D: f32 = 2.75;
+                if t < 1.0 / D {
+                    N * t * t
+                } else if t < 2.0 / D {
+                    let t = t - 1.5 / D;
+                    N * t * t + 0.75
+                } else if t < 2.5 / D {
+                    let t = t - 2.25 / D;
+                    N * t * t + 0.9375
+                } else {
+                    let t = t - 2.625 / D;
+                    N * t * t + 0.984375
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Easing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some((name, _)) = PRESETS.iter().find(|(_, preset)| preset == self) {
+            return f.write_str(name);
+        }
+        match self {
+            Easing::In(n) => write!(f, "in^{}", n),
+            Easing::Out(n) => write!(f, "out^{}", n),
+            Easing::InOut(n) => write!(f, "inout^{}", n),
+            Easing::Bezier(curve) => {
+                let [x0, y0, x1, y1] = curve.points();
+                write!(f, "[{}, {}, {}, {}]", x0, y0, x1, y1)
+            }
+            // Every other variant is a preset
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// Hermite smoothstep of `t` clamped to 0..1.
+pub fn smoothstep(t: f32) -> f32 {
+    let t = t.clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// `a` at `t = 0`, `b` at `t = 1`, unclamped.
+pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Built-in curves, named like Hyprland's and easings.net's.
+pub const PRESETS: &[(&str, Easing)] = &[
+    ("linear", Easing::Linear),
+    ("default", Easing::HYPRLAND_DEFAULT),
+    ("easeInQuad", Easing::In(2)),
+    ("easeOutQuad", Easing::Out(2)),
+    ("easeInOutQuad", Easing::InOut(2)),
+    ("easeInCubic", Easing::In(3)),
+    ("easeOutCubic", Easing::Out(3)),
+    ("easeInOutCubic", Easing::InOut(3)),
+    ("easeInQuart", Easing::In(4)),
+    ("easeOutQuart", Easing::Out(4)),
+    ("easeInOutQuart", Easing::InOut(4)),
+    ("spring", Easing::Spring),
+    ("elastic", Easing::Elastic),
+    ("bounce", Easing::Bounce),
+];
+
+/// Named curves available to `easing` settings.
+#[derive(Debug, Clone)]
+pub struct EasingLibrary {
+    curves: BTreeMap<String, Easing>,
+}
+
+impl EasingLibrary {
+    /// Just the built-in presets.
+    pub fn new() -> Self {
+        Self {
+            curves: PRESETS
+                .iter()
+                .map(|(name, easing)| (name.to_string(), *easing))
+                .collect(),
+        }
+    }
+
+    /// Add or replace a named curve.
+    pub fn insert(&mut self, name: impl Into<String>, easing: Easing) {
+        self.curves.insert(name.into(), easing);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Easing> {
+        self.curves.get(name).copied()
+    }
+
+    /// Every name with its curve, sorted by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Easing)> {
+        self.curves.iter().map(|(name, easing)| (name.as_str(), *easing))
+    }
+
+    /// Add every bezier Hyprland has defined; a missing Hyprland adds nothing.
+    pub fn import_hyprland(&mut self) {
+        let beziers = match beziers_from_ipc() {
+            Ok(beziers) => beziers,
+            Err(e) => {
+                debug!("No beziers over IPC ({:#}), reading hyprland.conf", e);
+                match hyprland_conf_path().map(|path| beziers_from_conf(&path)) {
+                    Some(Ok(beziers)) => beziers,
+                    Some(Err(e)) => {
+                        debug!("No beziers from hyprland.conf: {:#}", e);
+                        Vec::new()
+                    }
+                    None => Vec::new(),
+                }
+            }
+        };
+        debug!("Imported {} bezier(s) from Hyprland", beziers.len());
+        for (name, curve) in beziers {
+            self.insert(name, Easing::Bezier(curve));
+        }
+    }
+}
+
+impl Default for EasingLibrary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parse the value of a Hyprland `bezier` keyword: `name, x0, y0, x1, y1`.
+pub fn parse_bezier(value: &str) -> Result<(String, CubicBezier)> {
+    let fields: Vec<&str> = value.split(',').map(str::trim).collect();
+    let [name, coordinates @ ..] = fields.as_slice() else {
+        unreachable!("split always yields a field");
+    };
+    if name.is_empty() || coordinates.len() != 4 {
+        anyhow::bail!("expected 'name, x0, y0, x1, y1', got '{}'", value.trim());
+    }
+
+    let mut points = [0.0; 4];
+    for (point, field) in points.iter_mut().zip(coordinates) {
+        *point = field
+            .parse()
+            .with_context(|| format!("bezier {}: '{}' is not a number", name, field))?;
+    }
+    let [x0, y0, x1, y1] = points;
+    let curve = CubicBezier::new(x0, y0, x1, y1)
+        .with_context(|| format!("bezier {}: x0 and x1 must be within 0..1", name))?;
+    Ok((name.to_string(), curve))
+}
+
+/// Older Hyprland versions only report the name.
+#[derive(Deserialize)]
+struct IpcBezier {
+    name: String,
+    #[serde(rename = "X0", alias = "x0")]
+    x0: Option<f32>,
+    #[serde(rename = "Y0", alias = "y0")]
+    y0: Option<f32>,
+    #[serde(rename = "X1", alias = "x1")]
+    x1: Option<f32>,
+    #[serde(rename = "Y1", alias = "y1")]
+    y1: Option<f32>,
+}
+
+/// Beziers from the running compositor; an error if it doesn't report
+/// control points, so the caller can read the config file instead.
+fn beziers_from_ipc() -> Result<Vec<(String, CubicBezier)>> {
+    let output = Command::new("hyprctl")
+        .args(["-j", "animations"])
+        .output()
+        .context("Failed to run hyprctl")?;
+    if !output.status.success() {
+        anyhow::bail!("hyprctl animations failed: {}", String::from_utf8_lossy(&output.stderr));
+    }
+
+    // A pair of lists: animations, then beziers
+    let (_, beziers): (IgnoredAny, Vec<IpcBezier>) =
+        serde_json::from_slice(&output.stdout).context("Failed to parse hyprctl animations")?;
+    beziers
+        .into_iter()
+        .map(|b| {
+            let (Some(x0), Some(y0), Some(x1), Some(y1)) = (b.x0, b.y0, b.x1, b.y1) else {
+                anyhow::bail!("hyprctl doesn't report control points for '{}'", b.name);
+            };
+            let curve = CubicBezier::new(x0, y0, x1, y1)
+                .with_context(|| format!("bezier {} is out of range", b.name))?;
+            Ok((b.name, curve))
+        })
+        .collect()
+}
+
+/// `$XDG_CONFIG_HOME/hypr/hyprland.conf`, falling back to `~/.config/hypr`.
+pub fn hyprland_conf_path() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("hypr").join("hyprland.conf"))
+}
+
+/// Every `bezier =` line in a Hyprland config, following `source =` includes.
+pub fn beziers_from_conf(path: &Path) -> Result<Vec<(String, CubicBezier)>> {
+    let mut beziers = Vec::new();
+    read_conf(path, 0, &mut beziers)?;
+    Ok(beziers)
+}
+
+fn read_conf(path: &Path, depth: usize, beziers: &mut Vec<(String, CubicBezier)>) -> Result<()> {
+    if depth > MAX_SOURCE_DEPTH {
+        anyhow::bail!("{}: sources nested too deeply", path.display());
+    }
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    for (number, line) in text.lines().enumerate() {
+        // hyprlang escapes a literal '#' as "##"; bezier lines never need one
+        let line = line.split('#').next().unwrap_or_default();
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match key.trim() {
+            "bezier" => match parse_bezier(value) {
+                Ok(bezier) => beziers.push(bezier),
+                Err(e) => debug!("{}:{}: {:#}", path.display(), number + 1, e),
+            },
+            "source" => {
+                let source = resolve_source(path, value.trim());
+                if let Err(e) = read_conf(&source, depth + 1, beziers) {
+                    debug!("{}:{}: {:#}", path.display(), number + 1, e);
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// A `source =` path: `~` is the home directory, relative paths start at the
+/// including file's directory.
+fn resolve_source(including: &Path, source: &str) -> PathBuf {
+    if let Some(rest) = source.strip_prefix("~/") {
+        if let Some(home) = std::env::var_os("HOME") {
+            return PathBuf::from(home).join(rest);
+        }
+    }
+    let source = Path::new(source);
+    match including.parent() {
+        Some(dir) if source.is_relative() => dir.join(source),
+        _ => source.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Hyprland's default, easeInOutBack-style overshoot, and curves with flat
+    /// spots where Newton's method stalls.
+    const CURVES: [[f32; 4]; 5] = [
+        [0.0, 0.75, 0.15, 1.0],
+        [0.68, -0.6, 0.32, 1.6],
+        [0.0, 0.0, 1.0, 1.0],
+        [1.0, 0.0, 0.0, 1.0],
+        [0.25, 0.1, 0.25, 1.0],
+    ];
+
+    fn curve([x0, y0, x1, y1]: [f32; 4]) -> CubicBezier {
+        CubicBezier::new(x0, y0, x1, y1).unwrap()
+    }
+
+    #[test]
+    fn bezier_hits_its_endpoints() {
+        for points in CURVES {
+            let curve = curve(points);
+            assert_eq!(curve.apply(0.0), 0.0, "{:?}", points);
+            assert_eq!(curve.apply(1.0), 1.0, "{:?}", points);
+            assert_eq!(curve.apply(-0.5), 0.0, "{:?}", points);
+            assert_eq!(curve.apply(1.5), 1.0, "{:?}", points);
+            // Continuous into them, however steep the curve leaves 0
+            assert!(curve.apply(1e-4).abs() < 0.1, "{:?}", points);
+            assert!((curve.apply(1.0 - 1e-4) - 1.0).abs() < 0.1, "{:?}", points);
+        }
+    }
+
+    #[test]
+    fn bezier_solves_for_time() {
+        for points in CURVES {
+            let curve = curve(points);
+            for i in 1..1000 {
+                let t = i as f32 / 1000.0;
+                let s = curve.solve_x(t);
+                let x = CubicBezier::component(points[0], points[2], s);
+                assert!((x - t).abs() < 1e-4, "{:?}: x({}) = {}, wanted {}", points, s, x, t);
+            }
+        }
+    }
+
+    #[test]
+    fn bezier_is_monotonic_without_overshoot() {
+        // y control points in 0..1 can't overshoot, so y never goes back
+        let tame = |p: &[f32; 4]| (0.0..=1.0).contains(&p[1]) && (0.0..=1.0).contains(&p[3]);
+        for points in CURVES.into_iter().filter(tame) {
+            let curve = curve(points);
+            let mut last = 0.0;
+            for i in 0..=1000 {
+                let y = curve.apply(i as f32 / 1000.0);
+                assert!(y >= last - 1e-5, "{:?} goes back at {}: {} < {}", points, i, y, last);
+                last = y;
+            }
+        }
+    }
+
+    #[test]
+    fn bezier_rejects_x_outside_0_to_1() {
+        assert!(CubicBezier::new(-0.1, 0.0, 0.5, 1.0).is_none());
+        assert!(CubicBezier::new(0.5, 0.0, 1.1, 1.0).is_none());
+        assert!(CubicBezier::new(0.5, f32::NAN, 0.5, 1.0).is_none());
+        assert!(CubicBezier::new(0.5, -2.0, 0.5, 3.0).is_some());
+    }
+
+    #[test]
+    fn every_preset_starts_at_0_and_ends_at_1() {
+        for (name, easing) in PRESETS {
+            assert!(easing.apply(0.0).abs() < 1e-6, "{}", name);
+            assert!((easing.apply(1.0) - 1.0).abs() < 1e-6, "{}", name);
+        }
+    }
+
+    #[test]
+    fn parses_bezier_lines() {
+        let (name, curve) = parse_bezier(" overshot, 0.05, 0.9, 0.1, 1.1 ").unwrap();
+        assert_eq!(name, "overshot");
+        assert_eq!(curve.points(), [0.05, 0.9, 0.1, 1.1]);
+
+        assert!(parse_bezier("overshot, 0.05, 0.9, 0.1").is_err());
+        assert!(parse_bezier(", 0.05, 0.9, 0.1, 1.1").is_err());
+        assert!(parse_bezier("overshot, 0.05, high, 0.1, 1.1").is_err());
+        assert!(parse_bezier("overshot, 1.5, 0.9, 0.1, 1.1").is_err());
+    }
+
+    #[test]
+    fn reads_beziers_through_source_includes() {
+        let dir = std::env::temp_dir().join(format!("hypr-vortex-easing-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("conf.d")).unwrap();
+        std::fs::write(
+            dir.join("hyprland.conf"),
+            "# bezier = commented, 0, 0, 1, 1\n\
+             bezier = first, 0.1, 0.2, 0.3, 0.4\n\
+             bezier = broken, 2, 0, 0, 1\n\
+             source = conf.d/animations.conf\n\
+             source = missing.conf\n\
+             animation = windows, 1, 5, first # bezier = trailing, 0, 0, 1, 1\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("conf.d/animations.conf"),
+            "bezier=second,0.5,0.6,0.7,0.8\n\
+             source = ../hyprland.conf\n",
+        )
+        .unwrap();
+
+        let beziers = beziers_from_conf(&dir.join("hyprland.conf")).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        // The include cycle is cut off at the depth limit, repeating what it read
+        let names: Vec<&str> = beziers.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(&names[..2], ["first", "second"]);
+        assert!(names.iter().all(|name| ["first", "second"].contains(name)));
+        assert_eq!(beziers[1].1.points(), [0.5, 0.6, 0.7, 0.8]);
+    }
+
+    #[test]
+    fn resolves_source_paths() {
+        let including = Path::new("/etc/hypr/hyprland.conf");
+        assert_eq!(resolve_source(including, "extra.conf"), Path::new("/etc/hypr/extra.conf"));
+        assert_eq!(resolve_source(including, "/opt/a.conf"), Path::new("/opt/a.conf"));
+    }
+}
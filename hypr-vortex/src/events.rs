@@ -0,0 +1,82 @@
+//! Hyprland's event socket (`.socket2.sock`), which announces windows as
+//! they open.
+//!
+//! Events arrive one per line as `name>>data`; everything but the ones in
+//! `Event` is ignored.
+
+use std::io::{BufRead, BufReader};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+/// An event the daemon reacts to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// A window was mapped; `address` is in `hyprctl clients` form (`0x...`)
+    OpenWindow { address: String },
+}
+
+impl Event {
+    /// Parse one line from the socket; `None` for events the daemon doesn't use.
+    pub fn parse(line: &str) -> Option<Self> {
+        let (name, data) = line.split_once(">>")?;
+        match name {
+            // openwindow>>ADDRESS,WORKSPACE,CLASS,TITLE with the address bare hex
+            "openwindow" => {
+                let address = data.split(',').next().filter(|a| !a.is_empty())?;
+                Some(Event::OpenWindow {
+                    address: format!("0x{}", address),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Event socket of the running Hyprland instance.
+pub fn socket_path() -> Result<PathBuf> {
+    let signature = std::env::var("HYPRLAND_INSTANCE_SIGNATURE")
+        .context("HYPRLAND_INSTANCE_SIGNATURE is not set; is Hyprland running?")?;
+    // Hyprland 0.40 moved its sockets from /tmp into the runtime dir
+    let current = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(|dir| PathBuf::from(dir).join("hypr").join(&signature).join(".socket2.sock"))
+        .filter(|path| path.exists());
+    Ok(current.unwrap_or_else(|| {
+        PathBuf::from("/tmp/hypr").join(&signature).join(".socket2.sock")
+    }))
+}
+
+/// Read events until Hyprland closes the socket, handing each one the daemon
+/// uses to `on_event`.
+pub fn listen(mut on_event: impl FnMut(Event)) -> Result<()> {
+    let path = socket_path()?;
+    let stream = UnixStream::connect(&path)
+        .with_context(|| format!("Failed to connect to {}", path.display()))?;
+    for line in BufReader::new(stream).lines() {
+        let line = line.context("Failed to read Hyprland event")?;
+        if let Some(event) = Event::parse(&line) {
+            on_event(event);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_window_takes_the_address_before_the_first_comma() {
+        let line = "openwindow>>55d6a2c3e4f0,2,kitty,vim a.rs, b.rs,c.rs";
+        let expected = Event::OpenWindow { address: "0x55d6a2c3e4f0".into() };
+        assert_eq!(Event::parse(line), Some(expected));
+    }
+
+    #[test]
+    fn other_and_malformed_lines_are_ignored() {
+        assert_eq!(Event::parse("closewindow>>55d6a2c3e4f0"), None);
+        assert_eq!(Event::parse("openwindow>>,2,kitty,~"), None);
+        assert_eq!(Event::parse("openwindow 55d6a2c3e4f0"), None);
+    }
+}
@@ -0,0 +1,283 @@
+//! Window capture through Hyprland's toplevel export protocol.
+//!
+//! A screenshot of the window's region only shows the window once it's on
+//! screen. Exporting asks Hyprland to render the window on its own instead,
+//! so an opening window can be captured while it's hidden (alpha 0) and never
+//! pops up ahead of its animation. Hyprland leaves out the border, shadow and
+//! rounded corners, and nothing from behind the window gets in.
+
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use smithay_client_toolkit::{
+    delegate_shm,
+    shm::{slot::SlotPool, Shm, ShmHandler},
+};
+use tracing::debug;
+use wayland_client::{
+    globals::{registry_queue_init, GlobalListContents},
+    protocol::{wl_registry, wl_shm},
+    Connection, Dispatch, EventQueue, QueueHandle, WEnum,
+};
+
+use crate::overlay::dispatch_with_timeout;
+use crate::pixel::{Image, PixelFormat};
+
+use self::protocol::{
+    hyprland_toplevel_export_frame_v1::{self as frame, HyprlandToplevelExportFrameV1},
+    hyprland_toplevel_export_manager_v1::{self as manager, HyprlandToplevelExportManagerV1},
+};
+
+mod protocol {
+    #![allow(dead_code, non_camel_case_types, unused_unsafe, unused_variables)]
+    #![allow(non_upper_case_globals, non_snake_case, unused_imports)]
+    #![allow(missing_docs, clippy::all)]
+
+    use wayland_client;
+    use wayland_client::protocol::*;
+    use wayland_protocols_wlr::foreign_toplevel::v1::client::*;
+
+    pub mod __interfaces {
+        use wayland_client::protocol::__interfaces::*;
+        use wayland_protocols_wlr::foreign_toplevel::v1::client::__interfaces::*;
+        wayland_scanner::generate_interfaces!("protocols/hyprland-toplevel-export-v1.xml");
+    }
+    use self::__interfaces::*;
+
+    wayland_scanner::generate_client_code!("protocols/hyprland-toplevel-export-v1.xml");
+}
+
+/// How long Hyprland gets to render the window into our buffer.
+const EXPORT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Shared-memory buffer layout Hyprland offered for the frame.
+#[derive(Debug, Clone, Copy)]
+struct BufferOffer {
+    format: wl_shm::Format,
+    width: u32,
+    height: u32,
+    stride: u32,
+}
+
+struct ExportState {
+    shm: Shm,
+    /// Offer in a format we can read, if there was one
+    offer: Option<BufferOffer>,
+    y_invert: bool,
+    ready: bool,
+    failed: bool,
+}
+
+/// Capture the window at `window_address` (as hyprctl prints it), whether
+/// it's visible or not.
+pub fn capture_toplevel(window_address: &str) -> Result<Image> {
+    let handle = window_handle(window_address)?;
+
+    let conn = Connection::connect_to_env().context("Failed to connect to Wayland")?;
+    let (globals, mut event_queue) =
+        registry_queue_init(&conn).context("Failed to init registry")?;
+    let qh = event_queue.handle();
+
+    let shm = Shm::bind(&globals, &qh).context("wl_shm not available")?;
+    let manager: HyprlandToplevelExportManagerV1 = globals
+        .bind(&qh, 1..=2, ())
+        .context("hyprland_toplevel_export_v1 not available")?;
+    let mut state = ExportState {
+        shm,
+        offer: None,
+        y_invert: false,
+        ready: false,
+        failed: false,
+    };
+
+    // The buffer offers are sent in reply to the request, so one roundtrip has them all
+    let frame = manager.capture_toplevel(0, handle, &qh, ());
+    event_queue
+        .roundtrip(&mut state)
+        .context("Failed to read export buffer offers")?;
+    let result = copy_frame(&frame, &mut event_queue, &mut state);
+    frame.destroy();
+    manager.destroy();
+    let _ = event_queue.flush();
+    result
+}
+
+/// Have Hyprland render the window into a buffer matching its offer and read it back.
+fn copy_frame(
+    frame: &HyprlandToplevelExportFrameV1,
+    event_queue: &mut EventQueue<ExportState>,
+    state: &mut ExportState,
+) -> Result<Image> {
+    anyhow::ensure!(!state.failed, "Hyprland can't export the window");
+    let offer = state
+        .offer
+        .context("Hyprland offered no buffer format we can read")?;
+    debug!("Export buffer: {:?}", offer);
+
+    let mut pool = SlotPool::new(offer.stride as usize * offer.height as usize, &state.shm)
+        .context("Failed to create SHM pool")?;
+    let (buffer, _) = pool
+        .create_buffer(
+            offer.width as i32,
+            offer.height as i32,
+            offer.stride as i32,
+            offer.format,
+        )
+        .context("Failed to create SHM buffer")?;
+    // Don't wait for the window to change: it may not draw again for a while
+    frame.copy(buffer.wl_buffer(), 1);
+
+    let deadline = Instant::now() + EXPORT_TIMEOUT;
+    while !state.ready {
+        anyhow::ensure!(!state.failed, "Hyprland failed to export the window");
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        anyhow::ensure!(!remaining.is_zero(), "Timed out waiting for the window export");
+        dispatch_with_timeout(event_queue, state, remaining)?;
+    }
+
+    let canvas = buffer
+        .canvas(&mut pool)
+        .context("Export buffer is still in use")?;
+    Ok(read_frame(canvas, offer, state.y_invert))
+}
+
+/// Copy the exported pixels out of the SHM buffer, top row first.
+fn read_frame(canvas: &[u8], offer: BufferOffer, y_invert: bool) -> Image {
+    let (format, opaque) = match offer.format {
+        wl_shm::Format::Argb8888 => (PixelFormat::Bgra8Premultiplied, false),
+        wl_shm::Format::Xrgb8888 => (PixelFormat::Bgra8Premultiplied, true),
+        wl_shm::Format::Abgr8888 => (PixelFormat::Rgba8Premultiplied, false),
+        _ => (PixelFormat::Rgba8Premultiplied, true),
+    };
+    let width = offer.width as usize;
+    let height = offer.height as usize;
+    let row_len = width * PixelFormat::BYTES_PER_PIXEL;
+
+    let mut pixels = Vec::with_capacity(row_len * height);
+    for y in 0..height {
+        let source_y = if y_invert { height - 1 - y } else { y };
+        let start = source_y * offer.stride as usize;
+        pixels.extend_from_slice(&canvas[start..start + row_len]);
+    }
+    // X formats leave the alpha byte undefined
+    if opaque {
+        for px in pixels.chunks_exact_mut(PixelFormat::BYTES_PER_PIXEL) {
+            px[3] = 255;
+        }
+    }
+    Image::new(pixels, width, height, format)
+}
+
+/// Hyprland's handle for a window: the low 32 bits of its address.
+fn window_handle(window_address: &str) -> Result<u32> {
+    let hex = window_address.trim_start_matches("0x");
+    let address = u64::from_str_radix(hex, 16)
+        .with_context(|| format!("Invalid window address '{}'", window_address))?;
+    Ok(address as u32)
+}
+
+/// Whether `read_frame` can read a buffer in `format`.
+fn readable(format: wl_shm::Format) -> bool {
+    matches!(
+        format,
+        wl_shm::Format::Argb8888
+            | wl_shm::Format::Xrgb8888
+            | wl_shm::Format::Abgr8888
+            | wl_shm::Format::Xbgr8888
+    )
+}
+
+impl Dispatch<HyprlandToplevelExportFrameV1, ()> for ExportState {
+    fn event(
+        state: &mut Self,
+        _proxy: &HyprlandToplevelExportFrameV1,
+        event: frame::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            frame::Event::Buffer {
+                format: WEnum::Value(format),
+                width,
+                height,
+                stride,
+            } if readable(format) => {
+                state.offer = Some(BufferOffer {
+                    format,
+                    width,
+                    height,
+                    stride,
+                });
+            }
+            frame::Event::Flags { flags } => {
+                state.y_invert = matches!(
+                    flags,
+                    WEnum::Value(flags) if flags.contains(frame::Flags::YInvert)
+                );
+            }
+            frame::Event::Ready { .. } => state.ready = true,
+            frame::Event::Failed => state.failed = true,
+            _ => {}
+        }
+    }
+}
+
+impl Dispatch<HyprlandToplevelExportManagerV1, ()> for ExportState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &HyprlandToplevelExportManagerV1,
+        _event: manager::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for ExportState {
+    fn event(
+        _state: &mut Self,
+        _proxy: &wl_registry::WlRegistry,
+        _event: wl_registry::Event,
+        _data: &GlobalListContents,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl ShmHandler for ExportState {
+    fn shm_state(&mut self) -> &mut Shm {
+        &mut self.shm
+    }
+}
+
+delegate_shm!(ExportState);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_handle_is_the_low_half_of_the_address() {
+        assert_eq!(window_handle("0x55d1a2b3c4d0").unwrap(), 0xa2b3_c4d0);
+        assert_eq!(window_handle("1f").unwrap(), 0x1f);
+        assert!(window_handle("0xnope").is_err());
+    }
+
+    #[test]
+    fn read_frame_drops_padding_and_flips() {
+        let offer = BufferOffer {
+            format: wl_shm::Format::Xrgb8888,
+            width: 1,
+            height: 2,
+            stride: 8,
+        };
+        // Two rows of one pixel, each padded to 8 bytes
+        let canvas = [1, 2, 3, 0, 9, 9, 9, 9, 4, 5, 6, 0, 9, 9, 9, 9];
+        let image = read_frame(&canvas, offer, true);
+        assert_eq!(image.format, PixelFormat::Bgra8Premultiplied);
+        assert_eq!(image.pixels, [4, 5, 6, 255, 1, 2, 3, 255]);
+    }
+}
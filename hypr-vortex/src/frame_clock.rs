@@ -0,0 +1,171 @@
+//! Frame pacing from `wp_presentation` feedback.
+//!
+//! Animation progress is computed from the time a frame is expected to hit
+//! the screen rather than from when it happens to be drawn. Presentation
+//! feedback tells us when previous frames were actually shown and how long
+//! the output's refresh cycle is, so we can predict the next vblank on
+//! 60/120/144 Hz monitors alike and count frames that never made it.
+
+use std::time::{Duration, Instant};
+
+use rustix::time::{clock_gettime, ClockId};
+use tracing::{debug, info};
+
+/// Presentation statistics for a single animation run.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FrameStats {
+    /// Frames committed to the compositor
+    pub submitted: u32,
+    /// Frames the compositor reported as shown
+    pub presented: u32,
+    /// Frames replaced before they were ever shown
+    pub discarded: u32,
+    /// Refresh cycles skipped between consecutive presented frames
+    pub missed_vblanks: u64,
+}
+
+impl FrameStats {
+    /// Total frames that should have been shown but were not.
+    pub fn dropped(&self) -> u64 {
+        self.discarded as u64 + self.missed_vblanks
+    }
+}
+
+/// Tracks presentation timing and predicts when the next frame will be shown.
+pub struct FrameClock {
+    /// Clock domain of presentation timestamps (from `wp_presentation.clock_id`)
+    clock: Option<ClockId>,
+    /// Presentation time of the first frame, i.e. progress 0.0
+    start: Option<Duration>,
+    /// Whether `start` is the real presentation time or just a guess
+    start_confirmed: bool,
+    /// Wall-clock time of the first frame, for compositors without presentation-time
+    first_frame: Option<Instant>,
+    last_presented: Option<Duration>,
+    last_seq: Option<u64>,
+    /// Refresh interval reported by feedback, or the output mode as a fallback
+    refresh: Option<Duration>,
+    stats: FrameStats,
+}
+
+impl FrameClock {
+    pub fn new() -> Self {
+        Self {
+            clock: None,
+            start: None,
+            start_confirmed: false,
+            first_frame: None,
+            last_presented: None,
+            last_seq: None,
+            refresh: None,
+            stats: FrameStats::default(),
+        }
+    }
+
+    /// Record the clock domain announced by `wp_presentation.clock_id`.
+    pub fn set_clock_id(&mut self, clk_id: u32) {
+        self.clock = match clk_id as i32 {
+            id if id == ClockId::Monotonic as i32 => Some(ClockId::Monotonic),
+            id if id == ClockId::MonotonicRaw as i32 => Some(ClockId::MonotonicRaw),
+            id if id == ClockId::Realtime as i32 => Some(ClockId::Realtime),
+            _ => {
+                debug!("Unsupported presentation clock {}, using wall clock", clk_id);
+                None
+            }
+        };
+    }
+
+    /// Refresh interval from the output mode, used until feedback reports one.
+    pub fn set_output_refresh(&mut self, refresh_mhz: i32) {
+        if self.refresh.is_none() && refresh_mhz > 0 {
+            self.refresh = Some(Duration::from_nanos(1_000_000_000_000 / refresh_mhz as u64));
+        }
+    }
+
+    /// Time since the first frame at which the next frame will be presented.
+    pub fn next_frame_time(&mut self) -> Duration {
+        let since_first = self.first_frame.get_or_insert_with(Instant::now).elapsed();
+        let Some(clock) = self.clock else {
+            return since_first;
+        };
+
+        // If the clock id arrived after drawing started, keep the timeline continuous
+        let now = timespec_to_duration(clock_gettime(clock));
+        let start = *self.start.get_or_insert(now.saturating_sub(since_first));
+        self.predict_presentation(now).saturating_sub(start)
+    }
+
+    /// Predict the next vblank at or after `now`.
+    fn predict_presentation(&self, now: Duration) -> Duration {
+        let (Some(last), Some(refresh)) = (self.last_presented, self.refresh) else {
+            return now;
+        };
+        if refresh.is_zero() || now < last {
+            return now;
+        }
+
+        let cycles = (now - last).as_nanos() / refresh.as_nanos() + 1;
+        last + refresh * cycles as u32
+    }
+
+    /// A frame was committed to the compositor.
+    pub fn frame_submitted(&mut self) {
+        self.stats.submitted += 1;
+    }
+
+    /// Handle `wp_presentation_feedback.presented`.
+    pub fn frame_presented(&mut self, timestamp: Duration, refresh_ns: u32, seq: u64) {
+        self.stats.presented += 1;
+
+        // Anchor progress 0.0 to when the first frame was really shown
+        if !self.start_confirmed {
+            self.start = Some(timestamp);
+            self.start_confirmed = true;
+        }
+
+        if let Some(last_seq) = self.last_seq {
+            if seq > last_seq + 1 {
+                self.stats.missed_vblanks += seq - last_seq - 1;
+            }
+        }
+
+        if refresh_ns > 0 {
+            self.refresh = Some(Duration::from_nanos(refresh_ns as u64));
+        }
+        self.last_presented = Some(timestamp);
+        self.last_seq = Some(seq);
+    }
+
+    /// Handle `wp_presentation_feedback.discarded`.
+    pub fn frame_discarded(&mut self) {
+        self.stats.discarded += 1;
+    }
+
+    /// Log a summary of the run.
+    pub fn report(&self) {
+        let refresh_hz = self
+            .refresh
+            .filter(|r| !r.is_zero())
+            .map(|r| 1.0 / r.as_secs_f64())
+            .unwrap_or(0.0);
+        info!(
+            "Frames: {} submitted, {} presented, {} dropped ({} discarded, {} missed vblanks) at {:.0} Hz",
+            self.stats.submitted,
+            self.stats.presented,
+            self.stats.dropped(),
+            self.stats.discarded,
+            self.stats.missed_vblanks,
+            refresh_hz
+        );
+    }
+}
+
+impl Default for FrameClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn timespec_to_duration(ts: rustix::time::Timespec) -> Duration {
+    Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+}
@@ -0,0 +1,25 @@
+//! Hypr-Vortex library: the animation system and renderers behind the daemon.
+//!
+//! Split out of the binary so benchmarks can drive the renderers directly.
+
+pub mod animation;
+pub mod animations;
+pub mod config;
+pub mod damage;
+pub mod decoration;
+pub mod easing;
+pub mod events;
+pub mod export;
+pub mod frame_clock;
+pub mod noise;
+pub mod overlay;
+pub mod params;
+pub mod pixel;
+pub mod render;
+pub mod rng;
+pub mod rules;
+pub mod screenshot;
+pub mod shader;
+pub mod swapchain;
+pub mod watch;
+pub mod window;
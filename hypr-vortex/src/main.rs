@@ -7,75 +7,163 @@
 //! 4. Daemon signals script to actually close the window
 //! 5. Daemon displays layer-shell overlay with animated effect
 //!
-//! Available animations: vortex, shrink, fade
+//! Opening windows are announced on Hyprland's event socket instead: the
+//! daemon hides the new window, exports its first frame from Hyprland, plays
+//! the open animation backwards over it, then shows it again.
+//!
+//! Available animations: vortex, shrink, fade, shatter, dust, genie, portal, crt, burn,
+//! slide, glitch, fold, plus the `random` and `cycle` selectors that pick one of them per close
 //! Default: vortex (black hole sucking effect)
+//!
+//! Settings live in `$XDG_CONFIG_HOME/hypr-vortex/config.toml`, extra WGSL
+//! animations in `shaders/` next to it; both are reloaded when they change.
 
-mod animation;
-mod animations;
-mod overlay;
-mod screenshot;
-
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::io::{BufRead, BufReader, Write};
 use std::os::unix::net::{UnixListener, UnixStream};
-use std::sync::Arc;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 
 use anyhow::{Context, Result};
+use serde_json::{Map, Value};
 use tracing::{debug, error, info, warn};
 
-use animation::{AnimationRegistry, WindowGeometry};
+use hypr_vortex::animation::{Animation, AnimationRegistry, Direction, WindowGeometry};
+use hypr_vortex::config::{CaptureBackend, Config};
+use hypr_vortex::decoration::Decoration;
+use hypr_vortex::easing::EasingLibrary;
+use hypr_vortex::events::{self, Event};
+use hypr_vortex::pixel::Image;
+use hypr_vortex::rules::{RuleAction, Rules, NO_ANIMATION};
+use hypr_vortex::window::WindowInfo;
+use hypr_vortex::{animations, export, overlay, screenshot, watch};
 
-const SOCKET_PATH: &str = "/tmp/hypr-vortex.sock";
+/// Time a newly mapped window gets to draw its first frame before a
+/// screenshot of it, when it can't be exported.
+const OPEN_CAPTURE_DELAY_MS: u64 = 50;
 
 fn main() -> Result<()> {
-    // Initialize logging
+    let command = std::env::args().nth(1);
+
+    // Initialize logging; `check` prints its own report instead
+    let default_filter = match command.as_deref() {
+        Some("check") => "off",
+        _ => "hypr_vortex=info",
+    };
     tracing_subscriber::fmt()
         .with_env_filter(
-            std::env::var("RUST_LOG").unwrap_or_else(|_| "hypr_vortex=info".to_string()),
+            std::env::var("RUST_LOG").unwrap_or_else(|_| default_filter.to_string()),
         )
         .init();
 
-    info!("Starting hypr-vortex daemon v0.2");
-
-    // Set up animation registry
-    let mut registry = AnimationRegistry::new();
-    animations::register_all(&mut registry);
+    // `hypr-vortex check [FILE.wgsl...]` validates shaders for CI
+    let config_path = Config::path();
+    if command.as_deref() == Some("check") {
+        return check(config_path.as_deref(), std::env::args().skip(2).map(PathBuf::from).collect());
+    }
 
-    // Check for animation override from env
-    if let Ok(anim_name) = std::env::var("VORTEX_ANIMATION") {
-        if registry.get(&anim_name).is_some() {
-            info!("Using animation from env: {}", anim_name);
-            registry.set_default(Box::leak(anim_name.into_boxed_str()));
-        } else {
-            warn!("Unknown animation '{}', using default", anim_name);
+    // Load config, keeping built-in defaults if it's broken
+    let config = match config_path.as_deref().map(Config::load) {
+        Some(Ok(config)) => config,
+        Some(Err(e)) => {
+            error!("{:#}", e);
+            warn!("Using built-in defaults until the config is fixed");
+            Config::default()
         }
+        None => Config::default(),
+    };
+    let registry = build_registry(&config);
+
+    // `hypr-vortex list` shows what can be configured instead of starting the daemon
+    if command.as_deref() == Some("list") {
+        print_animations(&registry, &config);
+        return Ok(());
     }
 
+    info!("Starting hypr-vortex daemon v0.2");
+    let rules = build_rules(&config);
+
     info!(
         "Available animations: {:?}",
         registry.list()
     );
 
-    let registry = Arc::new(registry);
+    let socket_path = config.socket_path.clone();
+    let live = Arc::new(RwLock::new(Live {
+        registry: Arc::new(registry),
+        rules,
+        config,
+    }));
+
+    // Hot reload: swap in the new registry only if the new config is valid.
+    // The directories are created up front so a config written later is seen.
+    if let Some(dir) = Config::shader_dir() {
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            warn!("Failed to create {}: {}", dir.display(), e);
+        }
+    }
+    if let (Some(path), Some(dir)) = (config_path.clone(), Config::dir()) {
+        let live = Arc::clone(&live);
+        let watched = watch::spawn(&dir, |name| name == "config.toml", move || {
+            reload_config(&path, &live)
+        });
+        if let Err(e) = watched {
+            info!("Config hot reload disabled: {:#}", e);
+        }
+    }
+
+    // Shader animations are part of the config, so an edit reloads both
+    if let (Some(path), Some(dir)) = (config_path, Config::shader_dir()) {
+        let live = Arc::clone(&live);
+        let is_shader =
+            |name: &std::ffi::OsStr| Path::new(name).extension().is_some_and(|ext| ext == "wgsl");
+        let watched = watch::spawn(&dir, is_shader, move || reload_config(&path, &live));
+        if let Err(e) = watched {
+            info!("Shader hot reload disabled: {:#}", e);
+        }
+    }
+
+    // Opening windows come from Hyprland's event socket rather than a script
+    {
+        let live = Arc::clone(&live);
+        thread::spawn(move || {
+            let listened = events::listen(|event| match event {
+                Event::OpenWindow { address } => {
+                    let live = Arc::clone(&live);
+                    thread::spawn(move || {
+                        if let Err(e) = handle_open(&address, &live) {
+                            error!("Open animation error: {}", e);
+                        }
+                    });
+                }
+            });
+            match listened {
+                Ok(()) => warn!("Hyprland event socket closed, open animations stopped"),
+                Err(e) => info!("Open animations disabled: {:#}", e),
+            }
+        });
+    }
 
     // Remove old socket
-    let _ = std::fs::remove_file(SOCKET_PATH);
+    let _ = std::fs::remove_file(&socket_path);
 
     // Bind socket
-    let listener = UnixListener::bind(SOCKET_PATH).context("Failed to create socket")?;
+    let listener = UnixListener::bind(&socket_path).context("Failed to create socket")?;
 
-    info!("Listening on {}", SOCKET_PATH);
-    info!("Ready for window close events");
+    info!("Listening on {}", socket_path.display());
+    info!("Ready for window open and close events");
 
     // Accept connections
     for stream in listener.incoming() {
         match stream {
             Ok(stream) => {
-                let registry = Arc::clone(&registry);
+                let live = Arc::clone(&live);
                 // Handle each connection in a thread for responsiveness
                 thread::spawn(move || {
-                    if let Err(e) = handle_connection(stream, &registry) {
+                    if let Err(e) = handle_connection(stream, &live) {
                         error!("Connection error: {}", e);
                     }
                 });
@@ -87,7 +175,79 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn handle_connection(mut stream: UnixStream, registry: &AnimationRegistry) -> Result<()> {
+/// State the config watcher replaces while connections are being served.
+struct Live {
+    registry: Arc<AnimationRegistry>,
+    rules: Arc<Rules>,
+    config: Config,
+}
+
+/// Registry for `config`, with `VORTEX_ANIMATION` as the default when the
+/// config doesn't name one.
+fn build_registry(config: &Config) -> AnimationRegistry {
+    let mut registry = config.build_registry().unwrap_or_else(|e| {
+        error!("{:#}", e);
+        Config::default().build_registry().expect("built-in config is valid")
+    });
+
+    if config.default_animation.is_none() {
+        if let Ok(anim_name) = std::env::var("VORTEX_ANIMATION") {
+            match registry.static_name(&anim_name) {
+                Some(name) => {
+                    info!("Using animation from env: {}", anim_name);
+                    registry.set_default(name);
+                }
+                None => warn!("Unknown animation '{}', using default", anim_name),
+            }
+        }
+    }
+    registry
+}
+
+fn build_rules(config: &Config) -> Arc<Rules> {
+    let rules = config.build_rules().unwrap_or_else(|e| {
+        error!("{:#}", e);
+        Rules::default()
+    });
+    if !rules.is_empty() {
+        info!("Loaded {} window rule(s)", rules.len());
+    }
+    Arc::new(rules)
+}
+
+/// Re-read the config after it changed on disk.
+fn reload_config(path: &Path, live: &RwLock<Live>) {
+    let config = match Config::load(path) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{:#}", e);
+            warn!("Keeping the previous configuration");
+            return;
+        }
+    };
+    let registry = build_registry(&config);
+    let rules = build_rules(&config);
+
+    let mut live = live.write().unwrap_or_else(|e| e.into_inner());
+    if config.socket_path != live.config.socket_path {
+        warn!(
+            "socket_path changed to {}; restart the daemon to apply it",
+            config.socket_path.display()
+        );
+    }
+    info!(
+        "Reloaded {} (default animation '{}')",
+        path.display(),
+        registry.default_name()
+    );
+    *live = Live {
+        registry: Arc::new(registry),
+        rules,
+        config,
+    };
+}
+
+fn handle_connection(mut stream: UnixStream, live: &RwLock<Live>) -> Result<()> {
     // Set read timeout to prevent blocking forever
     stream.set_read_timeout(Some(Duration::from_secs(5)))?;
 
@@ -95,10 +255,14 @@ fn handle_connection(mut stream: UnixStream, registry: &AnimationRegistry) -> Re
     let mut line = String::new();
     reader.read_line(&mut line)?;
 
-    // Parse: "x,y,width,height,address" or "x,y,width,height,address,animation"
-    let parts: Vec<&str> = line.trim().split(',').collect();
+    // Parse: "x,y,width,height,address[,animation[,{json params}]]"
+    // The JSON may contain commas, so it is everything after the sixth one
+    let parts: Vec<&str> = line.trim().splitn(7, ',').collect();
     if parts.len() < 5 {
-        anyhow::bail!("Invalid format: expected 'x,y,width,height,address[,animation]', got: {}", line);
+        anyhow::bail!(
+            "Invalid format: expected 'x,y,width,height,address[,animation[,params]]', got: {}",
+            line
+        );
     }
 
     let geometry = WindowGeometry {
@@ -110,17 +274,45 @@ fn handle_connection(mut stream: UnixStream, registry: &AnimationRegistry) -> Re
 
     let window_address = parts[4].to_string();
 
-    // Optional animation name
-    let animation = if parts.len() > 5 {
-        let name = parts[5];
-        registry.get(name).unwrap_or_else(|| {
-            warn!("Unknown animation '{}', using default", name);
-            registry.default_animation()
-        })
-    } else {
-        registry.default_animation()
+    // Snapshot what we need so a reload mid-close doesn't affect this one
+    let (registry, rules, capture, easings) = {
+        let live = live.read().unwrap_or_else(|e| e.into_inner());
+        (
+            Arc::clone(&live.registry),
+            Arc::clone(&live.rules),
+            live.config.capture,
+            live.config.easings().clone(),
+        )
     };
 
+    // Optional animation name (or selector)
+    let requested = parts.get(5).filter(|name| !name.is_empty()).and_then(|&name| {
+        let animation = registry.resolve(name);
+        if animation.is_none() {
+            warn!("Unknown animation '{}', using default", name);
+        }
+        animation
+    });
+
+    // An explicitly requested animation wins, then window rules, then the default
+    let action = match requested {
+        Some(animation) => RuleAction::Animate(animation),
+        None => select_rule(&rules, &window_address)
+            .unwrap_or_else(|| RuleAction::Animate(registry.default_animation())),
+    };
+    let animation = match action {
+        RuleAction::Animate(animation) => animation,
+        RuleAction::Pick(selector) => registry
+            .resolve(selector)
+            .unwrap_or_else(|| registry.default_animation()),
+        RuleAction::Skip => {
+            info!("Window close: {} matched a 'none' rule, closing directly", window_address);
+            stream.write_all(b"CLOSE\n")?;
+            stream.flush()?;
+            close_window(&window_address);
+            return Ok(());
+        }
+    };
     info!(
         "Window close: ({}, {}) {}x{} using '{}'",
         geometry.x, geometry.y, geometry.width, geometry.height, animation.name()
@@ -134,19 +326,25 @@ fn handle_connection(mut stream: UnixStream, registry: &AnimationRegistry) -> Re
         anyhow::bail!("Invalid geometry: too large");
     }
 
-    // 1. Capture screenshot BEFORE closing window
-    let screenshot_data = screenshot::capture_region(&geometry).or_else(|e| {
-        warn!("Fast capture failed ({}), trying PNG fallback", e);
-        screenshot::capture_region_png(&geometry)
-    })?;
+    // Rounded corners, border and shadow live outside the reported rectangle
+    let decoration = Decoration::query().unwrap_or_else(|e| {
+        warn!("Failed to read decoration settings ({}), capturing bare window", e);
+        Decoration::default()
+    });
+    let geometry = decoration.expand(&geometry);
+
+    // Request params go on last: a target is relative to the captured area
+    let animation = match parts.get(6) {
+        Some(json) => with_request_params(animation, json, &easings, &geometry),
+        None => animation,
+    };
 
-    debug!("Screenshot captured: {} bytes", screenshot_data.len());
+    // 1. Capture screenshot BEFORE closing window
+    let screenshot = capture_window(&geometry, capture, &decoration)?;
 
     // 2. Make window invisible but keep it in tiling layout
     // Using alpha 0 keeps the window in place (siblings don't resize) but invisible
-    let _ = std::process::Command::new("hyprctl")
-        .args(["setprop", &format!("address:{}", window_address), "alpha", "0"])
-        .output();
+    set_alpha(&window_address, 0.0);
 
     // 3. Signal client that we're ready (they don't need to do anything)
     stream.write_all(b"CLOSE\n")?;
@@ -156,15 +354,421 @@ fn handle_connection(mut stream: UnixStream, registry: &AnimationRegistry) -> Re
     thread::sleep(Duration::from_millis(16));
 
     // 4. Run the animation overlay FIRST
-    if let Err(e) = overlay::run_overlay(geometry, screenshot_data, animation) {
+    let seed = close_seed(&window_address);
+    let direction = Direction::Forward;
+    let hooks = overlay::Hooks::none();
+    let result = overlay::run_overlay(geometry, screenshot, animation, direction, seed, hooks);
+    if let Err(e) = result {
         error!("Overlay error: {}", e);
     }
 
     // 5. NOW close the window after animation completes
     info!("Animation done, closing window {}", window_address);
+    close_window(&window_address);
+
+    Ok(())
+}
+
+/// Play the open animation over a window that just mapped.
+fn handle_open(window_address: &str, live: &RwLock<Live>) -> Result<()> {
+    let (registry, rules, capture, open_animation) = {
+        let live = live.read().unwrap_or_else(|e| e.into_inner());
+        (
+            Arc::clone(&live.registry),
+            Arc::clone(&live.rules),
+            live.config.capture,
+            live.config.open_animation.clone(),
+        )
+    };
+    if open_animation.as_deref() == Some(NO_ANIMATION) {
+        return Ok(());
+    }
+
+    // Hide the window right away so it doesn't show before its animation does
+    set_alpha(window_address, 0.0);
+
+    // Window rules win, then the open animation, then the default
+    let action = select_rule(&rules, window_address).unwrap_or_else(|| {
+        let animation = open_animation.as_deref().and_then(|name| registry.resolve(name));
+        RuleAction::Animate(animation.unwrap_or_else(|| registry.default_animation()))
+    });
+    let animation = match action {
+        RuleAction::Animate(animation) => animation,
+        RuleAction::Pick(selector) => registry
+            .resolve(selector)
+            .unwrap_or_else(|| registry.default_animation()),
+        RuleAction::Skip => {
+            debug!("Window open: {} matched a 'none' rule", window_address);
+            unset_alpha(window_address);
+            return Ok(());
+        }
+    };
+
+    let result = play_open(window_address, animation, capture);
+    if result.is_err() {
+        unset_alpha(window_address);
+    }
+    result
+}
+
+/// Capture the hidden window and play `animation` backwards over it, showing
+/// the window again under the last frame.
+fn play_open(
+    window_address: &str,
+    animation: Arc<dyn Animation>,
+    capture: CaptureBackend,
+) -> Result<()> {
+    let window = WindowInfo::lookup(window_address)?
+        .with_context(|| format!("Window {} is already gone", window_address))?;
+    let content = window.geometry();
+    if content.width == 0 || content.height == 0 {
+        anyhow::bail!("Invalid geometry: zero dimension");
+    }
+    if content.width > 8192 || content.height > 8192 {
+        anyhow::bail!("Invalid geometry: too large");
+    }
+    info!(
+        "Window open: ({}, {}) {}x{} using '{}' in reverse",
+        content.x, content.y, content.width, content.height, animation.name()
+    );
+
+    let decoration = Decoration::query().unwrap_or_else(|e| {
+        warn!("Failed to read decoration settings ({}), capturing bare window", e);
+        Decoration::default()
+    });
+    let geometry = decoration.expand(&content);
+    let screenshot = match export::capture_toplevel(window_address) {
+        Ok(export) => decoration.apply(decoration.surround(export, &content), &geometry),
+        Err(e) => {
+            // A screenshot needs the window on screen, so it pops up after all
+            warn!("Window export failed ({}), capturing the screen instead", e);
+            unset_alpha(window_address);
+            // Let the client's first frame reach the screen before capturing it
+            thread::sleep(Duration::from_millis(OPEN_CAPTURE_DELAY_MS));
+            capture_window(&geometry, capture, &decoration)?
+        }
+    };
+
+    // Keep the window hidden under the overlay's first frame (the fallback
+    // showed it), and bring it back under the last
+    let seed = close_seed(window_address);
+    let direction = Direction::Reverse;
+    let hooks = overlay::Hooks {
+        covered: || set_alpha(window_address, 0.0),
+        handoff: || unset_alpha(window_address),
+    };
+    let result = overlay::run_overlay(geometry, screenshot, animation, direction, seed, hooks);
+    if let Err(e) = result {
+        error!("Overlay error: {}", e);
+        unset_alpha(window_address);
+    }
+    Ok(())
+}
+
+/// Screenshot of `geometry` with `decoration`'s rounded corners cut out.
+fn capture_window(
+    geometry: &WindowGeometry,
+    capture: CaptureBackend,
+    decoration: &Decoration,
+) -> Result<Image> {
+    let screenshot = match capture {
+        CaptureBackend::Ppm => screenshot::capture_region(geometry).or_else(|e| {
+            warn!("Fast capture failed ({}), trying PNG fallback", e);
+            screenshot::capture_region_png(geometry)
+        }),
+        CaptureBackend::Png => screenshot::capture_region_png(geometry),
+    }?;
+    let screenshot = decoration.apply(screenshot, geometry);
+
+    debug!(
+        "Screenshot captured: {}x{} {:?}",
+        screenshot.width, screenshot.height, screenshot.format
+    );
+    Ok(screenshot)
+}
+
+/// `animation` with the request's JSON parameter overrides, or unchanged if
+/// they don't parse or validate.
+fn with_request_params(
+    animation: Arc<dyn Animation>,
+    json: &str,
+    easings: &EasingLibrary,
+    window: &WindowGeometry,
+) -> Arc<dyn Animation> {
+    let overrides = serde_json::from_str::<Map<String, Value>>(json)
+        .map_err(anyhow::Error::from)
+        .and_then(|mut overrides| {
+            resolve_target(&mut overrides, window)?;
+            Ok(overrides)
+        });
+    let overrides = match overrides {
+        Ok(overrides) => overrides,
+        Err(e) => {
+            warn!("Ignoring request params ({}): {}", e, json);
+            return animation;
+        }
+    };
+    animation.configured(&overrides, easings).unwrap_or_else(|e| {
+        warn!("Ignoring request params for '{}': {}", animation.name(), e);
+        animation
+    })
+}
+
+/// Print every animation with its duration, easing and parameter schema,
+/// then the curves an `easing` setting can name.
+fn print_animations(registry: &AnimationRegistry, config: &Config) {
+    let mut names = registry.list();
+    names.sort_unstable();
+    for name in names {
+        let Some(animation) = registry.get(name) else {
+            continue;
+        };
+        println!(
+            "{} ({} ms, {}): {}",
+            name,
+            animation.duration_ms(),
+            animation.settings().easing,
+            animation.description()
+        );
+        let params = &animation.settings().params;
+        for spec in animation.param_schema() {
+            let current = params.get(spec.name).unwrap_or(spec.default);
+            println!(
+                "    {:<16} {:<20} = {:<10} {}",
+                spec.name,
+                spec.kind_summary(),
+                current.to_string(),
+                spec.description
+            );
+        }
+    }
+
+    for (name, e) in registry.quarantined() {
+        println!("{} (quarantined):\n{}", name, e);
+    }
+
+    println!("\nEasings:");
+    for (name, easing) in config.easings().iter() {
+        let curve = easing.to_string();
+        println!("    {:<16} {}", name, if curve == name { "built in" } else { &curve });
+    }
+}
+
+/// Validate `files` as shader animations or, with none given, the config,
+/// every built-in animation and the shaders directory. Fails if anything
+/// is invalid.
+fn check(config_path: Option<&Path>, files: Vec<PathBuf>) -> Result<()> {
+    let mut failures = 0;
+    let mut report = |name: &str, result: Result<()>| match result {
+        Ok(()) => println!("ok      {}", name),
+        Err(e) => {
+            failures += 1;
+            println!("FAILED  {}\n{:#}\n", name, e);
+        }
+    };
+
+    let config = config_path.map(Config::load).transpose();
+    if files.is_empty() {
+        match config_path {
+            // `Config::load` treats a missing file as the defaults, which is fine
+            // to run with but shouldn't read as a config that checked out
+            Some(path) if !path.exists() => {
+                println!("none    {} (no config, using defaults)", path.display());
+            }
+            Some(path) => {
+                let result = config.as_ref().map(drop).map_err(|e| anyhow::anyhow!("{:#}", e));
+                report(&path.display().to_string(), result);
+            }
+            None => {}
+        }
+
+        let mut builtin = AnimationRegistry::new();
+        animations::register_all(&mut builtin);
+        let mut names = builtin.list();
+        names.sort_unstable();
+        for name in names {
+            report(name, Ok(()));
+        }
+        for (name, e) in builtin.quarantined() {
+            report(name, Err(anyhow::anyhow!("{}", e)));
+        }
+    }
+
+    // Headers may name curves from the config, so use its easings if it loads
+    let easings = match config {
+        Ok(Some(config)) => config.easings().clone(),
+        _ => EasingLibrary::new(),
+    };
+    let shaders = match Config::shader_dir() {
+        Some(dir) if files.is_empty() => animations::load_dir(&dir, &easings),
+        _ => files
+            .iter()
+            .map(|path| animations::CustomAnimation::load(path, &easings))
+            .collect(),
+    };
+    for shader in shaders {
+        match shader {
+            Ok(animation) => report(animation.name(), Ok(())),
+            Err(e) => report("shader", Err(e)),
+        }
+    }
+
+    if failures > 0 {
+        anyhow::bail!("{} check(s) failed", failures);
+    }
+    Ok(())
+}
+
+/// Action of the last rule matching the window; the lookup is skipped when
+/// there are no rules.
+fn select_rule(rules: &Rules, window_address: &str) -> Option<RuleAction> {
+    if rules.is_empty() {
+        return None;
+    }
+    match WindowInfo::lookup(window_address) {
+        Ok(Some(window)) => {
+            let action = rules.select(&window).cloned();
+            debug!(
+                "Window {} (class '{}', title '{}') matched a rule: {}",
+                window_address,
+                window.class,
+                window.title,
+                action.is_some()
+            );
+            action
+        }
+        Ok(None) => {
+            warn!("Window {} not found, ignoring rules", window_address);
+            None
+        }
+        Err(e) => {
+            warn!("Window lookup failed ({}), ignoring rules", e);
+            None
+        }
+    }
+}
+
+/// Replace a request's `"target"`, either `[x, y, width, height]` in layout
+/// pixels or `"cursor"`, with `target_*` parameters measured in sizes of
+/// `window` from its top left, the way animations like genie take them.
+fn resolve_target(overrides: &mut Map<String, Value>, window: &WindowGeometry) -> Result<()> {
+    let Some(target) = overrides.remove("target") else {
+        return Ok(());
+    };
+    let [x, y, width, height] = match &target {
+        Value::String(name) if name == "cursor" => {
+            let (x, y) = cursor_position()?;
+            [x, y, 0.0, 0.0]
+        }
+        _ => serde_json::from_value::<[f64; 4]>(target.clone()).map_err(|_| {
+            anyhow::anyhow!("target must be [x, y, width, height] or \"cursor\", got {}", target)
+        })?,
+    };
+
+    let (window_width, window_height) = (window.width.max(1) as f64, window.height.max(1) as f64);
+    for (key, value) in [
+        ("target_x", (x - window.x as f64) / window_width),
+        ("target_y", (y - window.y as f64) / window_height),
+        ("target_width", width / window_width),
+        ("target_height", height / window_height),
+    ] {
+        overrides.insert(key.to_string(), Value::from(value));
+    }
+    Ok(())
+}
+
+/// Cursor position in layout pixels.
+fn cursor_position() -> Result<(f64, f64)> {
+    #[derive(serde::Deserialize)]
+    struct Position {
+        x: f64,
+        y: f64,
+    }
+
+    let output = std::process::Command::new("hyprctl")
+        .args(["-j", "cursorpos"])
+        .output()
+        .context("Failed to run hyprctl")?;
+    if !output.status.success() {
+        anyhow::bail!("hyprctl cursorpos failed: {}", String::from_utf8_lossy(&output.stderr));
+    }
+    let position: Position =
+        serde_json::from_slice(&output.stdout).context("Failed to parse hyprctl cursorpos")?;
+    Ok((position.x, position.y))
+}
+
+/// Set a window's opacity; 0 hides it without taking it out of the layout.
+fn set_alpha(window_address: &str, alpha: f32) {
+    set_prop(window_address, "alpha", &alpha.to_string());
+}
+
+/// Drop the opacity `set_alpha` forced, back to whatever the window's rules
+/// and the user had set.
+fn unset_alpha(window_address: &str) {
+    set_prop(window_address, "alpha", "unset");
+}
+
+fn set_prop(window_address: &str, prop: &str, value: &str) {
+    let _ = std::process::Command::new("hyprctl")
+        .args(["setprop", &format!("address:{}", window_address), prop, value])
+        .output();
+}
+
+fn close_window(window_address: &str) {
     let _ = std::process::Command::new("hyprctl")
         .args(["dispatch", &format!("closewindow address:{}", window_address)])
         .output();
+}
 
-    Ok(())
+/// Seed for one close animation, different every time.
+fn close_seed(window_address: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    window_address.hash(&mut hasher);
+    SystemTime::now().hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WINDOW: WindowGeometry = WindowGeometry {
+        x: 100,
+        y: 200,
+        width: 400,
+        height: 300,
+    };
+
+    fn overrides(json: &str) -> Map<String, Value> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn resolve_target_measures_in_window_sizes() {
+        let mut params = overrides(r#"{"target": [300, 800, 100, 30], "duration": 300}"#);
+        resolve_target(&mut params, &WINDOW).unwrap();
+
+        assert!(!params.contains_key("target"));
+        assert_eq!(params["duration"], 300);
+        assert_eq!(params["target_x"], 0.5);
+        assert_eq!(params["target_y"], 2.0);
+        assert_eq!(params["target_width"], 0.25);
+        assert_eq!(params["target_height"], 0.1);
+    }
+
+    #[test]
+    fn resolve_target_leaves_requests_without_one_alone() {
+        let mut params = overrides(r#"{"duration": 300}"#);
+        resolve_target(&mut params, &WINDOW).unwrap();
+        assert_eq!(params, overrides(r#"{"duration": 300}"#));
+    }
+
+    #[test]
+    fn resolve_target_rejects_malformed_targets() {
+        let targets = [r#"[1, 2, 3]"#, r#"[1, 2, 3, 4, 5]"#, r#"[1, "2", 3, 4]"#, r#""mouse""#, "7"];
+        for target in targets {
+            let mut params = overrides(&format!(r#"{{"target": {}}}"#, target));
+            assert!(resolve_target(&mut params, &WINDOW).is_err(), "{}", target);
+        }
+    }
 }
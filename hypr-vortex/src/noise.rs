@@ -0,0 +1,325 @@
+//! Seeded procedural noise shared by the CPU renderers and the shaders.
+//!
+//! Every function here has a WGSL twin in `wgsl!()` doing the same integer
+//! hashing and the same arithmetic, so a seed gives the same pattern on both
+//! paths. Shaders pull the twins in with `concat!(noise::wgsl!(), r#"..."#)`
+//! and get their seed from the uniforms with `noise_seed(u.seed)`.
+
+use glam::{Vec2, Vec3, Vec4};
+
+/// Noise seed for a close, from `AnimationUniforms::seed`.
+///
+/// That value is 24 random bits over 2^24, so this recovers them exactly.
+pub fn seed(uniform_seed: f32) -> u32 {
+    (uniform_seed * 16_777_216.0) as u32
+}
+
+/// Random value in `[0, 1)` for an integer point, e.g. a block or a slice.
+pub fn random(x: i32, y: i32, seed: u32) -> f32 {
+    unit(hash(x, y, seed)).min(0.999_999_94)
+}
+
+/// Smooth value noise in `[-1, 1]` at `p`, one lattice cell per unit.
+pub fn value(p: Vec2, seed: u32) -> f32 {
+    let cell = p.floor();
+    let f = p - cell;
+    let (ix, iy) = (cell.x as i32, cell.y as i32);
+    let fade = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+
+    let a = lattice(ix, iy, seed);
+    let b = lattice(ix + 1, iy, seed);
+    let c = lattice(ix, iy + 1, seed);
+    let d = lattice(ix + 1, iy + 1, seed);
+    a + (b - a) * fade.x + (c - a) * fade.y + (a - b - c + d) * fade.x * fade.y
+}
+
+/// Gradient of `value` at `p`.
+pub fn value_grad(p: Vec2, seed: u32) -> Vec2 {
+    let cell = p.floor();
+    let f = p - cell;
+    let (ix, iy) = (cell.x as i32, cell.y as i32);
+
+    // Quintic fade and its derivative, so the gradient is continuous too
+    let fade = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+    let dfade = 30.0 * f * f * (f * (f - 2.0) + 1.0);
+
+    let a = lattice(ix, iy, seed);
+    let b = lattice(ix + 1, iy, seed);
+    let c = lattice(ix, iy + 1, seed);
+    let d = lattice(ix + 1, iy + 1, seed);
+    let k = a - b - c + d;
+    Vec2::new(
+        dfade.x * (b - a + k * fade.y),
+        dfade.y * (c - a + k * fade.x),
+    )
+}
+
+/// Fractal sum of `octaves` layers of `value`, each twice as fine and half
+/// as strong as the last, in `[-1, 1]`.
+pub fn fbm(p: Vec2, octaves: u32, seed: u32) -> f32 {
+    let mut sum = 0.0;
+    let mut amplitude = 1.0;
+    let mut total = 0.0;
+    let mut q = p;
+    for octave in 0..octaves.max(1) {
+        sum += value(q, seed.wrapping_add(octave)) * amplitude;
+        total += amplitude;
+        amplitude *= 0.5;
+        q *= 2.0;
+    }
+    sum / total
+}
+
+/// 2D simplex noise in about `[-1, 1]` (Ashima Arts' version, as in Burn My
+/// Windows); `seed` shifts the pattern.
+pub fn simplex(v: Vec2, seed: u32) -> f32 {
+    const C: Vec4 = Vec4::new(0.211_324_87, 0.366_025_4, -0.577_350_26, 0.024_390_243);
+    let mod289 = |x: Vec3| x - (x * (1.0 / 289.0)).floor() * 289.0;
+    let permute = |x: Vec3| mod289((x * 34.0 + 1.0) * x);
+
+    // The pattern repeats every 289 units, so that's as far as a shift needs to go
+    let v = v + Vec2::new(unit(hash(0, 0, seed)), unit(hash(1, 0, seed))) * 289.0;
+
+    let i = (v + v.dot(Vec2::splat(C.y))).floor();
+    let x0 = v - i + i.dot(Vec2::splat(C.x));
+    let i1 = if x0.x > x0.y { Vec2::X } else { Vec2::Y };
+    let x12a = x0 + C.x - i1;
+    let x12b = x0 + C.z;
+
+    let i = i - (i * (1.0 / 289.0)).floor() * 289.0;
+    let p = permute(permute(Vec3::new(0.0, i1.y, 1.0) + i.y) + Vec3::new(0.0, i1.x, 1.0) + i.x);
+    let m = Vec3::new(x0.dot(x0), x12a.dot(x12a), x12b.dot(x12b));
+    let m = (Vec3::splat(0.5) - m).max(Vec3::ZERO);
+    let m = m * m;
+    let m = m * m;
+
+    let x = 2.0 * (p * C.w - (p * C.w).floor()) - 1.0;
+    let h = x.abs() - 0.5;
+    let a0 = x - (x + 0.5).floor();
+    let m = m * (1.792_842_9 - 0.853_734_7 * (a0 * a0 + h * h));
+    let g = Vec3::new(
+        a0.x * x0.x + h.x * x0.y,
+        a0.y * x12a.x + h.y * x12a.y,
+        a0.z * x12b.x + h.z * x12b.y,
+    );
+    130.0 * m.dot(g)
+}
+
+/// Random value in `[-1, 1]` for a lattice point.
+fn lattice(x: i32, y: i32, seed: u32) -> f32 {
+    unit(hash(x, y, seed)) * 2.0 - 1.0
+}
+
+/// `h` mapped to `[0, 1]`.
+fn unit(h: u32) -> f32 {
+    h as f32 / u32::MAX as f32
+}
+
+/// Well-mixed 32 bits for a lattice point.
+fn hash(x: i32, y: i32, seed: u32) -> u32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343)
+        ^ (y as u32).wrapping_mul(0xd816_3841)
+        ^ seed.wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2c1b_3c6d);
+    h ^= h >> 12;
+    h = h.wrapping_mul(0x297a_2d39);
+    h ^= h >> 15;
+    h
+}
+
+/// WGSL twins of this module's functions: `noise_seed`, `noise_random`,
+/// `noise_value`, `noise_value_grad`, `noise_fbm` and `noise_simplex`. A string literal, so
+/// shaders can `concat!` it in front of their own source.
+macro_rules! wgsl {
+    () => {
+        r#"
+// Shared procedural noise (src/noise.rs)
+
+fn noise_seed(uniform_seed: f32) -> u32 {
+    return u32(uniform_seed * 16777216.0);
+}
+
+fn noise_hash(x: i32, y: i32, seed: u32) -> u32 {
+    var h = (bitcast<u32>(x) * 0x8da6b343u) ^ (bitcast<u32>(y) * 0xd8163841u) ^ (seed * 0xcb1ab31fu);
+    h ^= h >> 15u;
+    h *= 0x2c1b3c6du;
+    h ^= h >> 12u;
+    h *= 0x297a2d39u;
+    h ^= h >> 15u;
+    return h;
+}
+
+fn noise_unit(h: u32) -> f32 {
+    return f32(h) / 4294967295.0;
+}
+
+// Random value in [0, 1) for an integer point
+fn noise_random(cell: vec2<i32>, seed: u32) -> f32 {
+    return min(noise_unit(noise_hash(cell.x, cell.y, seed)), 0.99999994);
+}
+
+fn noise_lattice(cell: vec2<i32>, seed: u32) -> f32 {
+    return noise_unit(noise_hash(cell.x, cell.y, seed)) * 2.0 - 1.0;
+}
+
+// Smooth value noise in [-1, 1], one lattice cell per unit
+fn noise_value(p: vec2<f32>, seed: u32) -> f32 {
+    let cell = floor(p);
+    let f = p - cell;
+    let i = vec2<i32>(cell);
+    let fade = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+    let a = noise_lattice(i, seed);
+    let b = noise_lattice(i + vec2<i32>(1, 0), seed);
+    let c = noise_lattice(i + vec2<i32>(0, 1), seed);
+    let d = noise_lattice(i + vec2<i32>(1, 1), seed);
+    return a + (b - a) * fade.x + (c - a) * fade.y + (a - b - c + d) * fade.x * fade.y;
+}
+
+// Gradient of noise_value
+fn noise_value_grad(p: vec2<f32>, seed: u32) -> vec2<f32> {
+    let cell = floor(p);
+    let f = p - cell;
+    let i = vec2<i32>(cell);
+    let fade = f * f * f * (f * (f * 6.0 - 15.0) + 10.0);
+    let dfade = 30.0 * f * f * (f * (f - 2.0) + 1.0);
+    let a = noise_lattice(i, seed);
+    let b = noise_lattice(i + vec2<i32>(1, 0), seed);
+    let c = noise_lattice(i + vec2<i32>(0, 1), seed);
+    let d = noise_lattice(i + vec2<i32>(1, 1), seed);
+    let k = a - b - c + d;
+    return dfade * vec2<f32>(b - a + k * fade.y, c - a + k * fade.x);
+}
+
+// Octaves of noise_value, each twice as fine and half as strong, in [-1, 1]
+fn noise_fbm(p: vec2<f32>, octaves: u32, seed: u32) -> f32 {
+    var sum = 0.0;
+    var amplitude = 1.0;
+    var total = 0.0;
+    var q = p;
+    for (var octave = 0u; octave < max(octaves, 1u); octave++) {
+        sum += noise_value(q, seed + octave) * amplitude;
+        total += amplitude;
+        amplitude *= 0.5;
+        q *= 2.0;
+    }
+    return sum / total;
+}
+
+fn noise_mod289_3(x: vec3<f32>) -> vec3<f32> {
+    return x - floor(x * (1.0 / 289.0)) * 289.0;
+}
+
+fn noise_permute(x: vec3<f32>) -> vec3<f32> {
+    return noise_mod289_3((x * 34.0 + 1.0) * x);
+}
+
+// Simplex noise in about [-1, 1]; seed shifts the pattern
+fn noise_simplex(point: vec2<f32>, seed: u32) -> f32 {
+    let C = vec4<f32>(0.211324865405187, 0.366025403784439, -0.577350269189626, 0.024390243902439);
+    let v = point + vec2<f32>(noise_unit(noise_hash(0, 0, seed)), noise_unit(noise_hash(1, 0, seed))) * 289.0;
+    var i = floor(v + dot(v, C.yy));
+    let x0 = v - i + dot(i, C.xx);
+    let i1 = select(vec2<f32>(0.0, 1.0), vec2<f32>(1.0, 0.0), x0.x > x0.y);
+    let x12a = x0 + C.xx - i1;
+    let x12b = x0 + C.zz;
+    i = i - floor(i * (1.0 / 289.0)) * 289.0;
+    let p = noise_permute(noise_permute(i.y + vec3<f32>(0.0, i1.y, 1.0)) + i.x + vec3<f32>(0.0, i1.x, 1.0));
+    var m = max(0.5 - vec3<f32>(dot(x0, x0), dot(x12a, x12a), dot(x12b, x12b)), vec3<f32>(0.0));
+    m = m * m;
+    m = m * m;
+    let x = 2.0 * fract(p * C.www) - 1.0;
+    let h = abs(x) - 0.5;
+    let a0 = x - floor(x + 0.5);
+    m *= 1.79284291400159 - 0.85373472095314 * (a0 * a0 + h * h);
+    let g = vec3<f32>(
+        a0.x * x0.x + h.x * x0.y,
+        a0.y * x12a.x + h.y * x12a.y,
+        a0.z * x12b.x + h.z * x12b.y,
+    );
+    return 130.0 * dot(m, g);
+}
+"#
+    };
+}
+pub(crate) use wgsl;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// Random points spanning many lattice cells, negative ones included.
+    fn points(count: usize) -> Vec<Vec2> {
+        let mut rng = Rng::new(11);
+        (0..count)
+            .map(|_| Vec2::new(rng.range(-50.0, 50.0), rng.range(-50.0, 50.0)))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_same_noise_other_seed_other_noise() {
+        let points = points(64);
+        let sample = |seed: u32| -> Vec<f32> {
+            points
+                .iter()
+                .flat_map(|&p| {
+                    let (x, y) = (p.x as i32, p.y as i32);
+                    [random(x, y, seed), value(p, seed), fbm(p, 4, seed), simplex(p, seed)]
+                })
+                .collect()
+        };
+        assert_eq!(sample(5), sample(5));
+
+        let (a, b) = (sample(5), sample(6));
+        let differing = a.iter().zip(&b).filter(|(a, b)| a != b).count();
+        assert!(differing > a.len() * 9 / 10, "{differing} of {} differ", a.len());
+    }
+
+    #[test]
+    fn noise_stays_in_range() {
+        for seed in 0..4 {
+            for p in points(2000) {
+                let r = random(p.x as i32, p.y as i32, seed);
+                assert!((0.0..1.0).contains(&r), "random {r}");
+                for (name, n) in [
+                    ("value", value(p, seed)),
+                    ("fbm", fbm(p, 5, seed)),
+                    ("simplex", simplex(p, seed)),
+                ] {
+                    assert!((-1.0..=1.0).contains(&n), "{name} {n} at {p}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn noise_is_continuous_across_cell_edges() {
+        const EPS: f32 = 1e-3;
+        for p in points(200) {
+            // Onto a vertical and a horizontal lattice line
+            for edge in [Vec2::new(p.x.round(), p.y), Vec2::new(p.x, p.y.round())] {
+                for (name, noise) in [
+                    ("value", value as fn(Vec2, u32) -> f32),
+                    ("fbm", |p, seed| fbm(p, 3, seed)),
+                    ("simplex", simplex),
+                ] {
+                    let across = (edge - EPS, edge + EPS);
+                    let jump = (noise(across.0, 9) - noise(across.1, 9)).abs();
+                    assert!(jump < 0.05, "{name} jumps {jump} at {edge}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn wgsl_parses_on_its_own() {
+        let module = naga::front::wgsl::parse_str(wgsl!()).expect("noise WGSL parses");
+        naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .expect("noise WGSL validates");
+    }
+}
@@ -1,10 +1,16 @@
 //! Layer-shell overlay for rendering animations.
 //!
-//! Creates a Wayland layer-shell surface positioned over the closing window,
-//! then renders the animation using wgpu.
+//! Creates a Wayland layer-shell surface over the closing window's monitor and
+//! draws the animation into shared-memory buffers, on the CPU or, for shaders,
+//! with wgpu and read back (see `render`).
+//!
+//! Frames are driven by `wl_surface.frame` callbacks: a new frame is only drawn
+//! once the compositor asks for one, and its progress comes from the predicted
+//! presentation time (see `frame_clock`).
 
 use std::sync::Arc;
-use std::time::Instant;
+use std::thread;
+use std::time::{Duration, Instant};
 
 use anyhow::{Context, Result};
 use smithay_client_toolkit::{
@@ -20,17 +26,30 @@ use smithay_client_toolkit::{
         },
         WaylandSurface,
     },
-    shm::{slot::SlotPool, Shm, ShmHandler},
+    shm::{Shm, ShmHandler},
 };
-use rayon::prelude::*;
+use rustix::event::{poll, PollFd, PollFlags};
 use tracing::{debug, info, warn};
 use wayland_client::{
     globals::registry_queue_init,
-    protocol::{wl_output, wl_shm, wl_surface},
-    Connection, QueueHandle,
+    protocol::{wl_output, wl_surface},
+    Connection, Dispatch, EventQueue, QueueHandle,
 };
+use wayland_protocols::wp::presentation_time::client::{wp_presentation, wp_presentation_feedback};
+
+use crate::animation::{Animation, Direction, Placement, WindowGeometry};
+use crate::damage::{clear_rect, Rect};
+use crate::frame_clock::FrameClock;
+use crate::pixel::Image;
+use crate::render::{CpuRenderer, Target, Texture};
+use crate::swapchain::Swapchain;
 
-use crate::animation::{Animation, WindowGeometry};
+/// Extra time to wait for frame callbacks before giving up on the animation.
+/// Covers a compositor that stops calling back (e.g. the output turned off).
+const FRAME_TIMEOUT_MS: u64 = 500;
+
+/// How long the last frame stays up after the handoff, about two frames at 60 Hz.
+const HANDOFF_DELAY: Duration = Duration::from_millis(32);
 
 /// Overlay state for Wayland event handling.
 struct OverlayState {
@@ -40,23 +59,36 @@ struct OverlayState {
     shm_state: Shm,
     layer_shell: LayerShell,
 
+    /// Presentation-time global, if the compositor supports it
+    presentation: Option<wp_presentation::WpPresentation>,
+
     /// The layer surface for our overlay
     layer_surface: Option<LayerSurface>,
 
-    /// SHM buffer pool for software rendering fallback
-    pool: Option<SlotPool>,
+    /// SHM buffers for software rendering, sized to the configured surface
+    swapchain: Option<Swapchain>,
+
+    /// Region drawn in the last committed frame (`None` before the first commit)
+    last_drawn: Option<Rect>,
 
     /// Animation parameters
     geometry: WindowGeometry,
     animation: Arc<dyn Animation>,
-    screenshot_data: Vec<u8>,
+    direction: Direction,
+    seed: u64,
+    /// Window screenshot with its mip chain
+    texture: Texture,
+    /// Logical bounds of the window's monitor, which the surface covers
+    monitor: Option<Rect>,
+    /// Built on the first configure, once the monitor is known
+    renderer: Option<Box<dyn CpuRenderer>>,
 
     /// Configured surface size (from compositor)
     surface_width: u32,
     surface_height: u32,
 
     /// Animation timing
-    start_time: Instant,
+    frame_clock: FrameClock,
     configured: bool,
     done: bool,
 }
@@ -75,13 +107,12 @@ impl OverlayState {
             debug!("Surface not configured yet");
             return;
         }
-        let stride = width * 4;
 
-        // Calculate animation progress
-        let elapsed = self.start_time.elapsed().as_secs_f32();
+        // Calculate animation progress at the time this frame will be shown
+        let elapsed = self.frame_clock.next_frame_time().as_secs_f32();
         let duration = self.animation.duration_ms() as f32 / 1000.0;
         let raw_progress = (elapsed / duration).min(1.0);
-        let progress = self.animation.ease(raw_progress);
+        let progress = self.animation.progress(raw_progress, self.direction);
 
         debug!("Drawing frame: progress={:.2}, elapsed={:.3}s", progress, elapsed);
 
@@ -92,233 +123,79 @@ impl OverlayState {
             return;
         }
 
-        // Get buffer from pool
-        let pool = self.pool.as_mut().expect("pool must exist");
-        let (buffer, canvas) = pool
-            .create_buffer(
-                width,
-                height,
-                stride,
-                wl_shm::Format::Argb8888,
-            )
-            .expect("create buffer");
-
-        // CPU-side vortex rendering
-        let render_start = std::time::Instant::now();
+        let (Some(ref mut swapchain), Some(renderer), Some(monitor)) =
+            (&mut self.swapchain, &self.renderer, self.monitor)
+        else {
+            debug!("No swapchain yet");
+            return;
+        };
+        let surface = layer_surface.wl_surface();
 
-        // Clear canvas to transparent
-        canvas.fill(0);
+        // Get a buffer the compositor is done reading
+        let Some(back) = swapchain.acquire() else {
+            // Every buffer is still busy; skip this frame and retry on the next callback
+            debug!("All buffers busy, skipping frame");
+            surface.frame(qh, surface.clone());
+            surface.commit();
+            return;
+        };
+
+        // CPU-side rendering
+        let render_start = std::time::Instant::now();
 
         let win_w = self.geometry.width as usize;
         let win_h = self.geometry.height as usize;
         let surf_w = width as usize;
         let surf_h = height as usize;
 
-        // Calculate offset within surface based on window position
-        // For multi-monitor: window coords are global, use rem_euclid to handle negative coords
-        // (left monitor has negative x values)
-        let offset_x = (self.geometry.x).rem_euclid(surf_w as i32) as usize;
-        let offset_y = (self.geometry.y).rem_euclid(surf_h as i32) as usize;
-
-        // Render vortex effect at the correct offset
-        render_cpu_vortex(
-            canvas,
-            &self.screenshot_data,
-            win_w,
-            win_h,
-            surf_w,
-            surf_h,
-            offset_x,
-            offset_y,
-            progress,
-        );
-
-        debug!("Drew vortex at ({},{}) {}x{} in {}x{} surface, progress={:.2}",
-               offset_x, offset_y, win_w, win_h, surf_w, surf_h, progress);
+        // Window coords are global; the surface covers the window's monitor
+        let offset_x = self.geometry.x - monitor.x;
+        let offset_y = self.geometry.y - monitor.y;
+
+        // Only the region the animation can still reach changes this frame
+        let window_rect = Rect::new(offset_x, offset_y, win_w as i32, win_h as i32);
+        let bounds = renderer
+            .bounds(&window_rect, progress)
+            .intersect(&Rect::new(0, 0, width, height));
+
+        // Clear whatever this buffer drew last time, then draw the new region
+        clear_rect(back.canvas, surf_w * 4, back.drawn);
+        let mut target = Target {
+            canvas: back.canvas,
+            width: surf_w,
+            window: window_rect,
+            bounds,
+        };
+        renderer.render(&mut target, &self.texture, progress);
+        *back.drawn = bounds;
+
+        debug!("Drew frame at ({},{}) {}x{} in {}x{} surface, bounds={:?}, progress={:.2}",
+               offset_x, offset_y, win_w, win_h, surf_w, surf_h, bounds, progress);
         debug!("Render took {:?}", render_start.elapsed());
 
-        // Attach and commit
-        let surface = layer_surface.wl_surface();
-        surface.attach(Some(buffer.wl_buffer()), 0, 0);
-        surface.damage_buffer(0, 0, width, height);
-        surface.commit();
+        // Attach, request the next frame callback and presentation feedback, then commit
+        if let Err(e) = back.buffer.attach_to(surface) {
+            warn!("Failed to attach buffer: {:?}", e);
+            return;
+        }
+
+        // Damage what changed since the last committed frame: its region and ours
+        let damage = match self.last_drawn {
+            Some(last) => last.union(&bounds),
+            None => Rect::new(0, 0, width, height),
+        };
+        surface.damage_buffer(damage.x, damage.y, damage.width, damage.height);
+        self.last_drawn = Some(bounds);
 
-        // Request next frame
         surface.frame(qh, surface.clone());
+        if let Some(ref presentation) = self.presentation {
+            presentation.feedback(surface, qh, ());
+        }
+        surface.commit();
+        self.frame_clock.frame_submitted();
     }
 }
 
-/// CPU rendering for vortex effect using rayon for parallelization.
-/// Spaghettification: content stretches into thin strands that spiral into the black hole.
-fn render_cpu_vortex(
-    canvas: &mut [u8],
-    screenshot_data: &[u8],
-    win_w: usize,
-    win_h: usize,
-    surf_w: usize,
-    _surf_h: usize,
-    offset_x: usize,
-    offset_y: usize,
-    progress: f32,
-) {
-    let center_x = 0.5f32;
-    let center_y = 0.5f32;
-    let tau = std::f32::consts::TAU;
-
-    // The singularity - black void at center
-    let singularity_radius = 0.03 + progress * 0.02;
-
-    // x^4 acceleration - slow start, fast end
-    let accel = progress.powi(4);
-
-    // Process rows in parallel
-    canvas
-        .par_chunks_mut(surf_w * 4)
-        .enumerate()
-        .for_each(|(surf_y, row)| {
-            if surf_y < offset_y || surf_y >= offset_y + win_h {
-                return;
-            }
-            let y = surf_y - offset_y;
-            let v = y as f32 / win_h as f32;
-            let dy = v - center_y;
-
-            let x_end = (offset_x + win_w).min(surf_w);
-            for surf_x in offset_x..x_end {
-                let x = surf_x - offset_x;
-                let u = x as f32 / win_w as f32;
-                let dx = u - center_x;
-
-                let dist = (dx * dx + dy * dy).sqrt();
-                let angle = dy.atan2(dx);
-                let dst_idx = surf_x * 4;
-
-                // Inside the singularity = SOLID BLACK
-                if dist < singularity_radius {
-                    row[dst_idx] = 0;
-                    row[dst_idx + 1] = 0;
-                    row[dst_idx + 2] = 0;
-                    row[dst_idx + 3] = 255;
-                    continue;
-                }
-
-                // Accretion disk - many thin wispy light strands
-                let disk_outer = singularity_radius + 0.06;
-                if dist < disk_outer && dist > singularity_radius {
-                    let num_strands = 32;
-                    let rotation = accel * tau * 3.0;
-
-                    let mut total_intensity = 0.0f32;
-
-                    for i in 0..num_strands {
-                        // Pseudo-random per strand (deterministic based on index)
-                        let seed = i as f32 * 7.31;
-                        let rand1 = (seed.sin() * 43758.5453).fract();
-                        let rand2 = ((seed + 1.0).cos() * 22578.1459).fract();
-                        let rand3 = ((seed * 2.3).sin() * 19283.2918).fract();
-
-                        // Variable properties per strand
-                        let strand_brightness = 0.3 + rand1 * 0.7; // 0.3 to 1.0
-                        let strand_length = 0.02 + rand2 * 0.04; // How far it extends
-                        let strand_width = 0.008 + rand3 * 0.015; // Very thin
-                        let spiral_tight = 12.0 + rand1 * 8.0; // 12-20, tight spirals
-
-                        let strand_base = (i as f32 / num_strands as f32) * tau;
-                        let expected_angle = strand_base + spiral_tight * dist + rotation;
-
-                        let mut angle_diff = (angle - expected_angle).rem_euclid(tau);
-                        if angle_diff > tau / 2.0 {
-                            angle_diff = tau - angle_diff;
-                        }
-
-                        // Only render if within this strand's length
-                        let strand_start = singularity_radius;
-                        let strand_end = singularity_radius + strand_length;
-                        if dist > strand_start && dist < strand_end && angle_diff < strand_width {
-                            let core = 1.0 - (angle_diff / strand_width);
-                            // Fade at the outer tip
-                            let tip_fade = 1.0 - ((dist - strand_start) / strand_length).powf(2.0);
-                            let intensity = core.powf(2.0) * tip_fade * strand_brightness;
-                            total_intensity += intensity;
-                        }
-                    }
-
-                    if total_intensity > 0.01 {
-                        let i = total_intensity.min(1.0);
-                        // Purple with brightness variation
-                        let r = (90.0 + 90.0 * i) * i;
-                        let g = (20.0 + 35.0 * i) * i;
-                        let b = (150.0 + 70.0 * i) * i;
-
-                        row[dst_idx] = b.min(255.0) as u8;
-                        row[dst_idx + 1] = g.min(255.0) as u8;
-                        row[dst_idx + 2] = r.min(255.0) as u8;
-                        row[dst_idx + 3] = 255;
-                        continue;
-                    }
-                }
-
-                // === SPIRAL SPAGHETTIFICATION ===
-                // The key: stretch ALONG the spiral (tangentially), compress ACROSS it (radially)
-                // This creates thin strands that wind around the center
-
-                // How much to wind around - more rotations as we approach center
-                // and as animation progresses
-                let spiral_tightness = 1.0 / (dist + 0.05);
-                let total_rotation = accel * spiral_tightness * 3.0 * tau;
-
-                // Tangential stretch: sample from positions that are "behind" on the spiral
-                // This elongates content along the spiral path
-                let tangential_stretch = accel * spiral_tightness * 0.4;
-
-                // Radial compression: as things stretch tangentially, they thin radially
-                // Sample from further out = content compressing inward
-                let radial_compression = 1.0 + accel * spiral_tightness * 2.0;
-
-                // Calculate where to sample from
-                // Unwind the spiral: go backwards along the spiral path
-                let sample_angle = angle - total_rotation - tangential_stretch;
-                let sample_dist = dist * radial_compression;
-
-                let sample_u = center_x + sample_angle.cos() * sample_dist;
-                let sample_v = center_y + sample_angle.sin() * sample_dist;
-
-                // Outside bounds = that strand has been consumed
-                if sample_u < 0.0 || sample_u > 1.0 || sample_v < 0.0 || sample_v > 1.0 {
-                    row[dst_idx] = 0;
-                    row[dst_idx + 1] = 0;
-                    row[dst_idx + 2] = 0;
-                    row[dst_idx + 3] = 0;
-                    continue;
-                }
-
-                let src_x = (sample_u * win_w as f32) as usize;
-                let src_y = (sample_v * win_h as f32) as usize;
-                let src_idx = (src_y * win_w + src_x) * 4;
-
-                if src_idx + 3 < screenshot_data.len() && dst_idx + 3 < row.len() {
-                    let r = screenshot_data[src_idx];
-                    let g = screenshot_data[src_idx + 1];
-                    let b = screenshot_data[src_idx + 2];
-
-                    // Darken as it approaches the hole
-                    let near_hole = (0.2 - dist).max(0.0) / 0.2;
-                    let darkness = 1.0 - near_hole * 0.5;
-
-                    row[dst_idx] = (b as f32 * darkness) as u8;
-                    row[dst_idx + 1] = (g as f32 * darkness) as u8;
-                    row[dst_idx + 2] = (r as f32 * darkness) as u8;
-                    row[dst_idx + 3] = 255;
-                } else {
-                    row[dst_idx] = 0;
-                    row[dst_idx + 1] = 0;
-                    row[dst_idx + 2] = 0;
-                    row[dst_idx + 3] = 0;
-                }
-            }
-        });
-}
-
 // Implement required SCTK traits
 
 impl CompositorHandler for OverlayState {
@@ -357,8 +234,16 @@ impl CompositorHandler for OverlayState {
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
         _surface: &wl_surface::WlSurface,
-        _output: &wl_output::WlOutput,
+        output: &wl_output::WlOutput,
     ) {
+        // Refresh rate guess until presentation feedback reports the real one
+        let refresh = self
+            .output_state
+            .info(output)
+            .and_then(|info| info.modes.iter().find(|m| m.current).map(|m| m.refresh_rate));
+        if let Some(refresh_mhz) = refresh {
+            self.frame_clock.set_output_refresh(refresh_mhz);
+        }
     }
 
     fn surface_leave(
@@ -423,14 +308,43 @@ impl LayerShellHandler for OverlayState {
 
         self.configured = true;
 
-        // Create buffer pool if not exists (use configured size)
-        if self.pool.is_none() && self.surface_width > 0 && self.surface_height > 0 {
-            let pool = SlotPool::new(
-                (self.surface_width * self.surface_height * 4) as usize,
-                &self.shm_state,
-            )
-            .expect("create pool");
-            self.pool = Some(pool);
+        // (Re)create the swapchain for the configured size
+        let (width, height) = (self.surface_width, self.surface_height);
+        let stale = self
+            .swapchain
+            .as_ref()
+            .is_none_or(|swapchain| !swapchain.matches(width, height));
+        if stale && width > 0 && height > 0 {
+            match Swapchain::new(&self.shm_state, width, height) {
+                Ok(swapchain) => self.swapchain = Some(swapchain),
+                Err(e) => {
+                    warn!("Failed to allocate buffers: {:#}", e);
+                    self.done = true;
+                    return;
+                }
+            }
+        }
+
+        if self.renderer.is_none() {
+            // Without output positions, assume monitors tile the layout from the origin
+            let (x, y) = (self.geometry.x, self.geometry.y);
+            let monitor = self.monitor.unwrap_or_else(|| {
+                let (w, h) = (width.max(1) as i32, height.max(1) as i32);
+                Rect::new(x - x.rem_euclid(w), y - y.rem_euclid(h), w, h)
+            });
+            let placement = Placement {
+                window: self.geometry,
+                monitor,
+            };
+            self.monitor = Some(monitor);
+            match self.animation.renderer(&placement, self.seed) {
+                Ok(renderer) => self.renderer = Some(renderer),
+                Err(e) => {
+                    warn!("Can't play '{}': {:#}", self.animation.name(), e);
+                    self.done = true;
+                    return;
+                }
+            }
         }
 
         // First draw
@@ -451,17 +365,89 @@ impl ProvidesRegistryState for OverlayState {
     registry_handlers![OutputState];
 }
 
+impl Dispatch<wp_presentation::WpPresentation, ()> for OverlayState {
+    fn event(
+        state: &mut Self,
+        _proxy: &wp_presentation::WpPresentation,
+        event: wp_presentation::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        if let wp_presentation::Event::ClockId { clk_id } = event {
+            debug!("Presentation clock: {}", clk_id);
+            state.frame_clock.set_clock_id(clk_id);
+        }
+    }
+}
+
+impl Dispatch<wp_presentation_feedback::WpPresentationFeedback, ()> for OverlayState {
+    fn event(
+        state: &mut Self,
+        _proxy: &wp_presentation_feedback::WpPresentationFeedback,
+        event: wp_presentation_feedback::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            wp_presentation_feedback::Event::Presented {
+                tv_sec_hi,
+                tv_sec_lo,
+                tv_nsec,
+                refresh,
+                seq_hi,
+                seq_lo,
+                ..
+            } => {
+                let secs = ((tv_sec_hi as u64) << 32) | tv_sec_lo as u64;
+                let seq = ((seq_hi as u64) << 32) | seq_lo as u64;
+                state
+                    .frame_clock
+                    .frame_presented(Duration::new(secs, tv_nsec), refresh, seq);
+            }
+            wp_presentation_feedback::Event::Discarded => {
+                debug!("Frame discarded");
+                state.frame_clock.frame_discarded();
+            }
+            _ => {}
+        }
+    }
+}
+
 delegate_compositor!(OverlayState);
 delegate_output!(OverlayState);
 delegate_layer!(OverlayState);
 delegate_shm!(OverlayState);
 delegate_registry!(OverlayState);
 
+/// What the caller does to the window under the overlay, and when.
+pub struct Hooks<C, H> {
+    /// Runs once the first frame is committed, so the window can go without
+    /// a gap before the overlay shows
+    pub covered: C,
+    /// Runs once the last frame is up, while the overlay still covers the
+    /// window, so whatever shows the window next can take over unseen
+    pub handoff: H,
+}
+
+impl Hooks<fn(), fn()> {
+    /// Leave the window alone.
+    pub fn none() -> Self {
+        Self { covered: || (), handoff: || () }
+    }
+}
+
 /// Run an animation overlay at the given position.
+///
+/// `seed` varies the randomized parts of the effect from one close to the next.
 pub fn run_overlay(
     geometry: WindowGeometry,
-    screenshot_data: Vec<u8>,
+    screenshot: Image,
     animation: Arc<dyn Animation>,
+    direction: Direction,
+    seed: u64,
+    hooks: Hooks<impl FnOnce(), impl FnOnce()>,
 ) -> Result<()> {
     info!(
         "Starting overlay at ({}, {}) {}x{}",
@@ -471,6 +457,9 @@ pub fn run_overlay(
     // Get duration before moving animation
     let duration_ms = animation.duration_ms();
 
+    // Mip chain is built once up front so every frame can sample it filtered
+    let texture = Texture::new(screenshot);
+
     let conn = Connection::connect_to_env().context("Failed to connect to Wayland")?;
 
     let (globals, mut event_queue) =
@@ -481,26 +470,10 @@ pub fn run_overlay(
         CompositorState::bind(&globals, &qh).context("wl_compositor not available")?;
     let layer_shell = LayerShell::bind(&globals, &qh).context("layer_shell not available")?;
     let shm_state = Shm::bind(&globals, &qh).context("wl_shm not available")?;
-
-    let surface = compositor_state.create_surface(&qh);
-
-    // Create layer surface - overlay layer, anchored to top-left with margin offset
-    let layer_surface = layer_shell.create_layer_surface(
-        &qh,
-        surface,
-        Layer::Overlay,
-        Some("hypr-vortex"),
-        None, // No specific output - appears on all
-    );
-
-    // TEST: Anchor to all edges (fullscreen on output) to verify surface shows
-    layer_surface.set_anchor(Anchor::TOP | Anchor::BOTTOM | Anchor::LEFT | Anchor::RIGHT);
-    layer_surface.set_size(0, 0); // 0 = use anchor constraints (fullscreen)
-    layer_surface.set_keyboard_interactivity(KeyboardInteractivity::None);
-    layer_surface.set_exclusive_zone(-1); // Don't reserve space
-    layer_surface.commit();
-
-    info!("Layer surface created with fullscreen anchor for testing");
+    let presentation = globals
+        .bind::<wp_presentation::WpPresentation, _, _>(&qh, 1..=1, ())
+        .map_err(|e| warn!("wp_presentation not available ({}), using wall-clock timing", e))
+        .ok();
 
     let mut state = OverlayState {
         registry_state: RegistryState::new(&globals),
@@ -508,51 +481,136 @@ pub fn run_overlay(
         output_state: OutputState::new(&globals, &qh),
         shm_state,
         layer_shell,
-        layer_surface: Some(layer_surface),
-        pool: None,
+        presentation,
+        layer_surface: None,
+        swapchain: None,
+        last_drawn: None,
         geometry,
         animation,
-        screenshot_data,
+        direction,
+        seed,
+        texture,
+        monitor: None,
+        renderer: None,
         surface_width: 0,
         surface_height: 0,
-        start_time: Instant::now(),
+        frame_clock: FrameClock::new(),
         configured: false,
         done: false,
     };
 
+    // Output positions arrive in reply to binding the outputs
+    event_queue
+        .roundtrip(&mut state)
+        .context("Failed to query outputs")?;
+    let output = monitor_of(&state.output_state, &geometry);
+    match &output {
+        Some((_, monitor)) => {
+            debug!("Window is on the monitor at {:?}", monitor);
+            state.monitor = Some(*monitor);
+        }
+        None => warn!("No output reported a position, guessing the window's monitor"),
+    }
+
+    // Fullscreen overlay layer on the window's monitor
+    let surface = state.compositor_state.create_surface(&qh);
+    let layer_surface = state.layer_shell.create_layer_surface(
+        &qh,
+        surface,
+        Layer::Overlay,
+        Some("hypr-vortex"),
+        output.as_ref().map(|(output, _)| output),
+    );
+    layer_surface.set_anchor(Anchor::TOP | Anchor::BOTTOM | Anchor::LEFT | Anchor::RIGHT);
+    layer_surface.set_size(0, 0); // 0 = use anchor constraints (fullscreen)
+    layer_surface.set_keyboard_interactivity(KeyboardInteractivity::None);
+    layer_surface.set_exclusive_zone(-1); // Don't reserve space
+    layer_surface.commit();
+    state.layer_surface = Some(layer_surface);
+
     // Wait for initial configure event
     while !state.configured && !state.done {
         event_queue.blocking_dispatch(&mut state)?;
     }
 
-    // Animation loop with forced frame timing
-    let animation_duration = std::time::Duration::from_millis(duration_ms + 50);
-    let start = std::time::Instant::now();
-    let frame_time = std::time::Duration::from_millis(16); // ~60fps
+    // Animation loop: every frame after the first is drawn from a frame callback
+    let deadline = Instant::now() + Duration::from_millis(duration_ms + FRAME_TIMEOUT_MS);
 
-    while !state.done && start.elapsed() < animation_duration {
-        let frame_start = std::time::Instant::now();
+    let mut covered = Some(hooks.covered);
+    while !state.done {
+        if state.last_drawn.is_some() {
+            if let Some(covered) = covered.take() {
+                covered();
+            }
+        }
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            warn!("No frame callbacks from compositor, ending animation early");
+            break;
+        }
+        dispatch_with_timeout(&mut event_queue, &mut state, remaining)?;
+    }
 
-        // Process any pending Wayland events
-        let _ = event_queue.dispatch_pending(&mut state);
+    (hooks.handoff)();
+    // Give the compositor a frame to show the handoff before the overlay goes
+    thread::sleep(HANDOFF_DELAY);
 
-        // Draw frame
-        if !state.done {
-            state.draw(&qh);
-        }
+    state.frame_clock.report();
+    if let Some(busy) = state.swapchain.as_ref().map(Swapchain::busy_frames).filter(|&n| n > 0) {
+        debug!("Skipped {} frames waiting for buffer release", busy);
+    }
+    info!("Animation complete");
+    Ok(())
+}
 
-        // Flush to ensure buffer is sent
-        if let Err(e) = event_queue.flush() {
-            warn!("Flush error: {}", e);
-        }
+/// The output the window is mostly on and its logical bounds in layout
+/// pixels, if any output reported where it is.
+fn monitor_of(
+    outputs: &OutputState,
+    window: &WindowGeometry,
+) -> Option<(wl_output::WlOutput, Rect)> {
+    let window = Rect::new(window.x, window.y, window.width as i32, window.height as i32);
+    outputs
+        .outputs()
+        .filter_map(|output| {
+            let info = outputs.info(&output)?;
+            let (x, y) = info.logical_position?;
+            let (width, height) = info.logical_size?;
+            let bounds = Rect::new(x, y, width, height);
+            let overlap = bounds.intersect(&window);
+            let area = overlap.width as i64 * overlap.height as i64;
+            (!overlap.is_empty()).then_some((output, bounds, area))
+        })
+        .max_by_key(|&(_, _, area)| area)
+        .map(|(output, bounds, _)| (output, bounds))
+}
 
-        // Sleep remainder of frame time
-        let elapsed = frame_start.elapsed();
-        if elapsed < frame_time {
-            std::thread::sleep(frame_time - elapsed);
+/// Dispatch Wayland events, waiting at most `timeout` for new ones to arrive.
+pub fn dispatch_with_timeout<S>(
+    event_queue: &mut EventQueue<S>,
+    state: &mut S,
+    timeout: Duration,
+) -> Result<()> {
+    event_queue.flush().context("Failed to flush Wayland connection")?;
+
+    if let Some(guard) = event_queue.prepare_read() {
+        let timeout_ms = timeout.as_millis().min(i32::MAX as u128) as i32;
+        let ready = {
+            let fd = guard.connection_fd();
+            let mut fds = [PollFd::new(&fd, PollFlags::IN)];
+            match poll(&mut fds, timeout_ms) {
+                Ok(n) => n > 0,
+                Err(rustix::io::Errno::INTR) => false,
+                Err(e) => return Err(e).context("Failed to poll Wayland connection"),
+            }
+        };
+        if ready {
+            guard.read().context("Failed to read Wayland events")?;
         }
     }
 
-    info!("Animation complete");
+    event_queue
+        .dispatch_pending(state)
+        .context("Failed to dispatch Wayland events")?;
     Ok(())
 }
@@ -0,0 +1,437 @@
+//! Typed animation parameters: schema, validated values and uniform packing.
+//!
+//! Each animation declares its parameters once as a `ParamSpec` list. Values
+//! from the config file or a request are checked against it, and the current
+//! values are packed in schema order into `AnimationUniforms::params`, so a
+//! shader reads them as consecutive f32 fields (a color takes four).
+
+use std::fmt;
+
+use serde_json::Value;
+use thiserror::Error;
+
+/// Type and valid range of a parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamKind {
+    Float { min: f32, max: f32 },
+    Int { min: i64, max: i64 },
+    Bool,
+    /// RGBA in `0.0..=1.0`, straight alpha
+    Color,
+}
+
+impl ParamKind {
+    /// Uniform slots (f32s) a value of this kind occupies.
+    pub fn slots(self) -> usize {
+        match self {
+            ParamKind::Color => 4,
+            _ => 1,
+        }
+    }
+
+    fn type_name(self) -> &'static str {
+        match self {
+            ParamKind::Float { .. } => "a number",
+            ParamKind::Int { .. } => "an integer",
+            ParamKind::Bool => "true or false",
+            ParamKind::Color => "a color (\"#rrggbb[aa]\", \"rgba(rrggbbaa)\" or [r, g, b, a])",
+        }
+    }
+}
+
+/// A parameter value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamValue {
+    Float(f32),
+    Int(i64),
+    Bool(bool),
+    Color([f32; 4]),
+}
+
+impl ParamValue {
+    /// Write this value into uniform slots.
+    fn pack(self, out: &mut [f32]) {
+        match self {
+            ParamValue::Float(v) => out[0] = v,
+            ParamValue::Int(v) => out[0] = v as f32,
+            ParamValue::Bool(v) => out[0] = if v { 1.0 } else { 0.0 },
+            ParamValue::Color(c) => out[..4].copy_from_slice(&c),
+        }
+    }
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamValue::Float(v) => write!(f, "{}", v),
+            ParamValue::Int(v) => write!(f, "{}", v),
+            ParamValue::Bool(v) => write!(f, "{}", v),
+            ParamValue::Color([r, g, b, a]) => {
+                let byte = |c: f32| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+                write!(f, "#{:02x}{:02x}{:02x}{:02x}", byte(*r), byte(*g), byte(*b), byte(*a))
+            }
+        }
+    }
+}
+
+/// Declaration of one parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub default: ParamValue,
+    pub description: &'static str,
+}
+
+impl ParamSpec {
+    pub const fn float(
+        name: &'static str,
+        min: f32,
+        max: f32,
+        default: f32,
+        description: &'static str,
+    ) -> Self {
+        Self {
+            name,
+            kind: ParamKind::Float { min, max },
+            default: ParamValue::Float(default),
+            description,
+        }
+    }
+
+    pub const fn int(
+        name: &'static str,
+        min: i64,
+        max: i64,
+        default: i64,
+        description: &'static str,
+    ) -> Self {
+        Self {
+            name,
+            kind: ParamKind::Int { min, max },
+            default: ParamValue::Int(default),
+            description,
+        }
+    }
+
+    pub const fn bool(name: &'static str, default: bool, description: &'static str) -> Self {
+        Self {
+            name,
+            kind: ParamKind::Bool,
+            default: ParamValue::Bool(default),
+            description,
+        }
+    }
+
+    pub const fn color(name: &'static str, default: [f32; 4], description: &'static str) -> Self {
+        Self {
+            name,
+            kind: ParamKind::Color,
+            default: ParamValue::Color(default),
+            description,
+        }
+    }
+
+    /// Human-readable type and range, for listings.
+    pub fn kind_summary(&self) -> String {
+        match self.kind {
+            ParamKind::Float { min, max } => format!("float {}..{}", min, max),
+            ParamKind::Int { min, max } => format!("int {}..{}", min, max),
+            ParamKind::Bool => "bool".to_string(),
+            ParamKind::Color => "color".to_string(),
+        }
+    }
+
+    /// Check `value` against this spec, converting where it's lossless.
+    fn check(&self, value: ParamValue) -> Result<ParamValue, ParamError> {
+        let checked = match (self.kind, value) {
+            (ParamKind::Float { min, max }, ParamValue::Float(v)) => {
+                (v.is_finite() && (min..=max).contains(&v)).then_some(value)
+            }
+            (ParamKind::Float { min, max }, ParamValue::Int(v)) => {
+                let v = v as f32;
+                (min..=max).contains(&v).then_some(ParamValue::Float(v))
+            }
+            (ParamKind::Int { min, max }, ParamValue::Int(v)) => (min..=max).contains(&v).then_some(value),
+            (ParamKind::Bool, ParamValue::Bool(_)) => Some(value),
+            (ParamKind::Color, ParamValue::Color(c)) => {
+                c.iter().all(|v| (0.0..=1.0).contains(v)).then_some(value)
+            }
+            _ => {
+                return Err(ParamError::WrongType {
+                    name: self.name.to_string(),
+                    expected: self.kind.type_name(),
+                    got: value.to_string(),
+                })
+            }
+        };
+
+        checked.ok_or_else(|| ParamError::OutOfRange {
+            name: self.name.to_string(),
+            range: self.kind_summary(),
+            value: value.to_string(),
+        })
+    }
+
+    /// Convert and check a JSON value for this parameter.
+    pub fn parse_json(&self, value: &Value) -> Result<ParamValue, ParamError> {
+        let parsed = match (self.kind, value) {
+            (ParamKind::Int { .. }, Value::Number(n)) => n.as_i64().map(ParamValue::Int),
+            (ParamKind::Float { .. }, Value::Number(n)) => n.as_f64().map(|v| ParamValue::Float(v as f32)),
+            (ParamKind::Bool, Value::Bool(b)) => Some(ParamValue::Bool(*b)),
+            (ParamKind::Color, Value::String(s)) => parse_color(s).map(ParamValue::Color),
+            (ParamKind::Color, Value::Array(items)) if (3..=4).contains(&items.len()) => {
+                let mut color = [1.0; 4];
+                let channels: Option<Vec<f32>> = items.iter().map(|v| v.as_f64().map(|c| c as f32)).collect();
+                channels.map(|channels| {
+                    color[..channels.len()].copy_from_slice(&channels);
+                    ParamValue::Color(color)
+                })
+            }
+            _ => None,
+        };
+        let parsed = parsed.ok_or_else(|| ParamError::WrongType {
+            name: self.name.to_string(),
+            expected: self.kind.type_name(),
+            got: value.to_string(),
+        })?;
+        self.check(parsed)
+    }
+}
+
+/// Why a parameter override was rejected.
+#[derive(Debug, Error)]
+pub enum ParamError {
+    #[error("unknown parameter '{name}' (available: {available})")]
+    Unknown { name: String, available: String },
+    #[error("{name}: expected {expected}, got {got}")]
+    WrongType {
+        name: String,
+        expected: &'static str,
+        got: String,
+    },
+    #[error("{name}: {value} is outside {range}")]
+    OutOfRange {
+        name: String,
+        range: String,
+        value: String,
+    },
+}
+
+/// A value for every parameter in a schema, in schema order.
+#[derive(Debug, Clone)]
+pub struct Params {
+    schema: &'static [ParamSpec],
+    values: Vec<ParamValue>,
+}
+
+impl Params {
+    /// All parameters at their defaults.
+    pub fn new(schema: &'static [ParamSpec]) -> Self {
+        Self {
+            schema,
+            values: schema.iter().map(|spec| spec.default).collect(),
+        }
+    }
+
+    pub fn schema(&self) -> &'static [ParamSpec] {
+        self.schema
+    }
+
+    pub fn get(&self, name: &str) -> Option<ParamValue> {
+        let index = self.schema.iter().position(|spec| spec.name == name)?;
+        Some(self.values[index])
+    }
+
+    /// Numeric value of `name` (bools as 0/1, colors as their red channel).
+    ///
+    /// Panics if `name` isn't in the schema: callers only ask for their own parameters.
+    pub fn float(&self, name: &str) -> f32 {
+        let mut slot = [0.0; 4];
+        self.get(name)
+            .unwrap_or_else(|| panic!("no parameter '{}' in schema", name))
+            .pack(&mut slot);
+        slot[0]
+    }
+
+    /// Validate and set one parameter.
+    pub fn set(&mut self, name: &str, value: ParamValue) -> Result<(), ParamError> {
+        let Some(index) = self.schema.iter().position(|spec| spec.name == name) else {
+            return Err(self.unknown(name));
+        };
+        self.values[index] = self.schema[index].check(value)?;
+        Ok(())
+    }
+
+    /// Validate and set one parameter from a JSON value (config files are
+    /// converted to JSON first, so both sources share this path).
+    pub fn set_json(&mut self, name: &str, value: &Value) -> Result<(), ParamError> {
+        let Some(index) = self.schema.iter().position(|spec| spec.name == name) else {
+            return Err(self.unknown(name));
+        };
+        self.values[index] = self.schema[index].parse_json(value)?;
+        Ok(())
+    }
+
+    /// Uniform slots used by all parameters together.
+    pub fn slots(&self) -> usize {
+        self.schema.iter().map(|spec| spec.kind.slots()).sum()
+    }
+
+    /// Pack values into uniform slots in schema order; values that don't fit are dropped.
+    ///
+    /// There's no padding, and a shader's `vec4<f32>` must start on a 16-byte
+    /// boundary, so schemas list their colors first (the slots start aligned).
+    pub fn pack(&self, out: &mut [f32]) {
+        let mut offset = 0;
+        for (spec, value) in self.schema.iter().zip(&self.values) {
+            let slots = spec.kind.slots();
+            let Some(dst) = out.get_mut(offset..offset + slots) else {
+                break;
+            };
+            value.pack(dst);
+            offset += slots;
+        }
+    }
+
+    fn unknown(&self, name: &str) -> ParamError {
+        let available: Vec<&str> = self.schema.iter().map(|spec| spec.name).collect();
+        ParamError::Unknown {
+            name: name.to_string(),
+            available: if available.is_empty() {
+                "none".to_string()
+            } else {
+                available.join(", ")
+            },
+        }
+    }
+}
+
+/// Parse `#rrggbb`, `#rrggbbaa`, Hyprland's `rgb(rrggbb)` / `rgba(rrggbbaa)`
+/// or `0xAARRGGBB`.
+pub fn parse_color(s: &str) -> Option<[f32; 4]> {
+    let s = s.trim();
+    let hex = |digits: &str| -> Option<Vec<f32>> {
+        if !digits.len().is_multiple_of(2) || !digits.is_ascii() {
+            return None;
+        }
+        (0..digits.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).ok().map(|b| b as f32 / 255.0))
+            .collect()
+    };
+
+    let (channels, alpha_first) = if let Some(argb) = s.strip_prefix("0x") {
+        (hex(argb)?, true)
+    } else if let Some(rest) = s.strip_prefix('#') {
+        (hex(rest)?, false)
+    } else if let Some(rest) = s.strip_prefix("rgba(").or_else(|| s.strip_prefix("rgb(")) {
+        (hex(rest.strip_suffix(')')?)?, false)
+    } else {
+        return None;
+    };
+
+    match (channels.as_slice(), alpha_first) {
+        ([r, g, b], false) => Some([*r, *g, *b, 1.0]),
+        ([r, g, b, a], false) => Some([*r, *g, *b, *a]),
+        ([a, r, g, b], true) => Some([*r, *g, *b, *a]),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    const SCHEMA: &[ParamSpec] = &[
+        ParamSpec::float("speed", 0.0, 10.0, 2.0, ""),
+        ParamSpec::color("tint", [1.0, 0.5, 0.0, 1.0], ""),
+        ParamSpec::int("count", 1, 8, 4, ""),
+        ParamSpec::bool("flip", false, ""),
+    ];
+
+    fn close(a: [f32; 4], b: [f32; 4]) -> bool {
+        a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-6)
+    }
+
+    #[test]
+    fn parses_every_color_form() {
+        let orange = [1.0, 0x80 as f32 / 255.0, 0.0, 1.0];
+        let forms = [
+            "#ff8000",
+            "#ff8000ff",
+            "rgb(ff8000)",
+            "rgba(ff8000ff)",
+            "0xffff8000",
+            " #FF8000 ",
+        ];
+        for text in forms {
+            let color = parse_color(text).unwrap_or_else(|| panic!("{} didn't parse", text));
+            assert!(close(color, orange), "{} parsed as {:?}", text, color);
+        }
+        // 0x is alpha first, the others alpha last
+        assert!(close(parse_color("0x80ff0000").unwrap(), [1.0, 0.0, 0.0, 0x80 as f32 / 255.0]));
+        assert!(close(parse_color("#ff000080").unwrap(), [1.0, 0.0, 0.0, 0x80 as f32 / 255.0]));
+    }
+
+    #[test]
+    fn rejects_malformed_colors() {
+        for text in [
+            "",
+            "#",
+            "#fff",
+            "#ff80",
+            "#ff8000f",
+            "#gg8000",
+            "ff8000",
+            "rgb(ff8000",
+            "rgba(ff8000ff",
+            "rgb()",
+            "0xff8000",
+            "#ff80é0",
+            "hsl(ff8000)",
+        ] {
+            assert_eq!(parse_color(text), None, "{:?} parsed", text);
+        }
+    }
+
+    #[test]
+    fn json_values_are_typed_and_range_checked() {
+        let mut params = Params::new(SCHEMA);
+        params.set_json("speed", &json!(3)).unwrap();
+        assert_eq!(params.get("speed"), Some(ParamValue::Float(3.0)));
+        params.set_json("tint", &json!([0.0, 0.5, 1.0])).unwrap();
+        assert_eq!(params.get("tint"), Some(ParamValue::Color([0.0, 0.5, 1.0, 1.0])));
+        params.set_json("tint", &json!("#00000000")).unwrap();
+        params.set_json("flip", &json!(true)).unwrap();
+
+        let err = |name, value| params.clone().set_json(name, &value).unwrap_err();
+        assert!(matches!(err("speed", json!(10.5)), ParamError::OutOfRange { .. }));
+        assert!(matches!(err("speed", json!("fast")), ParamError::WrongType { .. }));
+        assert!(matches!(err("count", json!(2.5)), ParamError::WrongType { .. }));
+        assert!(matches!(err("count", json!(0)), ParamError::OutOfRange { .. }));
+        assert!(matches!(err("flip", json!(1)), ParamError::WrongType { .. }));
+        assert!(matches!(err("tint", json!("#ff80")), ParamError::WrongType { .. }));
+        assert!(matches!(err("tint", json!([1.0, 2.0, 0.0])), ParamError::OutOfRange { .. }));
+        assert!(matches!(err("tint", json!([1.0, 0.0])), ParamError::WrongType { .. }));
+        assert!(matches!(err("sped", json!(1)), ParamError::Unknown { .. }));
+    }
+
+    #[test]
+    fn packs_in_schema_order() {
+        let mut params = Params::new(SCHEMA);
+        params.set("flip", ParamValue::Bool(true)).unwrap();
+        assert_eq!(params.slots(), 7);
+
+        let mut slots = [-1.0; 8];
+        params.pack(&mut slots);
+        assert_eq!(slots, [2.0, 1.0, 0.5, 0.0, 1.0, 4.0, 1.0, -1.0]);
+
+        // Values that don't fit are dropped, not split
+        let mut short = [-1.0; 4];
+        params.pack(&mut short);
+        assert_eq!(short, [2.0, -1.0, -1.0, -1.0]);
+    }
+}
@@ -0,0 +1,164 @@
+//! Typed pixel buffers and the premultiplied-alpha pipeline.
+//!
+//! Everything after capture is premultiplied: filtering or blending straight
+//! alpha pulls the color of transparent texels into their neighbors, which
+//! shows up as black fringes around translucent windows and rounded corners.
+//! Straight alpha only exists at the edges (decoders hand it to us) and is
+//! converted once when the texture is built.
+
+use wayland_client::protocol::wl_shm;
+
+/// Byte order and alpha convention of a 4-byte-per-pixel buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// R, G, B, A with straight (unassociated) alpha, as image decoders return
+    Rgba8,
+    /// R, G, B, A with color already multiplied by alpha
+    Rgba8Premultiplied,
+    /// B, G, R, A premultiplied: what `wl_shm` calls ARGB8888 on little-endian
+    Bgra8Premultiplied,
+}
+
+impl PixelFormat {
+    /// Bytes per pixel; every format here is 8 bits per channel.
+    pub const BYTES_PER_PIXEL: usize = 4;
+
+    pub fn is_premultiplied(self) -> bool {
+        !matches!(self, PixelFormat::Rgba8)
+    }
+
+    /// The `wl_shm` format with this exact layout, if the compositor can take it as is.
+    ///
+    /// `wl_shm` formats are always premultiplied and named by little-endian word order.
+    pub fn shm_format(self) -> Option<wl_shm::Format> {
+        match self {
+            PixelFormat::Rgba8 => None,
+            PixelFormat::Rgba8Premultiplied => Some(wl_shm::Format::Abgr8888),
+            PixelFormat::Bgra8Premultiplied => Some(wl_shm::Format::Argb8888),
+        }
+    }
+
+    /// Read one pixel as premultiplied RGBA.
+    #[inline]
+    fn decode(self, px: &[u8]) -> [u8; 4] {
+        match self {
+            PixelFormat::Rgba8 => premultiply([px[0], px[1], px[2], px[3]]),
+            PixelFormat::Rgba8Premultiplied => [px[0], px[1], px[2], px[3]],
+            PixelFormat::Bgra8Premultiplied => [px[2], px[1], px[0], px[3]],
+        }
+    }
+
+    /// Write one premultiplied RGBA pixel in this format.
+    #[inline]
+    fn encode(self, rgba: [u8; 4], px: &mut [u8]) {
+        let out = match self {
+            PixelFormat::Rgba8 => unpremultiply(rgba),
+            PixelFormat::Rgba8Premultiplied => rgba,
+            PixelFormat::Bgra8Premultiplied => [rgba[2], rgba[1], rgba[0], rgba[3]],
+        };
+        px.copy_from_slice(&out);
+    }
+}
+
+/// Tightly packed pixel buffer tagged with its format.
+pub struct Image {
+    pub pixels: Vec<u8>,
+    pub width: usize,
+    pub height: usize,
+    pub format: PixelFormat,
+}
+
+impl Image {
+    pub fn new(pixels: Vec<u8>, width: usize, height: usize, format: PixelFormat) -> Self {
+        Self {
+            pixels,
+            width,
+            height,
+            format,
+        }
+    }
+
+    /// Convert to `format`, reusing the buffer.
+    pub fn into_format(mut self, format: PixelFormat) -> Self {
+        if self.format != format {
+            let from = self.format;
+            for px in self.pixels.chunks_exact_mut(PixelFormat::BYTES_PER_PIXEL) {
+                let rgba = from.decode(px);
+                format.encode(rgba, px);
+            }
+            self.format = format;
+        }
+        self
+    }
+}
+
+/// Multiply color by alpha, rounding to nearest.
+#[inline]
+pub fn premultiply([r, g, b, a]: [u8; 4]) -> [u8; 4] {
+    let mul = |c: u8| ((c as u32 * a as u32 + 127) / 255) as u8;
+    [mul(r), mul(g), mul(b), a]
+}
+
+/// Divide color by alpha; fully transparent pixels come back as transparent black.
+#[inline]
+pub fn unpremultiply([r, g, b, a]: [u8; 4]) -> [u8; 4] {
+    if a == 0 {
+        return [0; 4];
+    }
+    let div = |c: u8| ((c.min(a) as u32 * 255 + a as u32 / 2) / a as u32) as u8;
+    [div(r), div(g), div(b), a]
+}
+
+/// Store a premultiplied RGBA color in `0.0..=1.0` as surface bytes (B, G, R, A).
+///
+/// Color is clamped to alpha so the compositor never sees an invalid
+/// premultiplied pixel, which it would blend as additive light.
+#[inline(always)]
+pub fn write_bgra(px: &mut [u8], [r, g, b, a]: [f32; 4]) {
+    let a = a.clamp(0.0, 1.0);
+    let to_byte = |c: f32| (c.clamp(0.0, a) * 255.0) as u8;
+    px.copy_from_slice(&[to_byte(b), to_byte(g), to_byte(r), to_byte(a)]);
+}
+
+/// Composite premultiplied `color`, scaled by `alpha`, over the surface pixel `px`.
+#[inline]
+pub fn blend_bgra(px: &mut [u8], [r, g, b, a]: [f32; 4], alpha: f32) {
+    let keep = 1.0 - a * alpha;
+    let dst = |i: usize| px[i] as f32 / 255.0 * keep;
+    write_bgra(px, [r * alpha + dst(2), g * alpha + dst(1), b * alpha + dst(0), a * alpha + dst(3)]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn premultiplied_pixels_survive_a_round_trip() {
+        for a in 0..=255u8 {
+            for c in 0..=a {
+                let premultiplied = [c, a - c, c / 2, a];
+                assert_eq!(premultiply(unpremultiply(premultiplied)), premultiplied);
+            }
+        }
+    }
+
+    #[test]
+    fn straight_opaque_pixels_survive_a_round_trip() {
+        for c in 0..=255u8 {
+            let straight = [c, 255 - c, c / 3, 255];
+            assert_eq!(premultiply(straight), straight);
+            assert_eq!(unpremultiply(premultiply(straight)), straight);
+        }
+        assert_eq!(unpremultiply([0, 0, 0, 0]), [0; 4]);
+    }
+
+    #[test]
+    fn format_conversions_round_trip() {
+        let pixels = vec![200, 100, 50, 128, 0, 0, 0, 0, 255, 255, 255, 255];
+        let image = Image::new(pixels.clone(), 3, 1, PixelFormat::Rgba8Premultiplied);
+        let image = image.into_format(PixelFormat::Bgra8Premultiplied);
+        assert_eq!(&image.pixels[..4], [50, 100, 200, 128]);
+        let image = image.into_format(PixelFormat::Rgba8Premultiplied);
+        assert_eq!(image.pixels, pixels);
+    }
+}
@@ -0,0 +1,132 @@
+//! Burn: a ragged front of fBm noise eats the window, glowing like embers
+//! where it bites and scorching what it's about to reach. The melt variant
+//! lets the columns sag and drip downward while the front rises from the
+//! bottom.
+//!
+//! The noise comes from `crate::noise`, so the shader draws the same front.
+
+use glam::{Vec2, Vec3, Vec4};
+use rayon::prelude::*;
+
+use super::{CpuRenderer, Target, Texture};
+use crate::damage::Rect;
+use crate::easing::smoothstep;
+use crate::noise;
+use crate::pixel::write_bgra;
+
+/// fBm octaves for the front.
+const OCTAVES: u32 = 4;
+
+/// Stretches the noise, which mostly sits near the middle of its range, so
+/// the front sweeps at a steadier pace.
+const CONTRAST: f32 = 1.8;
+
+/// Scorching reaches this many edge widths ahead of the embers.
+const SCORCH: f32 = 3.0;
+
+/// Width of the antialiased cut, in noise units.
+const SOFTNESS: f32 = 0.01;
+
+/// Farthest a melting column sags, in window heights.
+const MELT_DISTANCE: f32 = 0.6;
+
+/// Tunable settings.
+#[derive(Debug, Clone, Copy)]
+pub struct BurnParams {
+    /// Straight-alpha RGB of the embers
+    pub ember: Vec3,
+    /// Noise features across the window
+    pub scale: f32,
+    /// Width of the glowing border, in noise units
+    pub edge: f32,
+    /// Drip downward instead of burning in place
+    pub melt: bool,
+    pub seed: u32,
+}
+
+/// Per-close burn state.
+pub struct BurnRenderer {
+    params: BurnParams,
+}
+
+impl BurnRenderer {
+    pub fn new(params: BurnParams) -> Self {
+        Self { params }
+    }
+
+    /// How far the columns have sagged at `progress`, in window heights.
+    fn sag(progress: f32) -> f32 {
+        MELT_DISTANCE * progress * progress
+    }
+
+    /// Premultiplied color at window `uv` of a `size` window.
+    fn shade(&self, texture: &Texture, uv: Vec2, size: Vec2, lod: f32, progress: f32) -> Vec4 {
+        let p = &self.params;
+        let cells = Vec2::new(p.scale, p.scale * size.y / size.x);
+        let edge = p.edge.max(0.001);
+
+        // Melting columns each sag by their own amount
+        let mut source = uv;
+        if p.melt {
+            let column = noise::value(Vec2::new(uv.x * cells.x * 2.0, 0.5), p.seed ^ 0x5eed);
+            source.y -= Self::sag(progress) * (0.7 + 0.3 * column);
+            if !(0.0..=1.0).contains(&source.y) {
+                return Vec4::ZERO;
+            }
+        }
+
+        let mut level = (noise::fbm(source * cells, OCTAVES, p.seed) * CONTRAST * 0.5 + 0.5)
+            .clamp(0.0, 1.0);
+        if p.melt {
+            // The bottom goes first
+            level = 0.5 * level + 0.5 * (1.0 - source.y);
+        }
+        let front = -edge + progress * (1.0 + 2.0 * edge);
+        let d = level - front;
+        if d <= 0.0 {
+            return Vec4::ZERO;
+        }
+
+        let color = Vec4::from(texture.sample(source.x, source.y, lod));
+        let ember = 1.0 - smoothstep(d / edge);
+        let scorch = 1.0 - smoothstep(d / (edge * SCORCH));
+        // Hottest, nearly white, right at the front
+        let glow = p.ember.lerp(Vec3::ONE, ember * ember * 0.6) * color.w;
+        let rgb = (color.truncate() * (1.0 - 0.7 * scorch)).lerp(glow, ember);
+        rgb.extend(color.w) * smoothstep(d / SOFTNESS)
+    }
+}
+
+impl CpuRenderer for BurnRenderer {
+    fn bounds(&self, window: &Rect, progress: f32) -> Rect {
+        if !self.params.melt {
+            return *window;
+        }
+        let sag = (Self::sag(progress) * window.height as f32).ceil() as i32;
+        Rect::new(window.x, window.y, window.width, window.height + sag)
+    }
+
+    fn render(&self, target: &mut Target, texture: &Texture, progress: f32) {
+        let bounds = target.bounds.intersect(&self.bounds(&target.window, progress));
+        if bounds.is_empty() {
+            return;
+        }
+        let window = target.window;
+        let size = Vec2::new(window.width.max(1) as f32, window.height.max(1) as f32);
+        let lod = (texture.width() as f32 / size.x).log2().max(0.0);
+
+        let row_bytes = target.width * 4;
+        target.canvas[bounds.y as usize * row_bytes..bounds.bottom() as usize * row_bytes]
+            .par_chunks_mut(row_bytes)
+            .enumerate()
+            .for_each(|(row_index, row)| {
+                let y = bounds.y + row_index as i32;
+                for x in bounds.x..bounds.right() {
+                    let p = Vec2::new((x - window.x) as f32 + 0.5, (y - window.y) as f32 + 0.5);
+                    let color = self.shade(texture, p / size, size, lod, progress);
+                    let px = &mut row[x as usize * 4..x as usize * 4 + 4];
+                    write_bgra(px, color.into());
+                }
+            });
+    }
+}
@@ -0,0 +1,241 @@
+//! CRT power-off: the picture collapses to a bright horizontal line, the
+//! line to a glowing dot, and the dot fades out.
+//!
+//! Each output pixel maps back through the current squeeze into the window.
+//! Red and blue are sampled either side of green for the color fringing,
+//! every other surface row is dimmed like a scanline, and a halo around the
+//! squeezed picture stands in for phosphor bloom.
+
+use glam::{Vec2, Vec3, Vec4};
+use rayon::prelude::*;
+
+use super::{CpuRenderer, Target, Texture};
+use crate::damage::Rect;
+use crate::easing::{lerp, Easing};
+use crate::pixel::write_bgra;
+
+/// Height of the line, in pixels.
+const LINE_PX: f32 = 2.0;
+
+/// Width of the dot, in pixels.
+const DOT_PX: f32 = 4.0;
+
+/// How far the bloom reaches from the picture, in pixels.
+const BLOOM_RADIUS: f32 = 12.0;
+
+/// Peak opacity of the bloom right at the picture's edge.
+const BLOOM_STRENGTH: f32 = 0.6;
+
+/// Tunable settings.
+#[derive(Debug, Clone, Copy)]
+pub struct CrtParams {
+    /// How far the picture brightens as it becomes a line; 0 keeps its colors
+    pub line_brightness: f32,
+    /// Progress at which the picture has become a line
+    pub line_at: f32,
+    /// Progress at which the line has become a dot
+    pub dot_at: f32,
+    /// Darkening of every other row, 0..1
+    pub scanlines: f32,
+    /// Red/blue offset at its widest, in window widths
+    pub aberration: f32,
+}
+
+/// Where the picture is at one moment.
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    /// Picture size as a fraction of the window's
+    scale: Vec2,
+    /// Toward white, 0..1
+    whiteness: f32,
+    /// Multiplier on the picture's colors
+    gain: f32,
+    /// Opacity of the picture and its bloom
+    opacity: f32,
+    /// Bloom opacity before `opacity`
+    bloom: f32,
+    /// Red/blue offset in window widths
+    aberration: f32,
+    /// Scanline darkening
+    scanlines: f32,
+}
+
+impl CrtParams {
+    /// The picture at `progress` in a `size` window.
+    fn frame(&self, size: Vec2, progress: f32) -> Frame {
+        let line_at = self.line_at.clamp(0.01, 0.98);
+        let dot_at = self.dot_at.clamp(line_at + 0.01, 0.99);
+        // Quadratic, so each collapse snaps shut at the end
+        let collapse = Easing::In(2).apply(progress / line_at);
+        let shrink = Easing::In(2).apply((progress - line_at) / (dot_at - line_at));
+        let fade = ((progress - dot_at) / (1.0 - dot_at)).clamp(0.0, 1.0);
+
+        let thin = (LINE_PX / size.y).min(1.0);
+        let narrow = (DOT_PX / size.x).min(1.0);
+        Frame {
+            scale: Vec2::new(lerp(1.0, narrow, shrink), lerp(1.0, thin, collapse)),
+            whiteness: collapse,
+            gain: 1.0 + self.line_brightness * collapse,
+            opacity: 1.0 - fade * fade,
+            bloom: collapse,
+            // Both strongest while the picture is still recognisable, and
+            // gone at the start so the close begins on the untouched window
+            aberration: self.aberration * (collapse * (1.0 - shrink)).sqrt(),
+            scanlines: self.scanlines * (collapse * (1.0 - shrink)).sqrt(),
+        }
+    }
+}
+
+/// Per-close CRT state.
+pub struct CrtRenderer {
+    params: CrtParams,
+}
+
+impl CrtRenderer {
+    pub fn new(params: CrtParams) -> Self {
+        Self { params }
+    }
+}
+
+impl CpuRenderer for CrtRenderer {
+    fn bounds(&self, window: &Rect, _progress: f32) -> Rect {
+        *window
+    }
+
+    fn render(&self, target: &mut Target, texture: &Texture, progress: f32) {
+        let bounds = target.bounds.intersect(&target.window);
+        if bounds.is_empty() {
+            return;
+        }
+        let window = target.window;
+        let size = Vec2::new(window.width.max(1) as f32, window.height.max(1) as f32);
+        let frame = self.params.frame(size, progress);
+
+        // Squeezing averages many texels into each pixel along the short side
+        let texel_scale = texture.width() as f32 / size.x;
+        let lod = (texel_scale / frame.scale.min_element()).log2().max(0.0);
+        let half = frame.scale * size * 0.5;
+        let fringe = Vec2::new(frame.aberration, 0.0);
+
+        let row_bytes = target.width * 4;
+        target.canvas[bounds.y as usize * row_bytes..bounds.bottom() as usize * row_bytes]
+            .par_chunks_mut(row_bytes)
+            .enumerate()
+            .for_each(|(row_index, row)| {
+                let y = bounds.y + row_index as i32;
+                let scanline = if y % 2 == 1 { 1.0 - frame.scanlines } else { 1.0 };
+                for x in bounds.x..bounds.right() {
+                    let p = Vec2::new((x - window.x) as f32 + 0.5, (y - window.y) as f32 + 0.5);
+                    let offset = p - size * 0.5;
+
+                    let mut color = Vec4::ZERO;
+                    if offset.abs().cmple(half).all() {
+                        let uv = offset / half * 0.5 + 0.5;
+                        color = sample_fringed(texture, uv, fringe, lod);
+                        // Premultiplied white is the alpha itself
+                        let rgb = (color.truncate() * frame.gain)
+                            .lerp(Vec3::splat(color.w), frame.whiteness);
+                        color = (rgb * scanline).extend(color.w);
+                    }
+
+                    // Bloom: white glow falling off with distance from the picture
+                    let distance = (offset.abs() - half).max(Vec2::ZERO).length();
+                    let halo = BLOOM_STRENGTH * frame.bloom * (-distance / BLOOM_RADIUS).exp();
+                    color += Vec4::splat(halo) * (1.0 - color.w);
+
+                    let px = &mut row[x as usize * 4..x as usize * 4 + 4];
+                    write_bgra(px, (color * frame.opacity).into());
+                }
+            });
+    }
+}
+
+/// Premultiplied color at `uv` with red and blue pulled `fringe` either way.
+fn sample_fringed(texture: &Texture, uv: Vec2, fringe: Vec2, lod: f32) -> Vec4 {
+    let center = Vec4::from(texture.sample(uv.x, uv.y, lod));
+    if fringe.x == 0.0 {
+        return center;
+    }
+    let red = (uv + fringe).clamp(Vec2::ZERO, Vec2::ONE);
+    let blue = (uv - fringe).clamp(Vec2::ZERO, Vec2::ONE);
+    let red = texture.sample(red.x, red.y, lod);
+    let blue = texture.sample(blue.x, blue.y, lod);
+    let alpha = center.w.max(red[3]).max(blue[3]);
+    Vec4::new(red[0], center.y, blue[2], alpha)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixel::{Image, PixelFormat};
+
+    const WIDTH: usize = 64;
+    const HEIGHT: usize = 48;
+
+    const PARAMS: CrtParams = CrtParams {
+        line_brightness: 0.5,
+        line_at: 0.5,
+        dot_at: 0.8,
+        scanlines: 0.3,
+        aberration: 0.01,
+    };
+
+    /// Opaque window with a different color in every pixel.
+    fn image() -> Image {
+        let pixels = (0..WIDTH * HEIGHT)
+            .flat_map(|i| [(i % 251) as u8, (i * 7 % 253) as u8, (i * 13 % 255) as u8, 255])
+            .collect();
+        Image::new(pixels, WIDTH, HEIGHT, PixelFormat::Rgba8)
+    }
+
+    fn render(params: CrtParams, progress: f32) -> Vec<u8> {
+        let mut canvas = vec![0u8; WIDTH * HEIGHT * 4];
+        let window = Rect::new(0, 0, WIDTH as i32, HEIGHT as i32);
+        let mut target = Target {
+            canvas: &mut canvas,
+            width: WIDTH,
+            window,
+            bounds: window,
+        };
+        CrtRenderer::new(params).render(&mut target, &Texture::new(image()), progress);
+        canvas
+    }
+
+    #[test]
+    fn starts_as_the_whole_picture() {
+        let expected = image().into_format(PixelFormat::Bgra8Premultiplied).pixels;
+        let canvas = render(PARAMS, 0.0);
+        let off = canvas.iter().zip(&expected).filter(|(a, b)| a.abs_diff(**b) > 1).count();
+        assert_eq!(off, 0, "{off} channels differ from the window");
+    }
+
+    #[test]
+    fn ends_with_nothing() {
+        assert!(render(PARAMS, 1.0).iter().all(|&c| c == 0));
+    }
+
+    #[test]
+    fn dot_before_line_is_clamped_after_it() {
+        let size = Vec2::new(WIDTH as f32, HEIGHT as f32);
+        for (line_at, dot_at) in [(0.6, 0.3), (0.5, 0.5), (0.0, 0.0), (1.0, 1.0)] {
+            let params = CrtParams {
+                line_at,
+                dot_at,
+                ..PARAMS
+            };
+            let mut last = params.frame(size, 0.0);
+            assert_eq!(last.scale, Vec2::ONE, "{line_at}, {dot_at}");
+            for i in 1..=100 {
+                let frame = params.frame(size, i as f32 / 100.0);
+                let values = [frame.scale.x, frame.scale.y, frame.opacity, frame.aberration];
+                assert!(values.iter().all(|v| v.is_finite()), "{line_at}, {dot_at}: {frame:?}");
+                // The picture only ever shrinks, line first
+                assert!(frame.scale.cmple(last.scale + 1e-6).all(), "{line_at}, {dot_at}");
+                assert!(frame.scale.x == 1.0 || frame.scale.y < 1.0, "{line_at}, {dot_at}");
+                last = frame;
+            }
+            assert_eq!(last.opacity, 0.0, "{line_at}, {dot_at}");
+            assert!(render(params, 1.0).iter().all(|&c| c == 0), "{line_at}, {dot_at}");
+        }
+    }
+}
@@ -0,0 +1,98 @@
+//! Dust: a front sweeps across the window and crumbles it into specks that
+//! drift off on a turbulent breeze.
+
+use glam::Vec2;
+
+use super::particles::{Flow, Particle, ParticleSystem};
+use super::{CpuRenderer, Target, Texture};
+use crate::damage::Rect;
+use crate::noise;
+use crate::rng::Rng;
+
+/// Share of the animation the front takes to cross the window.
+const SWEEP: f32 = 0.55;
+
+/// Random spread of release times around the front, as a share of the sweep.
+const RAGGEDNESS: f32 = 0.06;
+
+/// Tunables, in units of the window diagonal so every window size looks alike.
+#[derive(Debug, Clone, Copy)]
+pub struct DustParams {
+    /// Cluster size in pixels
+    pub grain: u32,
+    /// Direction the front sweeps in, radians clockwise from rightward
+    pub angle: f32,
+    /// Drift speed along the sweep, diagonals per second
+    pub drift: f32,
+    /// Swirl speed, diagonals per second
+    pub turbulence: f32,
+    /// Animation length in seconds
+    pub duration: f32,
+}
+
+/// Per-close dust state: every cluster and when it lets go.
+pub struct DustRenderer {
+    system: ParticleSystem,
+    duration: f32,
+}
+
+impl DustRenderer {
+    /// Crumble a `width` x `height` window; `seed` picks the turbulence and jitter.
+    pub fn new(width: u32, height: u32, params: DustParams, seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let size = Vec2::new(width.max(1) as f32, height.max(1) as f32);
+        let diagonal = size.length();
+        let direction = Vec2::from_angle(params.angle);
+
+        // Where the front starts and ends, measured along the sweep
+        let corners = [Vec2::ZERO, Vec2::new(size.x, 0.0), Vec2::new(0.0, size.y), size];
+        let (start, end) = corners
+            .iter()
+            .map(|corner| corner.dot(direction))
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), d| (lo.min(d), hi.max(d)));
+
+        let flow = Flow {
+            lift: Vec2::new(0.0, -0.3 * diagonal),
+            scale: diagonal / 10.0,
+            strength: params.turbulence * diagonal,
+            // The first draw, like the uniforms' seed, so the shader's eddies match
+            seed: noise::seed(rng.next_f32()),
+        };
+        let sweep = SWEEP * params.duration;
+        let center = params.grain as f32 * 0.5;
+
+        let system = ParticleSystem::emit(width, height, params.grain, flow, |home| {
+            let front = ((home + center).dot(direction) - start) / (end - start).max(1.0);
+            let jitter = rng.range(-RAGGEDNESS, RAGGEDNESS);
+            let release = sweep * (front + jitter).clamp(0.0, 1.0);
+            let speed = params.drift * diagonal * rng.range(0.5, 1.5);
+            let scatter = Vec2::new(rng.range(-0.05, 0.05), rng.range(-0.1, 0.0)) * diagonal;
+            Particle {
+                home,
+                release,
+                // Everything is gone by the end
+                life: (params.duration - release) * rng.range(0.6, 1.0),
+                velocity: direction * speed + scatter,
+            }
+        });
+
+        Self {
+            system,
+            duration: params.duration,
+        }
+    }
+
+    pub fn particle_count(&self) -> usize {
+        self.system.particles().len()
+    }
+}
+
+impl CpuRenderer for DustRenderer {
+    fn bounds(&self, window: &Rect, progress: f32) -> Rect {
+        self.system.bounds(window, progress * self.duration)
+    }
+
+    fn render(&self, target: &mut Target, texture: &Texture, progress: f32) {
+        self.system.render(target, texture, progress * self.duration);
+    }
+}
@@ -0,0 +1,50 @@
+//! Fade: the window stays put and turns transparent.
+
+use glam::Vec2;
+use rayon::prelude::*;
+
+use super::{CpuRenderer, Target, Texture};
+use crate::damage::Rect;
+use crate::pixel::write_bgra;
+
+/// Per-close fade state; there's nothing to vary.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FadeRenderer;
+
+impl FadeRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl CpuRenderer for FadeRenderer {
+    fn bounds(&self, window: &Rect, _progress: f32) -> Rect {
+        *window
+    }
+
+    fn render(&self, target: &mut Target, texture: &Texture, progress: f32) {
+        let window = target.window;
+        let bounds = target.bounds.intersect(&window);
+        if bounds.is_empty() {
+            return;
+        }
+        let size = Vec2::new(window.width as f32, window.height as f32);
+        let lod = (texture.width() as f32 / size.x).log2().max(0.0);
+        // Premultiplied: fading scales color along with alpha
+        let opacity = (1.0 - progress).clamp(0.0, 1.0);
+
+        let row_bytes = target.width * 4;
+        target.canvas[bounds.y as usize * row_bytes..bounds.bottom() as usize * row_bytes]
+            .par_chunks_mut(row_bytes)
+            .enumerate()
+            .for_each(|(row_index, row)| {
+                let y = bounds.y + row_index as i32;
+                let v = ((y - window.y) as f32 + 0.5) / size.y;
+                for x in bounds.x..bounds.right() {
+                    let u = ((x - window.x) as f32 + 0.5) / size.x;
+                    let color = texture.sample(u, v, lod).map(|c| c * opacity);
+                    write_bgra(&mut row[x as usize * 4..x as usize * 4 + 4], color);
+                }
+            });
+    }
+}
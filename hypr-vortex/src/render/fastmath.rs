@@ -0,0 +1,114 @@
+//! Fast polynomial approximations for 8-lane SIMD math.
+//!
+//! atan2 is within 2e-6 rad of `f32::atan2`, and sin/cos within 1.2e-7 (about
+//! one ULP at 1.0) after range reduction, which is far below a texel at any
+//! window size we render.
+
+use std::f32::consts::{FRAC_2_PI, FRAC_PI_2, PI};
+
+use wide::{f32x8, CmpEq, CmpGt, CmpLt};
+
+/// Minimax coefficients for atan(x) on [0, 1], odd powers x^1..x^11.
+const ATAN_COEFFS: [f32; 6] = [
+    0.999_977_26,
+    -0.332_623_47,
+    0.193_543_46,
+    -0.116_432_87,
+    0.052_653_32,
+    -0.011_721_2,
+];
+
+/// pi/2 split into three parts so `x - j * pi/2` stays exact for large `j`.
+const PIO2_A: f32 = 1.570_312_5;
+const PIO2_B: f32 = 4.837_513e-4;
+const PIO2_C: f32 = 7.549_79e-8;
+
+/// Four-quadrant arctangent of `y / x`, matching `f32::atan2` conventions.
+#[inline(always)]
+pub fn atan2(y: f32x8, x: f32x8) -> f32x8 {
+    let zero = f32x8::ZERO;
+    let ax = x.abs();
+    let ay = y.abs();
+
+    // Reduce to atan(a) with a in [0, 1]
+    let num = ax.min(ay);
+    let den = ax.max(ay).max(f32x8::splat(f32::MIN_POSITIVE));
+    let a = num / den;
+    let s = a * a;
+
+    let mut poly = f32x8::splat(ATAN_COEFFS[5]);
+    for &c in ATAN_COEFFS[..5].iter().rev() {
+        poly = poly.mul_add(s, f32x8::splat(c));
+    }
+    let mut r = poly * a;
+
+    // Undo the reduction: octant, then half-plane, then sign of y
+    r = ay.cmp_gt(ax).blend(f32x8::splat(FRAC_PI_2) - r, r);
+    r = x.cmp_lt(zero).blend(f32x8::splat(PI) - r, r);
+    y.cmp_lt(zero).blend(-r, r)
+}
+
+/// Sine and cosine of `x`, with Cody-Waite range reduction to [-pi/4, pi/4].
+#[inline(always)]
+pub fn sin_cos(x: f32x8) -> (f32x8, f32x8) {
+    let j = (x * f32x8::splat(FRAC_2_PI)).round();
+    let r = x - j * f32x8::splat(PIO2_A) - j * f32x8::splat(PIO2_B) - j * f32x8::splat(PIO2_C);
+    let r2 = r * r;
+
+    // Taylor series, truncated where the error drops below f32 precision
+    let sin_r = r + r * r2
+        * (f32x8::splat(-1.0 / 6.0)
+            + r2 * (f32x8::splat(1.0 / 120.0)
+                + r2 * (f32x8::splat(-1.0 / 5040.0) + r2 * f32x8::splat(1.0 / 362_880.0))));
+    let cos_r = f32x8::ONE - r2 * f32x8::splat(0.5)
+        + r2 * r2
+            * (f32x8::splat(1.0 / 24.0)
+                + r2 * (f32x8::splat(-1.0 / 720.0) + r2 * f32x8::splat(1.0 / 40_320.0)));
+
+    // Quadrant q = j mod 4 picks which polynomial and sign each result uses
+    let q = j - f32x8::splat(4.0) * (j * f32x8::splat(0.25)).floor();
+    let q1 = q.cmp_eq(f32x8::ONE);
+    let q2 = q.cmp_eq(f32x8::splat(2.0));
+    let q3 = q.cmp_eq(f32x8::splat(3.0));
+
+    let swap = q1 | q3;
+    let sin = swap.blend(cos_r, sin_r);
+    let cos = swap.blend(sin_r, cos_r);
+    let sin = (q2 | q3).blend(-sin, sin);
+    let cos = (q1 | q2).blend(-cos, cos);
+    (sin, cos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn atan2_matches_std_all_the_way_round() {
+        let mut worst = 0.0f32;
+        for step in 0..100_000 {
+            let theta = step as f32 / 100_000.0 * 2.0 * PI - PI;
+            for radius in [1e-3, 1.0, 1e3] {
+                let (y, x) = (radius * theta.sin(), radius * theta.cos());
+                let fast = atan2(f32x8::splat(y), f32x8::splat(x)).to_array()[0];
+                let error = (fast - y.atan2(x)).abs();
+                // Straddling the branch cut, either side of +-pi is right
+                worst = worst.max(error.min((error - 2.0 * PI).abs()));
+            }
+        }
+        assert!(worst < 2e-6, "atan2 off by {worst:e} rad");
+    }
+
+    #[test]
+    fn sin_cos_matches_f64_after_range_reduction() {
+        let mut worst = 0.0f64;
+        for step in 0..100_000 {
+            let x = step as f32 / 100_000.0 * 200.0 - 100.0;
+            let (sin, cos) = sin_cos(f32x8::splat(x));
+            let (sin, cos) = (sin.to_array()[0] as f64, cos.to_array()[0] as f64);
+            let x = x as f64;
+            worst = worst.max((sin - x.sin()).abs()).max((cos - x.cos()).abs());
+        }
+        assert!(worst < 1.2e-7, "sin/cos off by {worst:e}");
+    }
+}
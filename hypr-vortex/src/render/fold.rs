@@ -0,0 +1,166 @@
+//! Fold: the window folds in half like a sheet of paper, right over left,
+//! then in half again, bottom over top, and the folded quarter flies off
+//! into the distance.
+//!
+//! Each fold swings a half of the sheet through 180 degrees toward the
+//! viewer around its crease. Every quarter stays flat, so a 2x2 mesh with
+//! the creases on its grid lines is enough; the mesh renderer's perspective
+//! and lighting do the rest.
+
+use std::f32::consts::PI;
+
+use glam::{Vec2, Vec3};
+
+use super::mesh::Deform;
+use crate::easing::{smoothstep, Easing};
+
+/// The first fold is flat at this progress.
+const FIRST_END: f32 = 0.35;
+
+/// The second fold is flat at this progress.
+const SECOND_END: f32 = 0.7;
+
+/// Paper thickness in pixels, which keeps folded layers apart in depth.
+const THICKNESS: f32 = 1.0;
+
+/// How far the folded sheet rises while flying off, in window heights.
+const FLY_RISE: f32 = 0.6;
+
+/// How far it recedes, in window sizes.
+const FLY_BACK: f32 = 3.0;
+
+/// The last bit of the flight also fades out.
+const FADE_START: f32 = 0.85;
+
+/// Paper-fold motion of one `size` window.
+#[derive(Debug, Clone, Copy)]
+pub struct FoldDeform {
+    size: Vec2,
+}
+
+impl FoldDeform {
+    pub fn new(size: Vec2) -> Self {
+        Self { size }
+    }
+}
+
+impl Deform for FoldDeform {
+    fn vertex(&self, uv: Vec2, progress: f32) -> Vec3 {
+        let crease = self.size * 0.5;
+        let mut p = (uv * self.size).extend(0.0);
+
+        // Right half over the left, lifted one sheet clear of it
+        if uv.x > 0.5 {
+            let angle = PI * smoothstep(progress / FIRST_END);
+            let (sin, cos) = angle.sin_cos();
+            let dx = p.x - crease.x;
+            p = Vec3::new(crease.x + dx * cos, p.y, dx * sin + THICKNESS * (1.0 - cos) * 0.5);
+        }
+
+        // Bottom half, already two sheets thick, over the top two
+        if uv.y > 0.5 {
+            let angle = PI * smoothstep((progress - FIRST_END) / (SECOND_END - FIRST_END));
+            let (sin, cos) = angle.sin_cos();
+            let dy = p.y - crease.y;
+            p = Vec3::new(
+                p.x,
+                crease.y + dy * cos - p.z * sin,
+                dy * sin + p.z * cos + 3.0 * THICKNESS * (1.0 - cos) * 0.5,
+            );
+        }
+
+        // Quadratic, so the flight picks up speed
+        let fly = Easing::In(2).apply((progress - SECOND_END) / (1.0 - SECOND_END));
+        let away = Vec3::new(0.0, -FLY_RISE * self.size.y, -FLY_BACK * self.size.max_element());
+        p + away * fly
+    }
+
+    fn opacity(&self, progress: f32) -> f32 {
+        1.0 - smoothstep((progress - FADE_START) / (1.0 - FADE_START))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: Vec2 = Vec2::new(640.0, 360.0);
+
+    /// Grid corners and the middles of the quarters, as uvs.
+    fn uvs() -> Vec<Vec2> {
+        let steps = [0.0, 0.25, 0.5, 0.75, 1.0];
+        steps
+            .iter()
+            .flat_map(|&v| steps.iter().map(move |&u| Vec2::new(u, v)))
+            .collect()
+    }
+
+    /// Where `uv` lands once folded over the vertical crease (`fold_x`) and
+    /// the horizontal one (`fold_y`).
+    fn mirrored(uv: Vec2, fold_x: bool, fold_y: bool) -> Vec2 {
+        let mut p = uv * SIZE;
+        if fold_x && uv.x > 0.5 {
+            p.x = SIZE.x - p.x;
+        }
+        if fold_y && uv.y > 0.5 {
+            p.y = SIZE.y - p.y;
+        }
+        p
+    }
+
+    #[test]
+    fn starts_flat() {
+        let deform = FoldDeform::new(SIZE);
+        for uv in uvs() {
+            assert_eq!(deform.vertex(uv, 0.0), (uv * SIZE).extend(0.0), "{uv}");
+        }
+        assert_eq!(deform.opacity(0.0), 1.0);
+    }
+
+    #[test]
+    fn first_fold_lays_right_half_on_left() {
+        let deform = FoldDeform::new(SIZE);
+        for uv in uvs() {
+            let p = deform.vertex(uv, FIRST_END);
+            let expected = mirrored(uv, true, false);
+            assert!(p.truncate().distance(expected) < 1e-3, "{uv}: {p} vs {expected}");
+            // The folded half lies one sheet above the other
+            let z = if uv.x > 0.5 { THICKNESS } else { 0.0 };
+            assert!((p.z - z).abs() < 1e-3, "{uv}: z {}", p.z);
+        }
+    }
+
+    #[test]
+    fn second_fold_stacks_all_quarters() {
+        let deform = FoldDeform::new(SIZE);
+        for uv in uvs() {
+            let p = deform.vertex(uv, SECOND_END);
+            let expected = mirrored(uv, true, true);
+            assert!(p.truncate().distance(expected) < 1e-3, "{uv}: {p} vs {expected}");
+            assert!(p.z > -1e-3, "{uv}: z {}", p.z);
+        }
+
+        // Four distinct layers, bottom to top: top left, top right, then the
+        // bottom half flipped over them
+        let layer = |uv: Vec2| deform.vertex(uv, SECOND_END).z;
+        let stack = [
+            layer(Vec2::new(0.25, 0.25)),
+            layer(Vec2::new(0.75, 0.25)),
+            layer(Vec2::new(0.75, 0.75)),
+            layer(Vec2::new(0.25, 0.75)),
+        ];
+        assert!(stack.windows(2).all(|pair| pair[0] < pair[1]), "{stack:?}");
+    }
+
+    #[test]
+    fn ends_gone_in_the_distance() {
+        let deform = FoldDeform::new(SIZE);
+        assert_eq!(deform.opacity(1.0), 0.0);
+        for uv in uvs() {
+            let folded = deform.vertex(uv, SECOND_END);
+            let p = deform.vertex(uv, 1.0);
+            let away = Vec3::new(0.0, -FLY_RISE * SIZE.y, -FLY_BACK * SIZE.x);
+            assert!(p.distance(folded + away) < 1e-3, "{uv}: {p}");
+        }
+    }
+}
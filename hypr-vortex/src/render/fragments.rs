@@ -0,0 +1,380 @@
+//! Rigid fragments: pieces cut from the window that fly apart as solid bodies.
+//!
+//! A `Fragment` is a convex polygon in window pixels plus its launch state.
+//! Its pose at any time is closed-form ballistic motion, so a frame never
+//! depends on the one before it and dropped frames can't make the debris
+//! drift. `FragmentSim` draws every fragment by mapping each covered surface
+//! pixel back into the window and sampling the screenshot there.
+
+use glam::Vec2;
+use rayon::prelude::*;
+
+use super::{Target, Texture};
+use crate::damage::Rect;
+use crate::pixel::blend_bgra;
+
+/// Polygons smaller than this (in square pixels) are dropped.
+const MIN_AREA: f32 = 1.0;
+
+/// One rigid piece of the window.
+#[derive(Debug, Clone)]
+pub struct Fragment {
+    /// Convex outline in window pixels
+    polygon: Vec<Vec2>,
+    centroid: Vec2,
+    /// Edge lines as (unit normal, offset): inside is `normal.dot(p) <= offset`
+    edges: Vec<(Vec2, f32)>,
+    /// Launch velocity in pixels per second
+    pub velocity: Vec2,
+    /// Radians per second, positive is clockwise on screen
+    pub angular_velocity: f32,
+    /// Seconds before the fragment starts moving
+    pub delay: f32,
+}
+
+/// Where a fragment is at some moment, relative to where it started.
+#[derive(Debug, Clone, Copy)]
+pub struct Pose {
+    /// Displacement of the centroid in pixels
+    pub offset: Vec2,
+    /// Rotation about the centroid in radians
+    pub angle: f32,
+}
+
+impl Fragment {
+    /// A resting fragment with this outline, or `None` if it's degenerate.
+    pub fn new(polygon: Vec<Vec2>) -> Option<Self> {
+        let (area, centroid) = area_centroid(&polygon);
+        if area.abs() < MIN_AREA {
+            return None;
+        }
+
+        let edges = polygon
+            .iter()
+            .zip(polygon.iter().cycle().skip(1))
+            .filter_map(|(&a, &b)| {
+                let normal = (b - a).perp().try_normalize()?;
+                // Orient outward, whichever way the polygon winds
+                let normal = if normal.dot(centroid - a) > 0.0 { -normal } else { normal };
+                Some((normal, normal.dot(a)))
+            })
+            .collect();
+
+        Some(Self {
+            polygon,
+            centroid,
+            edges,
+            velocity: Vec2::ZERO,
+            angular_velocity: 0.0,
+            delay: 0.0,
+        })
+    }
+
+    pub fn with_motion(mut self, velocity: Vec2, angular_velocity: f32, delay: f32) -> Self {
+        self.velocity = velocity;
+        self.angular_velocity = angular_velocity;
+        self.delay = delay;
+        self
+    }
+
+    pub fn polygon(&self) -> &[Vec2] {
+        &self.polygon
+    }
+
+    pub fn centroid(&self) -> Vec2 {
+        self.centroid
+    }
+
+    /// Pose `t` seconds after the start under constant `gravity` (pixels/s²).
+    pub fn pose(&self, t: f32, gravity: Vec2) -> Pose {
+        let t = (t - self.delay).max(0.0);
+        Pose {
+            offset: self.velocity * t + 0.5 * gravity * t * t,
+            angle: self.angular_velocity * t,
+        }
+    }
+
+    /// Signed distance from `p` (window pixels) to the outline, positive inside.
+    #[inline]
+    fn inside_distance(&self, p: Vec2) -> f32 {
+        self.edges
+            .iter()
+            .map(|(normal, offset)| offset - normal.dot(p))
+            .fold(f32::INFINITY, f32::min)
+    }
+}
+
+/// A fragment placed on the surface for one frame.
+struct Placed<'a> {
+    fragment: &'a Fragment,
+    /// Surface position of the centroid
+    position: Vec2,
+    /// Inverse rotation, as (cos, sin) of `-angle`
+    inverse: Vec2,
+    bounds: Rect,
+    /// Still in place, so drawn without antialiasing: resting fragments tile
+    /// the window exactly, and blending two soft edges would show the seam
+    resting: bool,
+}
+
+impl Placed<'_> {
+    /// Window-pixel point that lands on surface point `p`.
+    #[inline]
+    fn to_window(&self, p: Vec2) -> Vec2 {
+        self.inverse.rotate(p - self.position) + self.fragment.centroid
+    }
+}
+
+/// Fragments falling under shared gravity.
+pub struct FragmentSim {
+    fragments: Vec<Fragment>,
+    /// Pixels per second squared, +y is down
+    gravity: Vec2,
+}
+
+impl FragmentSim {
+    pub fn new(fragments: Vec<Fragment>, gravity: Vec2) -> Self {
+        Self { fragments, gravity }
+    }
+
+    pub fn fragments(&self) -> &[Fragment] {
+        &self.fragments
+    }
+
+    /// Surface-space bounding box of every fragment `t` seconds in.
+    pub fn bounds(&self, window: &Rect, t: f32) -> Rect {
+        self.place(window, t)
+            .iter()
+            .map(|placed| placed.bounds)
+            .reduce(|a, b| a.union(&b))
+            .unwrap_or_default()
+    }
+
+    /// Draw every fragment `t` seconds in, composited over the canvas and
+    /// faded by `opacity`. Only pixels inside `target.bounds` are written.
+    pub fn render(&self, target: &mut Target, texture: &Texture, t: f32, opacity: f32) {
+        let bounds = target.bounds;
+        if bounds.is_empty() || opacity <= 0.0 {
+            return;
+        }
+
+        let placed = self.place(&target.window, t);
+        let window_size = Vec2::new(target.window.width as f32, target.window.height as f32);
+        let lod = (texture.width() as f32 / window_size.x.max(1.0)).log2().max(0.0);
+
+        let row_bytes = target.width * 4;
+        let row_start = bounds.y as usize;
+        let row_end = bounds.bottom() as usize;
+
+        target.canvas[row_start * row_bytes..row_end * row_bytes]
+            .par_chunks_mut(row_bytes)
+            .enumerate()
+            .for_each(|(row_index, row)| {
+                let y = (row_start + row_index) as i32;
+                for piece in placed.iter().filter(|p| (p.bounds.y..p.bounds.bottom()).contains(&y)) {
+                    let span = piece.bounds.intersect(&bounds);
+                    for x in span.x..span.right() {
+                        let p = piece.to_window(Vec2::new(x as f32 + 0.5, y as f32 + 0.5));
+
+                        // One pixel of antialiasing across the outline
+                        let distance = piece.fragment.inside_distance(p);
+                        let coverage = match piece.resting {
+                            true if distance >= 0.0 => 1.0,
+                            true => 0.0,
+                            false => (distance + 0.5).min(1.0),
+                        };
+                        if coverage <= 0.0 {
+                            continue;
+                        }
+
+                        let uv = p / window_size;
+                        let src = texture.sample(uv.x, uv.y, lod);
+                        let px = &mut row[x as usize * 4..x as usize * 4 + 4];
+                        blend_bgra(px, src, coverage * opacity);
+                    }
+                }
+            });
+    }
+
+    /// Every fragment's surface placement `t` seconds in.
+    fn place<'a>(&'a self, window: &Rect, t: f32) -> Vec<Placed<'a>> {
+        let origin = Vec2::new(window.x as f32, window.y as f32);
+        self.fragments
+            .iter()
+            .map(|fragment| {
+                let pose = fragment.pose(t, self.gravity);
+                let position = origin + fragment.centroid + pose.offset;
+                let rotation = Vec2::from_angle(pose.angle);
+
+                let (min, max) = fragment.polygon.iter().fold(
+                    (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+                    |(min, max), &v| {
+                        let v = position + rotation.rotate(v - fragment.centroid);
+                        (min.min(v), max.max(v))
+                    },
+                );
+                // A pixel of slack for the antialiased edge
+                let min = min.floor() - Vec2::ONE;
+                let max = max.ceil() + Vec2::ONE;
+                let bounds = Rect::new(
+                    min.x as i32,
+                    min.y as i32,
+                    (max.x - min.x) as i32,
+                    (max.y - min.y) as i32,
+                );
+
+                Placed {
+                    fragment,
+                    position,
+                    inverse: Vec2::from_angle(-pose.angle),
+                    bounds,
+                    resting: t <= fragment.delay,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Signed area and centroid of a simple polygon.
+fn area_centroid(polygon: &[Vec2]) -> (f32, Vec2) {
+    let mut area = 0.0;
+    let mut centroid = Vec2::ZERO;
+    for (&a, &b) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+        let cross = a.perp_dot(b);
+        area += cross;
+        centroid += (a + b) * cross;
+    }
+    area *= 0.5;
+    if area.abs() < f32::EPSILON {
+        return (0.0, polygon.first().copied().unwrap_or_default());
+    }
+    (area, centroid / (6.0 * area))
+}
+
+/// Voronoi cells of `sites` inside a `width` x `height` rectangle, one per
+/// site and in the same order. Every cell is convex; a site given twice
+/// only gets its cell the first time, and an empty one after that.
+pub fn voronoi_cells(width: f32, height: f32, sites: &[Vec2]) -> Vec<Vec<Vec2>> {
+    let rect = vec![
+        Vec2::ZERO,
+        Vec2::new(width, 0.0),
+        Vec2::new(width, height),
+        Vec2::new(0.0, height),
+    ];
+
+    sites
+        .par_iter()
+        .enumerate()
+        .map(|(i, &site)| {
+            if sites[..i].contains(&site) {
+                return Vec::new();
+            }
+            sites
+                .iter()
+                .filter(|&&other| other != site)
+                .fold(rect.clone(), |cell, &other| {
+                    // Keep the half closer to `site` than to `other`
+                    let normal = other - site;
+                    let offset = normal.dot((site + other) * 0.5);
+                    clip(&cell, normal, offset)
+                })
+        })
+        .collect()
+}
+
+/// Sutherland-Hodgman: the part of `polygon` where `normal.dot(p) <= offset`.
+fn clip(polygon: &[Vec2], normal: Vec2, offset: f32) -> Vec<Vec2> {
+    let mut out = Vec::with_capacity(polygon.len() + 1);
+    for (&a, &b) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+        let da = offset - normal.dot(a);
+        let db = offset - normal.dot(b);
+        if da >= 0.0 {
+            out.push(a);
+        }
+        if (da >= 0.0) != (db >= 0.0) {
+            out.push(a + (b - a) * (da / (da - db)));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    const WIDTH: f32 = 640.0;
+    const HEIGHT: f32 = 360.0;
+
+    fn random_sites(seed: u64, count: usize) -> Vec<Vec2> {
+        let mut rng = Rng::new(seed);
+        (0..count)
+            .map(|_| Vec2::new(rng.range(0.0, WIDTH), rng.range(0.0, HEIGHT)))
+            .collect()
+    }
+
+    /// Whether `polygon` turns the same way at every corner.
+    fn is_convex(polygon: &[Vec2]) -> bool {
+        let n = polygon.len();
+        let turns: Vec<f32> = (0..n)
+            .map(|i| {
+                let (a, b, c) = (polygon[i], polygon[(i + 1) % n], polygon[(i + 2) % n]);
+                (b - a).perp_dot(c - b)
+            })
+            .collect();
+        turns.iter().all(|&t| t >= -1e-2) || turns.iter().all(|&t| t <= 1e-2)
+    }
+
+    #[test]
+    fn cells_tile_the_rect() {
+        for seed in 0..8 {
+            let sites = random_sites(seed, 40);
+            let cells = voronoi_cells(WIDTH, HEIGHT, &sites);
+            assert_eq!(cells.len(), sites.len());
+            let total: f32 = cells.iter().map(|cell| area_centroid(cell).0.abs()).sum();
+            assert!((total - WIDTH * HEIGHT).abs() < 1.0, "seed {seed}: {total}");
+        }
+    }
+
+    #[test]
+    fn cells_are_convex_and_hold_their_site() {
+        let sites = random_sites(3, 40);
+        for (cell, site) in voronoi_cells(WIDTH, HEIGHT, &sites).iter().zip(&sites) {
+            assert!(is_convex(cell), "{cell:?}");
+            let fragment = Fragment::new(cell.clone()).unwrap();
+            assert!(fragment.inside_distance(*site) >= -1e-3, "{site} outside {cell:?}");
+        }
+    }
+
+    #[test]
+    fn duplicate_sites_share_one_cell() {
+        let mut sites = random_sites(5, 10);
+        sites.push(sites[2]);
+        sites.insert(0, sites[7]);
+        let cells = voronoi_cells(WIDTH, HEIGHT, &sites);
+
+        // The first of each pair keeps the cell, so nothing is drawn twice
+        assert!(!cells[0].is_empty() && cells[8].is_empty());
+        assert!(!cells[3].is_empty() && cells[11].is_empty());
+        let total: f32 = cells.iter().map(|cell| area_centroid(cell).0.abs()).sum();
+        assert!((total - WIDTH * HEIGHT).abs() < 1.0, "{total}");
+        assert_eq!(cells.into_iter().filter_map(Fragment::new).count(), 10);
+    }
+
+    #[test]
+    fn rests_until_its_delay() {
+        let square = vec![Vec2::ZERO, Vec2::X * 10.0, Vec2::splat(10.0), Vec2::Y * 10.0];
+        let fragment = Fragment::new(square)
+            .unwrap()
+            .with_motion(Vec2::new(300.0, -200.0), 4.0, 0.25);
+        let gravity = Vec2::new(0.0, 900.0);
+        for t in [0.0, 0.1, 0.25] {
+            let pose = fragment.pose(t, gravity);
+            assert_eq!(pose.offset, Vec2::ZERO);
+            assert_eq!(pose.angle, 0.0);
+        }
+        let pose = fragment.pose(0.5, gravity);
+        assert!(pose.offset.distance(Vec2::new(75.0, -50.0 + 28.125)) < 1e-3, "{:?}", pose);
+        assert!((pose.angle - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,162 @@
+//! Genie: the window pinches toward a target rectangle and pours into it.
+//!
+//! The maths works in a frame where the window always flows toward +y, so
+//! the same curve serves a dock below, above or to either side.
+
+use glam::{Vec2, Vec2Swizzles, Vec3};
+
+use super::mesh::Deform;
+use crate::easing::smoothstep;
+
+/// The pinch finishes forming at this progress.
+const SQUEEZE_END: f32 = 0.45;
+
+/// Rows start sliding into the target at this progress.
+const SLIDE_START: f32 = 0.25;
+
+/// The last bit of the slide also fades out.
+const FADE_START: f32 = 0.85;
+
+/// Maps window pixels into the frame where the target lies toward +y.
+#[derive(Debug, Clone, Copy)]
+struct Funnel {
+    /// Flowing sideways: x and y trade places
+    swap: bool,
+    /// Flowing up or left: the axis runs backwards
+    flip: bool,
+    /// Window length along the flow
+    along: f32,
+}
+
+impl Funnel {
+    /// The frame for a `size` window flowing toward `target` (window pixels).
+    fn toward(size: Vec2, target: Vec2) -> Self {
+        let offset = (target - size * 0.5) / size;
+        let swap = offset.x.abs() > offset.y.abs();
+        Self {
+            swap,
+            flip: if swap { offset.x < 0.0 } else { offset.y < 0.0 },
+            along: if swap { size.x } else { size.y },
+        }
+    }
+
+    fn to_frame(self, p: Vec2) -> Vec2 {
+        let q = if self.swap { p.yx() } else { p };
+        if self.flip {
+            Vec2::new(q.x, self.along - q.y)
+        } else {
+            q
+        }
+    }
+
+    fn to_window(self, q: Vec2) -> Vec2 {
+        let p = if self.flip { Vec2::new(q.x, self.along - q.y) } else { q };
+        if self.swap {
+            p.yx()
+        } else {
+            p
+        }
+    }
+}
+
+/// Genie motion of one `size` window into `target`.
+#[derive(Debug, Clone, Copy)]
+pub struct GenieDeform {
+    size: Vec2,
+    funnel: Funnel,
+    /// Target corners in the funnel frame
+    target_min: Vec2,
+    target_max: Vec2,
+}
+
+impl GenieDeform {
+    /// `target_min`/`target_max` are the target's corners in window pixels
+    /// (relative to the window's top left); it may lie anywhere.
+    pub fn new(size: Vec2, target_min: Vec2, target_max: Vec2) -> Self {
+        let funnel = Funnel::toward(size, (target_min + target_max) * 0.5);
+        let a = funnel.to_frame(target_min);
+        let b = funnel.to_frame(target_max);
+        Self {
+            size,
+            funnel,
+            target_min: a.min(b),
+            target_max: a.max(b),
+        }
+    }
+}
+
+impl Deform for GenieDeform {
+    fn vertex(&self, uv: Vec2, progress: f32) -> Vec3 {
+        let p = self.funnel.to_frame(uv * self.size);
+        let across = if self.funnel.swap { self.size.y } else { self.size.x };
+        let squeeze = smoothstep(progress / SQUEEZE_END);
+        let slide = smoothstep((progress - SLIDE_START) / (1.0 - SLIDE_START));
+
+        // Each row slides to its place in the target, keeping its order
+        let depth = p.y / self.funnel.along;
+        let landing = self.target_min.y + depth * (self.target_max.y - self.target_min.y);
+        let y = p.y + (landing - p.y) * slide;
+
+        // Rows narrow toward the target's width the closer they get to it
+        let closeness = smoothstep(y / self.target_min.y.max(1.0));
+        let narrowed = self.target_min.x + p.x / across * (self.target_max.x - self.target_min.x);
+        let x = p.x + (narrowed - p.x) * (squeeze * closeness).max(slide * slide);
+
+        self.funnel.to_window(Vec2::new(x, y)).extend(0.0)
+    }
+
+    fn opacity(&self, progress: f32) -> f32 {
+        1.0 - smoothstep((progress - FADE_START) / (1.0 - FADE_START))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: Vec2 = Vec2::new(400.0, 300.0);
+
+    /// A grid of uvs over the whole window, edges included.
+    fn uvs() -> Vec<Vec2> {
+        let steps = [0.0, 0.2, 0.5, 0.8, 1.0];
+        steps
+            .iter()
+            .flat_map(|&v| steps.iter().map(move |&u| Vec2::new(u, v)))
+            .collect()
+    }
+
+    /// Targets below, above, left and right of the window, as (min, max).
+    fn targets() -> [(Vec2, Vec2); 4] {
+        [
+            (Vec2::new(150.0, 600.0), Vec2::new(200.0, 640.0)),
+            (Vec2::new(300.0, -500.0), Vec2::new(340.0, -460.0)),
+            (Vec2::new(-400.0, 10.0), Vec2::new(-360.0, 50.0)),
+            (Vec2::new(900.0, 250.0), Vec2::new(960.0, 290.0)),
+        ]
+    }
+
+    #[test]
+    fn starts_as_the_window() {
+        for (min, max) in targets() {
+            let deform = GenieDeform::new(SIZE, min, max);
+            for uv in uvs() {
+                let p = deform.vertex(uv, 0.0);
+                assert!(p.distance((uv * SIZE).extend(0.0)) < 1e-3, "{uv} toward {min}: {p}");
+            }
+            assert_eq!(deform.opacity(0.0), 1.0);
+        }
+    }
+
+    #[test]
+    fn ends_inside_the_target() {
+        for (min, max) in targets() {
+            let deform = GenieDeform::new(SIZE, min, max);
+            for uv in uvs() {
+                let p = deform.vertex(uv, 1.0).truncate();
+                let inside = p.cmpge(min - 1e-3).all() && p.cmple(max + 1e-3).all();
+                assert!(inside, "{uv} toward {min}..{max} lands at {p}");
+            }
+            assert_eq!(deform.opacity(1.0), 0.0);
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Glitch: the window decays like a failing video signal. Pixel blocks grow,
+//! red and blue drift apart, horizontal slices jump sideways, and finally
+//! blocks drop out until nothing is left.
+//!
+//! Each block is box-filtered from a 2x2 grid of trilinear samples, and each
+//! of those splits into three for the channels, so every pixel takes twelve
+//! texture samples. That makes this the heaviest user of the sampling layer
+//! and the one `benches/glitch.rs` measures.
+
+use glam::{Vec2, Vec3, Vec4};
+use rayon::prelude::*;
+
+use super::{CpuRenderer, Target, Texture};
+use crate::damage::Rect;
+use crate::noise;
+use crate::pixel::write_bgra;
+
+/// Times per animation the slices are knocked into new places.
+const TICKS: f32 = 16.0;
+
+/// Height of a slice, in pixels.
+const SLICE_PX: f32 = 24.0;
+
+/// Farthest a slice jumps, in window widths.
+const SHIFT: f32 = 0.12;
+
+/// Progress at which blocks start dropping out.
+const CUTOFF_AT: f32 = 0.7;
+
+/// Seed offsets so the random choices don't line up with each other.
+const SLICE_SEED: u32 = 1;
+const SHIFT_SEED: u32 = 2;
+const CUTOFF_SEED: u32 = 3;
+
+/// Tunable settings.
+#[derive(Debug, Clone, Copy)]
+pub struct GlitchParams {
+    /// Largest block size, reached at the end, in pixels
+    pub block: f32,
+    /// Red/blue offset at its widest, in window widths
+    pub split: f32,
+    /// Chance that a slice is knocked sideways at the end, 0..1
+    pub slices: f32,
+    pub seed: u32,
+}
+
+/// The decay at one moment.
+#[derive(Debug, Clone, Copy)]
+struct Frame {
+    /// Block size in pixels
+    block: f32,
+    /// Red/blue offset in pixels
+    split: f32,
+    /// Chance that a slice is displaced
+    slice_chance: f32,
+    /// Farthest displacement in pixels
+    shift: f32,
+    /// Which set of slice displacements is showing
+    tick: i32,
+    /// Share of blocks that have dropped out
+    cutoff: f32,
+}
+
+impl GlitchParams {
+    /// The decay at `progress` in a `size` window.
+    fn frame(&self, size: Vec2, progress: f32) -> Frame {
+        Frame {
+            block: (1.0 + (self.block - 1.0).max(0.0) * progress * progress).round(),
+            split: self.split * progress * size.x,
+            slice_chance: self.slices * progress,
+            shift: SHIFT * progress * size.x,
+            tick: (progress * TICKS) as i32,
+            cutoff: ((progress - CUTOFF_AT) / (1.0 - CUTOFF_AT)).clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Per-close glitch state.
+pub struct GlitchRenderer {
+    params: GlitchParams,
+}
+
+impl GlitchRenderer {
+    pub fn new(params: GlitchParams) -> Self {
+        Self { params }
+    }
+
+    /// Premultiplied color at window pixel `p` of a `size` window.
+    fn shade(&self, texture: &Texture, frame: &Frame, p: Vec2, size: Vec2, lod: f32) -> Vec4 {
+        let seed = self.params.seed;
+
+        // Knock the slice sideways, re-rolled every tick
+        let slice = (p.y / SLICE_PX).floor() as i32;
+        let mut q = p;
+        if noise::random(slice, frame.tick, seed ^ SLICE_SEED) < frame.slice_chance {
+            let shift = noise::random(slice, frame.tick, seed ^ SHIFT_SEED) * 2.0 - 1.0;
+            q.x -= shift * frame.shift;
+        }
+        if q.x < 0.0 || q.x >= size.x {
+            return Vec4::ZERO;
+        }
+
+        let cell = (q / frame.block).floor();
+        if noise::random(cell.x as i32, cell.y as i32, seed ^ CUTOFF_SEED) < frame.cutoff {
+            return Vec4::ZERO;
+        }
+
+        // 2x2 box filter over the block, each sample split into channels
+        let center = (cell + 0.5) * frame.block;
+        let quarter = frame.block * 0.25;
+        let fringe = Vec2::new(frame.split, 0.0);
+        let mut color = Vec4::ZERO;
+        for offset in [
+            Vec2::new(-quarter, -quarter),
+            Vec2::new(quarter, -quarter),
+            Vec2::new(-quarter, quarter),
+            Vec2::new(quarter, quarter),
+        ] {
+            let at = center + offset;
+            let red = texture_at(texture, at + fringe, size, lod);
+            let green = texture_at(texture, at, size, lod);
+            let blue = texture_at(texture, at - fringe, size, lod);
+            color += Vec4::new(red.x, green.y, blue.z, red.w.max(green.w).max(blue.w));
+        }
+        // Split channels can leave a color brighter than its alpha allows
+        let color = color * 0.25;
+        color.truncate().min(Vec3::splat(color.w)).extend(color.w)
+    }
+}
+
+impl CpuRenderer for GlitchRenderer {
+    fn bounds(&self, window: &Rect, progress: f32) -> Rect {
+        let size = Vec2::new(window.width as f32, window.height as f32);
+        let reach = self.params.frame(size, progress).shift.ceil() as i32;
+        Rect::new(window.x - reach, window.y, window.width + 2 * reach, window.height)
+    }
+
+    fn render(&self, target: &mut Target, texture: &Texture, progress: f32) {
+        let bounds = target.bounds.intersect(&self.bounds(&target.window, progress));
+        if bounds.is_empty() {
+            return;
+        }
+        let window = target.window;
+        let size = Vec2::new(window.width.max(1) as f32, window.height.max(1) as f32);
+        let frame = self.params.frame(size, progress);
+
+        // Samples sit half a block apart
+        let texel_scale = texture.width() as f32 / size.x;
+        let lod = (texel_scale * frame.block * 0.5).log2().max(0.0);
+
+        let row_bytes = target.width * 4;
+        target.canvas[bounds.y as usize * row_bytes..bounds.bottom() as usize * row_bytes]
+            .par_chunks_mut(row_bytes)
+            .enumerate()
+            .for_each(|(row_index, row)| {
+                let y = bounds.y + row_index as i32;
+                for x in bounds.x..bounds.right() {
+                    let p = Vec2::new((x - window.x) as f32 + 0.5, (y - window.y) as f32 + 0.5);
+                    let color = self.shade(texture, &frame, p, size, lod);
+                    let px = &mut row[x as usize * 4..x as usize * 4 + 4];
+                    write_bgra(px, color.into());
+                }
+            });
+    }
+}
+
+/// Premultiplied color at window pixel `p`, clamped to the window's edges.
+fn texture_at(texture: &Texture, p: Vec2, size: Vec2, lod: f32) -> Vec4 {
+    let uv = (p / size).clamp(Vec2::ZERO, Vec2::ONE);
+    Vec4::from(texture.sample(uv.x, uv.y, lod))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixel::{Image, PixelFormat};
+
+    const WIDTH: usize = 96;
+    const HEIGHT: usize = 64;
+
+    const PARAMS: GlitchParams = GlitchParams {
+        block: 16.0,
+        split: 0.02,
+        slices: 0.5,
+        seed: 7,
+    };
+
+    #[test]
+    fn blocks_only_grow() {
+        let size = Vec2::new(WIDTH as f32, HEIGHT as f32);
+        let mut last = PARAMS.frame(size, 0.0).block;
+        assert_eq!(last, 1.0);
+        for i in 1..=100 {
+            let block = PARAMS.frame(size, i as f32 / 100.0).block;
+            assert!(block >= last, "block shrank to {block} at {i}%");
+            last = block;
+        }
+        assert_eq!(last, PARAMS.block);
+    }
+
+    #[test]
+    fn every_block_drops_out_by_the_end() {
+        let size = Vec2::new(WIDTH as f32, HEIGHT as f32);
+        assert_eq!(PARAMS.frame(size, CUTOFF_AT).cutoff, 0.0);
+        assert_eq!(PARAMS.frame(size, 1.0).cutoff, 1.0);
+
+        let pixels = vec![255; WIDTH * HEIGHT * 4];
+        let texture = Texture::new(Image::new(pixels, WIDTH, HEIGHT, PixelFormat::Rgba8));
+        let renderer = GlitchRenderer::new(PARAMS);
+        let window = Rect::new(0, 0, WIDTH as i32, HEIGHT as i32);
+        let mut canvas = vec![0u8; WIDTH * HEIGHT * 4];
+        let mut target = Target {
+            canvas: &mut canvas,
+            width: WIDTH,
+            window,
+            bounds: renderer.bounds(&window, 1.0).intersect(&window),
+        };
+        renderer.render(&mut target, &texture, 1.0);
+        assert!(canvas.iter().all(|&c| c == 0));
+    }
+}
@@ -0,0 +1,1100 @@
+//! GPU renderer: runs an animation's WGSL with wgpu, off screen, and reads
+//! every frame back into the SHM canvas.
+//!
+//! This is how animations that only exist as a shader (the files in
+//! `shaders/`) get drawn. One device is opened the first time it's needed
+//! and shared by every close after it, along with a pipeline per shader;
+//! each close gets its own textures and buffers.
+//!
+//! Shaders place vertices in clip space where -1..1 spans the window. A mesh
+//! can leave the window, so it's drawn into a target that also covers the
+//! monitor, and its vertex shader's position is rewritten (in naga's IR) to
+//! map the window's clip space onto that target. A fragment shader that
+//! draws outside the window (`Animation::leaves_window`) gets the same
+//! target, covered by a quad whose uv runs past the window's edges.
+
+use std::borrow::Cow;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::{mpsc, Arc, Mutex, MutexGuard, OnceLock};
+use std::task::{Context as TaskContext, Poll, Waker};
+use std::time::Instant;
+
+use anyhow::{Context, Result};
+use naga::{
+    AddressSpace, Arena, BinaryOperator, Binding, Block, BuiltIn, Expression, GlobalVariable,
+    Handle, ResourceBinding, Scalar, ShaderStage, Span, Statement, SwizzleComponent, Type,
+    TypeInner, VectorSize,
+};
+use tracing::{debug, error, info, warn};
+use wgpu::util::DeviceExt;
+
+use super::{CpuRenderer, Target, Texture};
+use crate::animation::{Animation, AnimationUniforms, Placement, ShaderSource};
+use crate::damage::Rect;
+use crate::shader;
+
+/// Format of the off-screen target; read back and swizzled to the canvas's BGRA.
+const TARGET_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+/// Depth buffer format for meshes, which can overlap themselves.
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Binding of the `vec4<f32>` that maps window clip space onto the target:
+/// scale in xy, offset in zw.
+const AREA_BINDING: u32 = 3;
+
+/// Vertex shader for animations that don't move vertices: one quad over the
+/// window, as a triangle strip, handing the fragment shader its uv.
+const QUAD_VERTEX_SHADER: &str = r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@group(0) @binding(3) var<uniform> area: vec4<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32(index & 1u), f32(index >> 1u));
+    let ndc = vec2<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0);
+    var out: VertexOutput;
+    out.position = vec4<f32>(ndc * area.xy + area.zw, 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
+"#;
+
+/// Vertex shader for a fragment shader that leaves the window: one quad
+/// over the whole target, with the window's uv carried on past its edges.
+const COVER_VERTEX_SHADER: &str = r#"
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+@group(0) @binding(3) var<uniform> area: vec4<f32>;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let corner = vec2<f32>(f32(index & 1u), f32(index >> 1u));
+    let ndc = vec2<f32>(corner.x * 2.0 - 1.0, 1.0 - corner.y * 2.0);
+    // Back into the window's clip space, and from there to its uv
+    let window = (ndc - area.zw) / area.xy;
+    var out: VertexOutput;
+    out.position = vec4<f32>(ndc, 0.0, 1.0);
+    out.uv = vec2<f32>(window.x + 1.0, 1.0 - window.y) * 0.5;
+    return out;
+}
+"#;
+
+/// Most pipelines kept compiled. Saving a shader file makes a new one each
+/// time, so the least recently used are dropped past this.
+const MAX_PIPELINES: usize = 16;
+
+/// What a pipeline draws its fragment shader over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Geometry {
+    /// A quad over the window
+    Window,
+    /// A quad over the whole target
+    Cover,
+    /// A grid moved by this vertex shader
+    Mesh(ShaderSource),
+}
+
+/// A compiled pipeline and the shader source it was compiled from.
+struct CachedPipeline {
+    fragment: Box<str>,
+    geometry: Geometry,
+    pipeline: Arc<wgpu::RenderPipeline>,
+}
+
+/// Compiled pipelines, least recently used first.
+type PipelineCache = Vec<CachedPipeline>;
+
+/// The device every GPU close shares.
+struct Gpu {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    sampler: wgpu::Sampler,
+    quad: wgpu::ShaderModule,
+    cover: wgpu::ShaderModule,
+    pipelines: Mutex<PipelineCache>,
+}
+
+impl Gpu {
+    /// The shared device, opened on first use; `None` (logged once) if
+    /// there's no adapter.
+    fn shared() -> Option<&'static Gpu> {
+        static GPU: OnceLock<Option<Gpu>> = OnceLock::new();
+        GPU.get_or_init(|| match Gpu::open() {
+            Ok(gpu) => Some(gpu),
+            Err(e) => {
+                warn!("GPU rendering unavailable: {:#}", e);
+                None
+            }
+        })
+        .as_ref()
+    }
+
+    fn open() -> Result<Self> {
+        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+        let adapter = block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::LowPower,
+            ..Default::default()
+        }))
+        .context("No GPU adapter")?;
+        let adapter_info = adapter.get_info();
+        let (device, queue) = block_on(adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: Some("hypr-vortex"),
+                // What GLES 3 can do, with the adapter's texture size
+                required_limits: wgpu::Limits::downlevel_webgl2_defaults()
+                    .using_resolution(adapter.limits()),
+                ..Default::default()
+            },
+            None,
+        ))
+        .context("Failed to open the GPU")?;
+        // A bad shader or a lost device logs instead of taking the daemon down
+        device.on_uncaptured_error(Box::new(|e| error!("GPU error: {}", e)));
+        info!("GPU rendering on {} ({:?})", adapter_info.name, adapter_info.backend);
+
+        let uniform = wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("animation"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                    ty: uniform,
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: AREA_BINDING,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: uniform,
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("animation"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        // Trilinear, clamped: what the CPU renderers' `Texture::sample` does
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("window"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let quad = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("quad"),
+            source: wgpu::ShaderSource::Wgsl(QUAD_VERTEX_SHADER.into()),
+        });
+        let cover = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("cover"),
+            source: wgpu::ShaderSource::Wgsl(COVER_VERTEX_SHADER.into()),
+        });
+
+        Ok(Self {
+            device,
+            queue,
+            layout,
+            pipeline_layout,
+            sampler,
+            quad,
+            cover,
+            pipelines: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// The pipeline for `fragment` drawn over `geometry`; compiled the first
+    /// time it's asked for, and kept while it's among the recently used.
+    fn pipeline(
+        &self,
+        name: &str,
+        fragment: &str,
+        geometry: Geometry,
+    ) -> Result<Arc<wgpu::RenderPipeline>> {
+        {
+            let mut pipelines = self.lock_pipelines();
+            let cached = pipelines
+                .iter()
+                .position(|cached| cached.geometry == geometry && *cached.fragment == *fragment);
+            if let Some(index) = cached {
+                let cached = pipelines.remove(index);
+                let pipeline = Arc::clone(&cached.pipeline);
+                pipelines.push(cached);
+                return Ok(pipeline);
+            }
+        }
+
+        let mesh_module = match geometry {
+            Geometry::Mesh(source) => {
+                let mut module = naga::front::wgsl::parse_str(source)
+                    .map_err(|e| anyhow::anyhow!(e.emit_to_string(source)))?;
+                fit_to_area(&mut module)
+                    .with_context(|| format!("Can't place the '{}' mesh", name))?;
+                Some(self.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: Some(name),
+                    source: wgpu::ShaderSource::Naga(Cow::Owned(module)),
+                }))
+            }
+            Geometry::Window | Geometry::Cover => None,
+        };
+        let mesh_layout = [wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<[f32; 4]>() as u64,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2],
+        }];
+        let (vertex, primitive, depth_stencil) = match &mesh_module {
+            Some(module) => (
+                wgpu::VertexState {
+                    module,
+                    entry_point: shader::VERTEX_ENTRY_POINT,
+                    compilation_options: Default::default(),
+                    buffers: &mesh_layout,
+                },
+                wgpu::PrimitiveTopology::TriangleList,
+                Some(wgpu::DepthStencilState {
+                    format: DEPTH_FORMAT,
+                    depth_write_enabled: true,
+                    // Ties go to the later triangle, as they would unsorted
+                    depth_compare: wgpu::CompareFunction::LessEqual,
+                    stencil: Default::default(),
+                    bias: Default::default(),
+                }),
+            ),
+            None => (
+                wgpu::VertexState {
+                    module: match geometry {
+                        Geometry::Cover => &self.cover,
+                        _ => &self.quad,
+                    },
+                    entry_point: shader::VERTEX_ENTRY_POINT,
+                    compilation_options: Default::default(),
+                    buffers: &[],
+                },
+                wgpu::PrimitiveTopology::TriangleStrip,
+                None,
+            ),
+        };
+
+        // naga has already validated it, but the backend may still refuse it
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = self.device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some(name),
+            source: wgpu::ShaderSource::Wgsl(fragment.into()),
+        });
+        let pipeline = self.device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some(name),
+            layout: Some(&self.pipeline_layout),
+            vertex,
+            primitive: wgpu::PrimitiveState {
+                topology: primitive,
+                ..Default::default()
+            },
+            depth_stencil,
+            multisample: Default::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: shader::ENTRY_POINT,
+                compilation_options: Default::default(),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: TARGET_FORMAT,
+                    // Shaders return premultiplied color
+                    blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+            cache: None,
+        });
+        if let Some(e) = block_on(self.device.pop_error_scope()) {
+            anyhow::bail!("GPU rejected the '{}' shader: {}", name, e);
+        }
+
+        let pipeline = Arc::new(pipeline);
+        let mut pipelines = self.lock_pipelines();
+        pipelines.push(CachedPipeline {
+            fragment: fragment.into(),
+            geometry,
+            pipeline: Arc::clone(&pipeline),
+        });
+        if pipelines.len() > MAX_PIPELINES {
+            pipelines.remove(0);
+        }
+        debug!("Compiled GPU pipeline for '{}'", name);
+        Ok(pipeline)
+    }
+
+    fn lock_pipelines(&self) -> MutexGuard<'_, PipelineCache> {
+        self.pipelines.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// The window as a `columns` x `rows` grid, cell by cell: each cell's four
+/// corners as (uv, uv of the cell's middle), and its two triangles.
+fn grid((columns, rows): (u32, u32)) -> (Vec<[f32; 4]>, Vec<u32>) {
+    let (columns, rows) = (columns.max(1), rows.max(1));
+    let size = [columns as f32, rows as f32];
+    let cells = (0..rows).flat_map(|row| (0..columns).map(move |column| (column, row)));
+    let vertices = cells
+        .clone()
+        .flat_map(|(c, r)| {
+            let middle = [(c as f32 + 0.5) / size[0], (r as f32 + 0.5) / size[1]];
+            [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(dc, dr)| {
+                let corner = [(c + dc) as f32 / size[0], (r + dr) as f32 / size[1]];
+                [corner[0], corner[1], middle[0], middle[1]]
+            })
+        })
+        .collect();
+    let indices = (0..columns * rows)
+        .flat_map(|cell| [0, 1, 2, 1, 3, 2].map(|corner| cell * 4 + corner))
+        .collect();
+    (vertices, indices)
+}
+
+/// Rewrite the vertex entry point of `module` so every position it returns
+/// goes through the area uniform: `xy * area.xy + area.zw * w`.
+fn fit_to_area(module: &mut naga::Module) -> Result<()> {
+    let entry = module
+        .entry_points
+        .iter()
+        .position(|ep| ep.name == shader::VERTEX_ENTRY_POINT && ep.stage == ShaderStage::Vertex)
+        .context("No vertex entry point")?;
+    let result = module.entry_points[entry].function.result.as_ref().context("No output")?;
+    let is_position = |binding: &Option<Binding>| {
+        matches!(binding, Some(Binding::BuiltIn(BuiltIn::Position { .. })))
+    };
+    let member = if is_position(&result.binding) {
+        None
+    } else {
+        let TypeInner::Struct { members, .. } = &module.types[result.ty].inner else {
+            anyhow::bail!("Output has no @builtin(position)");
+        };
+        let index = members
+            .iter()
+            .position(|member| is_position(&member.binding))
+            .context("Output has no @builtin(position)")?;
+        Some((index as u32, members.len() as u32))
+    };
+    let output = result.ty;
+
+    let vec4 = module.types.insert(
+        Type {
+            name: None,
+            inner: TypeInner::Vector {
+                size: VectorSize::Quad,
+                scalar: Scalar::F32,
+            },
+        },
+        Span::UNDEFINED,
+    );
+    let area = module.global_variables.append(
+        GlobalVariable {
+            name: Some("hypr_vortex_area".into()),
+            space: AddressSpace::Uniform,
+            binding: Some(ResourceBinding {
+                group: 0,
+                binding: AREA_BINDING,
+            }),
+            ty: vec4,
+            init: None,
+        },
+        Span::UNDEFINED,
+    );
+
+    let function = &mut module.entry_points[entry].function;
+    let area = function
+        .expressions
+        .append(Expression::GlobalVariable(area), Span::UNDEFINED);
+    let fit = Fit {
+        area,
+        vec4,
+        output,
+        member,
+    };
+    let body = std::mem::take(&mut function.body);
+    function.body = fit.returns(body, &mut function.expressions);
+    Ok(())
+}
+
+/// What `fit_to_area` needs to rewrite a return.
+struct Fit {
+    /// Pointer to the area uniform
+    area: Handle<Expression>,
+    vec4: Handle<Type>,
+    /// The entry point's return type
+    output: Handle<Type>,
+    /// Index of the position in the returned struct and how many members it
+    /// has, or `None` if the position is returned alone
+    member: Option<(u32, u32)>,
+}
+
+impl Fit {
+    /// `block` with every return value fitted, recursing into nested blocks.
+    fn returns(&self, block: Block, expressions: &mut Arena<Expression>) -> Block {
+        let mut fitted = Block::with_capacity(block.len());
+        for (statement, span) in block.span_into_iter() {
+            let statement = match statement {
+                Statement::Return { value: Some(value) } => {
+                    let start = expressions.len();
+                    let value = self.value(value, expressions);
+                    fitted.push(Statement::Emit(expressions.range_from(start)), span);
+                    Statement::Return { value: Some(value) }
+                }
+                Statement::Block(block) => Statement::Block(self.returns(block, expressions)),
+                Statement::If {
+                    condition,
+                    accept,
+                    reject,
+                } => Statement::If {
+                    condition,
+                    accept: self.returns(accept, expressions),
+                    reject: self.returns(reject, expressions),
+                },
+                Statement::Switch { selector, cases } => Statement::Switch {
+                    selector,
+                    cases: cases
+                        .into_iter()
+                        .map(|case| naga::SwitchCase {
+                            body: self.returns(case.body, expressions),
+                            ..case
+                        })
+                        .collect(),
+                },
+                Statement::Loop {
+                    body,
+                    continuing,
+                    break_if,
+                } => Statement::Loop {
+                    body: self.returns(body, expressions),
+                    continuing: self.returns(continuing, expressions),
+                    break_if,
+                },
+                statement => statement,
+            };
+            fitted.push(statement, span);
+        }
+        fitted
+    }
+
+    /// Expressions for `value` with its position fitted; the caller emits them.
+    fn value(
+        &self,
+        value: Handle<Expression>,
+        expressions: &mut Arena<Expression>,
+    ) -> Handle<Expression> {
+        use SwizzleComponent::{W, X, Y, Z};
+
+        let mut add = |expression| expressions.append(expression, Span::UNDEFINED);
+        let swizzle = |vector, [a, b]: [SwizzleComponent; 2]| Expression::Swizzle {
+            size: VectorSize::Bi,
+            vector,
+            pattern: [a, b, X, X],
+        };
+
+        let area = add(Expression::Load { pointer: self.area });
+        let position = match self.member {
+            Some((index, _)) => add(Expression::AccessIndex { base: value, index }),
+            None => value,
+        };
+        let xy = add(swizzle(position, [X, Y]));
+        let zw = add(swizzle(position, [Z, W]));
+        let w = add(Expression::AccessIndex {
+            base: position,
+            index: 3,
+        });
+        let scale = add(swizzle(area, [X, Y]));
+        let offset = add(swizzle(area, [Z, W]));
+        let scaled = add(Expression::Binary {
+            op: BinaryOperator::Multiply,
+            left: xy,
+            right: scale,
+        });
+        let shift = add(Expression::Binary {
+            op: BinaryOperator::Multiply,
+            left: offset,
+            right: w,
+        });
+        let moved = add(Expression::Binary {
+            op: BinaryOperator::Add,
+            left: scaled,
+            right: shift,
+        });
+        let position = add(Expression::Compose {
+            ty: self.vec4,
+            components: vec![moved, zw],
+        });
+
+        let Some((index, count)) = self.member else {
+            return position;
+        };
+        let components = (0..count)
+            .map(|i| {
+                if i == index {
+                    position
+                } else {
+                    add(Expression::AccessIndex { base: value, index: i })
+                }
+            })
+            .collect();
+        add(Expression::Compose {
+            ty: self.output,
+            components,
+        })
+    }
+}
+
+/// A mesh's grid on the GPU.
+struct Mesh {
+    vertices: wgpu::Buffer,
+    indices: wgpu::Buffer,
+    index_count: u32,
+    depth: wgpu::TextureView,
+}
+
+/// Per-close GPU resources, made on the first frame once the texture is known.
+struct Frame {
+    uniforms: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    target: wgpu::Texture,
+    mesh: Option<Mesh>,
+    readback: wgpu::Buffer,
+    /// Bytes per row of `readback`, padded to wgpu's copy alignment
+    row_bytes: u32,
+    started: Instant,
+}
+
+/// Draws an animation's shaders on the GPU.
+pub struct GpuRenderer {
+    gpu: &'static Gpu,
+    pipeline: Arc<wgpu::RenderPipeline>,
+    /// Everything but progress and time, which change every frame
+    uniforms: AnimationUniforms,
+    /// Grid size for a mesh animation
+    mesh: Option<(u32, u32)>,
+    /// The window on the surface
+    window: Rect,
+    /// What's drawn: the window, plus the monitor if the animation can
+    /// leave the window
+    area: Rect,
+    frame: Mutex<Option<Frame>>,
+}
+
+impl GpuRenderer {
+    /// Renderer for one close of `animation` at `placement`; fails if
+    /// there's no GPU or it can't compile the shaders.
+    pub fn new<A>(animation: &A, placement: &Placement, seed: u64) -> Result<Self>
+    where
+        A: Animation + ?Sized,
+    {
+        let gpu = Gpu::shared().context("No GPU to run shader animations on")?;
+        let mesh = animation.mesh().filter(|_| animation.vertex_shader().is_some());
+        let geometry = match (mesh.and(animation.vertex_shader()), animation.leaves_window()) {
+            (Some(vertex), _) => Geometry::Mesh(vertex),
+            (None, true) => Geometry::Cover,
+            (None, false) => Geometry::Window,
+        };
+        let pipeline = gpu.pipeline(animation.name(), animation.fragment_shader(), geometry)?;
+
+        let window = &placement.window;
+        let mut uniforms = AnimationUniforms::new(window.width as f32, window.height as f32, seed)
+            .with_exit(animation.exit(placement));
+        animation.update_uniforms(&mut uniforms, 0.0);
+
+        let window = placement.window_in_monitor();
+        let area = match geometry {
+            Geometry::Window => window,
+            Geometry::Cover | Geometry::Mesh(_) => {
+                let monitor = Rect::new(0, 0, placement.monitor.width, placement.monitor.height);
+                window.union(&monitor)
+            }
+        };
+        let limit = gpu.device.limits().max_texture_dimension_2d as i32;
+        if area.is_empty() || area.width > limit || area.height > limit {
+            anyhow::bail!("{}x{} is too large for the GPU", area.width, area.height);
+        }
+
+        Ok(Self {
+            gpu,
+            pipeline,
+            uniforms,
+            mesh,
+            window,
+            area,
+            frame: Mutex::new(None),
+        })
+    }
+
+    /// Scale and offset taking clip space over the window to clip space over
+    /// the area.
+    fn area_mapping(&self) -> [f32; 4] {
+        let (w, a) = (self.window, self.area);
+        let (aw, ah) = (a.width as f32, a.height as f32);
+        [
+            w.width as f32 / aw,
+            w.height as f32 / ah,
+            (2 * (w.x - a.x) + w.width) as f32 / aw - 1.0,
+            1.0 - (2 * (w.y - a.y) + w.height) as f32 / ah,
+        ]
+    }
+
+    fn create_frame(&self, texture: &Texture) -> Frame {
+        let device = &self.gpu.device;
+        let (width, height) = (texture.width() as u32, texture.height() as u32);
+        let window = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("window"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: texture.levels().count() as u32,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        for (level, (width, height, pixels)) in texture.levels().enumerate() {
+            self.gpu.queue.write_texture(
+                wgpu::ImageCopyTexture {
+                    texture: &window,
+                    mip_level: level as u32,
+                    origin: wgpu::Origin3d::ZERO,
+                    aspect: wgpu::TextureAspect::All,
+                },
+                pixels,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(width as u32 * 4),
+                    rows_per_image: None,
+                },
+                wgpu::Extent3d {
+                    width: width as u32,
+                    height: height as u32,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("uniforms"),
+            size: std::mem::size_of::<AnimationUniforms>() as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let area = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("area"),
+            contents: bytemuck::cast_slice(&self.area_mapping()),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("animation"),
+            layout: &self.gpu.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniforms.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(
+                        &window.create_view(&Default::default()),
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&self.gpu.sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: AREA_BINDING,
+                    resource: area.as_entire_binding(),
+                },
+            ],
+        });
+
+        let size = wgpu::Extent3d {
+            width: self.area.width as u32,
+            height: self.area.height as u32,
+            depth_or_array_layers: 1,
+        };
+        let target = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("frame"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: TARGET_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let mesh = self.mesh.map(|cells| {
+            let (vertices, indices) = grid(cells);
+            let depth = device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("depth"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: DEPTH_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                view_formats: &[],
+            });
+            Mesh {
+                vertices: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("grid"),
+                    contents: bytemuck::cast_slice(&vertices),
+                    usage: wgpu::BufferUsages::VERTEX,
+                }),
+                indices: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("grid"),
+                    contents: bytemuck::cast_slice(&indices),
+                    usage: wgpu::BufferUsages::INDEX,
+                }),
+                index_count: indices.len() as u32,
+                depth: depth.create_view(&Default::default()),
+            }
+        });
+        let row_bytes = (size.width * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: row_bytes as u64 * size.height as u64,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Frame {
+            uniforms,
+            bind_group,
+            target,
+            mesh,
+            readback,
+            row_bytes,
+            started: Instant::now(),
+        }
+    }
+
+    /// Draw the frame at `progress` and wait for it to be readable.
+    fn draw(&self, frame: &Frame, progress: f32) -> Result<()> {
+        let mut uniforms = self.uniforms;
+        uniforms.progress = progress;
+        uniforms.time = frame.started.elapsed().as_secs_f32();
+        self.gpu
+            .queue
+            .write_buffer(&frame.uniforms, 0, bytemuck::bytes_of(&uniforms));
+
+        let view = frame.target.create_view(&Default::default());
+        let mut encoder = self.gpu.device.create_command_encoder(&Default::default());
+        {
+            let depth = frame.mesh.as_ref().map(|mesh| wgpu::RenderPassDepthStencilAttachment {
+                view: &mesh.depth,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(1.0),
+                    store: wgpu::StoreOp::Discard,
+                }),
+                stencil_ops: None,
+            });
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("animation"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: depth,
+                ..Default::default()
+            });
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &frame.bind_group, &[]);
+            match &frame.mesh {
+                Some(mesh) => {
+                    pass.set_vertex_buffer(0, mesh.vertices.slice(..));
+                    pass.set_index_buffer(mesh.indices.slice(..), wgpu::IndexFormat::Uint32);
+                    pass.draw_indexed(0..mesh.index_count, 0, 0..1);
+                }
+                None => pass.draw(0..4, 0..1),
+            }
+        }
+        encoder.copy_texture_to_buffer(
+            frame.target.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &frame.readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(frame.row_bytes),
+                    rows_per_image: None,
+                },
+            },
+            frame.target.size(),
+        );
+        self.gpu.queue.submit([encoder.finish()]);
+
+        let (sender, receiver) = mpsc::channel();
+        frame
+            .readback
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                let _ = sender.send(result);
+            });
+        self.gpu.device.poll(wgpu::Maintain::Wait);
+        receiver
+            .recv()
+            .context("GPU readback was dropped")?
+            .context("Failed to read the frame back from the GPU")
+    }
+}
+
+impl CpuRenderer for GpuRenderer {
+    fn bounds(&self, _window: &Rect, _progress: f32) -> Rect {
+        self.area
+    }
+
+    fn render(&self, target: &mut Target, texture: &Texture, progress: f32) {
+        let bounds = target.bounds.intersect(&self.area);
+        if bounds.is_empty() {
+            return;
+        }
+        let mut frame = self.frame.lock().unwrap_or_else(|e| e.into_inner());
+        let frame = frame.get_or_insert_with(|| self.create_frame(texture));
+        if let Err(e) = self.draw(frame, progress) {
+            warn!("{:#}", e);
+            return;
+        }
+
+        {
+            let pixels = frame.readback.slice(..).get_mapped_range();
+            let row_bytes = target.width * 4;
+            for y in bounds.y..bounds.bottom() {
+                let src_row = (y - self.area.y) as usize * frame.row_bytes as usize;
+                let dst_row = y as usize * row_bytes;
+                for x in bounds.x..bounds.right() {
+                    let src = src_row + (x - self.area.x) as usize * 4;
+                    let dst = dst_row + x as usize * 4;
+                    let [r, g, b, a]: [u8; 4] = pixels[src..src + 4].try_into().unwrap();
+                    // Premultiplied, so no channel may exceed alpha
+                    target.canvas[dst..dst + 4].copy_from_slice(&[b.min(a), g.min(a), r.min(a), a]);
+                }
+            }
+        }
+        frame.readback.unmap();
+    }
+}
+
+/// Wait for a wgpu future; on native backends they're ready when returned
+/// or soon after, so polling in a loop is enough.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = TaskContext::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        std::thread::yield_now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::animation::WindowGeometry;
+    use crate::animations::{
+        DustAnimation, FadeAnimation, FoldAnimation, ShatterAnimation, SlideAnimation,
+    };
+    use crate::pixel::{Image, PixelFormat};
+
+    const MONITOR: Rect = Rect {
+        x: 0,
+        y: 0,
+        width: 320,
+        height: 240,
+    };
+
+    const SEED: u64 = 7;
+
+    const WINDOW: Rect = Rect {
+        x: 100,
+        y: 80,
+        width: 96,
+        height: 64,
+    };
+
+    fn placement() -> Placement {
+        Placement {
+            window: WindowGeometry {
+                x: WINDOW.x,
+                y: WINDOW.y,
+                width: WINDOW.width as u32,
+                height: WINDOW.height as u32,
+            },
+            monitor: MONITOR,
+        }
+    }
+
+    /// An opaque texture the size of `WINDOW`.
+    fn texture() -> Texture {
+        let (width, height) = (WINDOW.width as usize, WINDOW.height as usize);
+        let pixels = (0..width * height * 4)
+            .map(|i| if i % 4 == 3 { 255 } else { (i * 7 % 251) as u8 })
+            .collect();
+        Texture::new(Image::new(pixels, width, height, PixelFormat::Rgba8))
+    }
+
+    /// The monitor-sized canvas after drawing `animation` at `progress` on
+    /// the GPU, or `None` where there's no GPU to draw with.
+    fn render<A: Animation>(animation: &A, progress: f32) -> Option<Vec<u8>> {
+        Gpu::shared()?;
+        let renderer = GpuRenderer::new(animation, &placement(), SEED).unwrap();
+        Some(draw(&renderer, progress))
+    }
+
+    fn draw(renderer: &dyn CpuRenderer, progress: f32) -> Vec<u8> {
+        let mut canvas = vec![0u8; MONITOR.width as usize * MONITOR.height as usize * 4];
+        let mut target = Target {
+            canvas: &mut canvas,
+            width: MONITOR.width as usize,
+            window: WINDOW,
+            bounds: renderer.bounds(&WINDOW, progress).intersect(&MONITOR),
+        };
+        renderer.render(&mut target, &texture(), progress);
+        canvas
+    }
+
+    /// Whether anything is drawn outside the window.
+    fn drawn_outside(canvas: &[u8]) -> bool {
+        let around = [
+            Rect::new(0, 0, MONITOR.width, WINDOW.y),
+            Rect::new(0, WINDOW.bottom(), MONITOR.width, MONITOR.height - WINDOW.bottom()),
+            Rect::new(0, WINDOW.y, WINDOW.x, WINDOW.height),
+            Rect::new(WINDOW.right(), WINDOW.y, MONITOR.width - WINDOW.right(), WINDOW.height),
+        ];
+        around.into_iter().any(|rect| alphas(canvas, rect).any(|a| a > 0))
+    }
+
+    /// Alpha of every pixel in `rect`.
+    fn alphas(canvas: &[u8], rect: Rect) -> impl Iterator<Item = u8> + '_ {
+        (rect.y..rect.bottom()).flat_map(move |y| {
+            (rect.x..rect.right()).map(move |x| canvas[(y * MONITOR.width + x) as usize * 4 + 3])
+        })
+    }
+
+    #[test]
+    fn mesh_starts_as_the_window() {
+        let Some(canvas) = render(&FoldAnimation::new(), 0.0) else {
+            return;
+        };
+        assert!(alphas(&canvas, WINDOW).all(|a| a == 255));
+        let above = Rect::new(0, 0, MONITOR.width, WINDOW.y);
+        assert!(alphas(&canvas, above).all(|a| a == 0));
+    }
+
+    #[test]
+    fn mesh_folds_into_a_quarter() {
+        let Some(canvas) = render(&FoldAnimation::new(), 0.7) else {
+            return;
+        };
+        // Everything lies over the top left quarter, give or take perspective
+        let (w, h) = (WINDOW.width / 2, WINDOW.height / 2);
+        let quarter = Rect::new(WINDOW.x + 2, WINDOW.y + 2, w - 4, h - 4);
+        assert!(alphas(&canvas, quarter).all(|a| a == 255));
+        let right = Rect::new(WINDOW.x + w + 2, WINDOW.y, w - 2, WINDOW.height);
+        let bottom = Rect::new(WINDOW.x, WINDOW.y + h + 2, WINDOW.width, h - 2);
+        assert!(alphas(&canvas, right).chain(alphas(&canvas, bottom)).all(|a| a == 0));
+    }
+
+    #[test]
+    fn slide_matches_the_cpu() {
+        let animation = SlideAnimation::new();
+        let Some(gpu) = render(&animation, 0.5) else {
+            return;
+        };
+        let cpu = animation.cpu_renderer(&placement(), SEED).unwrap();
+        let cpu = draw(cpu.as_ref(), 0.5);
+
+        // Halfway up and out of the top edge: clear of where the window was
+        let exit = placement().nearest_exit();
+        assert_eq!(exit, glam::Vec2::new(0.0, -(WINDOW.bottom() as f32)));
+        let moved = Rect::new(WINDOW.x + 2, WINDOW.y / 2 - 20, WINDOW.width - 4, 10);
+        assert!(alphas(&gpu, moved).all(|a| a > 0));
+        let differing = alphas(&gpu, MONITOR)
+            .zip(alphas(&cpu, MONITOR))
+            .filter(|(g, c)| g.abs_diff(*c) > 8)
+            .count();
+        // Only the streak's edges may differ, a couple of rows at the top and
+        // bottom: the CPU averages fewer copies over a short streak
+        assert!(differing <= 4 * WINDOW.width as usize, "{differing} pixels differ");
+    }
+
+    #[test]
+    fn shatter_flies_past_the_window() {
+        let Some(canvas) = render(&ShatterAnimation::new(), 0.6) else {
+            return;
+        };
+        assert!(drawn_outside(&canvas));
+    }
+
+    #[test]
+    fn dust_drifts_past_the_window() {
+        let Some(start) = render(&DustAnimation::new(), 0.0) else {
+            return;
+        };
+        assert!(!drawn_outside(&start));
+        let late = render(&DustAnimation::new(), 0.7).unwrap();
+        assert!(drawn_outside(&late));
+    }
+
+    #[test]
+    fn pipelines_are_cached_by_source_and_bounded() {
+        let Some(gpu) = Gpu::shared() else {
+            return;
+        };
+        let fade = FadeAnimation::new();
+        // Each edit of a file is a new source, even where the old one lived
+        for edit in 0..MAX_PIPELINES + 4 {
+            let source = format!("{}// edit {}\n", fade.fragment_shader(), edit);
+            gpu.pipeline("fade", &source, Geometry::Window).unwrap();
+        }
+        assert!(gpu.lock_pipelines().len() <= MAX_PIPELINES);
+
+        let source = format!("{}// edit {}\n", fade.fragment_shader(), MAX_PIPELINES + 3);
+        let first = gpu.pipeline("fade", &source, Geometry::Window).unwrap();
+        // The same source at another address
+        let copy = source.clone();
+        let again = gpu.pipeline("fade", &copy, Geometry::Window).unwrap();
+        assert!(Arc::ptr_eq(&first, &again));
+        let cover = gpu.pipeline("fade", &source, Geometry::Cover).unwrap();
+        assert!(!Arc::ptr_eq(&first, &cover));
+    }
+}
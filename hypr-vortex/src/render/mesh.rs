@@ -0,0 +1,392 @@
+//! Mesh rendering: the window as a grid of textured triangles whose vertices
+//! an animation moves, for effects a per-pixel warp can't express (funnels,
+//! folds, anything where the inverse mapping isn't a simple formula).
+//!
+//! Interior edges are shared and drawn hard so the mesh has no seams; edges
+//! on the window's outline get a pixel of antialiasing.
+//!
+//! Vertices live in 3D, with z toward the viewer. Flat meshes stay at z = 0,
+//! where neither the optional perspective nor the lighting changes anything.
+//! Grid cells are drawn far to near by their middles, which stacks sheets
+//! folded over each other the right way round, and each triangle is lit by
+//! its own normal.
+
+use glam::{Vec2, Vec3, Vec4};
+use rayon::prelude::*;
+
+use super::{CpuRenderer, Target, Texture};
+use crate::damage::Rect;
+use crate::pixel::{blend_bgra, write_bgra};
+
+/// Triangles with less area than this (in square pixels) aren't drawn.
+const MIN_AREA: f32 = 1e-4;
+
+/// Direction the light comes from: above left, in front of the window.
+const LIGHT: Vec3 = Vec3::new(-0.36, -0.45, 0.82);
+
+/// Brightness of a face turned fully away from the light.
+const AMBIENT: f32 = 0.35;
+
+/// Vertices never get closer to the camera than this share of its distance.
+const NEAR: f32 = 0.05;
+
+/// Where an animation puts the mesh's vertices.
+pub trait Deform: Send + Sync {
+    /// Window-pixel position at `progress` of the vertex that starts at `uv`
+    /// (0..1 across the window); z points toward the viewer, and 0 is the
+    /// window's own plane.
+    fn vertex(&self, uv: Vec2, progress: f32) -> Vec3;
+
+    /// Opacity of the whole mesh at `progress`.
+    fn opacity(&self, _progress: f32) -> f32 {
+        1.0
+    }
+}
+
+/// A vertex after projection.
+#[derive(Debug, Clone, Copy)]
+struct Projected {
+    /// Where it lands on the surface
+    point: Vec2,
+    /// How much perspective magnifies it there, 1 without perspective
+    scale: f32,
+    /// Window-pixel position before projection
+    position: Vec3,
+}
+
+/// One triangle of the mesh, placed on the surface for a frame.
+struct Triangle {
+    /// Surface positions
+    points: [Vec2; 3],
+    /// Perspective magnification per point, for perspective-correct texturing
+    scales: [f32; 3],
+    uvs: [Vec2; 3],
+    /// Edge `i` runs from point `i` to point `i + 1`; outer ones are antialiased
+    outer: [bool; 3],
+    /// Twice the signed area, for barycentrics
+    area: f32,
+    lod: f32,
+    /// Multiplier on the color from lighting
+    light: f32,
+    /// Turned away from the viewer
+    back: bool,
+    /// Depth of its grid cell's middle toward the viewer, for drawing order
+    depth: f32,
+    bounds: Rect,
+}
+
+impl Triangle {
+    /// `eye` is the camera position in window pixels, `None` for a view
+    /// straight down -z.
+    fn new(
+        vertices: [Projected; 3],
+        uvs: [Vec2; 3],
+        outer: [bool; 3],
+        texels: Vec2,
+        eye: Option<Vec3>,
+        depth: f32,
+    ) -> Option<Self> {
+        let points = vertices.map(|v| v.point);
+        let area = (points[1] - points[0]).perp_dot(points[2] - points[0]);
+        if area.abs() < MIN_AREA {
+            return None;
+        }
+
+        // The grid winds so the normal of an untouched window points at the viewer
+        let [a, b, c] = vertices.map(|v| v.position);
+        let normal = (b - a).cross(c - a).normalize_or_zero();
+        let back = normal.dot(eye.map_or(Vec3::Z, |eye| eye - a)) < 0.0;
+        let facing = if back { -normal } else { normal };
+        // Normalized so a face lying flat in the window keeps its colors
+        let diffuse = facing.dot(LIGHT).max(0.0) / LIGHT.z;
+        let light = AMBIENT + (1.0 - AMBIENT) * diffuse;
+
+        // log2 of texels per pixel, from how much texture the triangle squeezes in
+        let uv_area = ((uvs[1] - uvs[0]) * texels).perp_dot((uvs[2] - uvs[0]) * texels);
+        let lod = 0.5 * (uv_area / area).abs().log2().max(0.0);
+
+        let min = points[0].min(points[1]).min(points[2]).floor() - Vec2::ONE;
+        let max = points[0].max(points[1]).max(points[2]).ceil() + Vec2::ONE;
+        let bounds = Rect::new(
+            min.x as i32,
+            min.y as i32,
+            (max.x - min.x) as i32,
+            (max.y - min.y) as i32,
+        );
+
+        Some(Self {
+            points,
+            scales: vertices.map(|v| v.scale),
+            uvs,
+            outer,
+            area,
+            lod,
+            light,
+            back,
+            depth,
+            bounds,
+        })
+    }
+
+    /// Texture coordinate and coverage at surface point `p`, if it's covered.
+    #[inline]
+    fn shade(&self, p: Vec2) -> Option<(Vec2, f32)> {
+        let mut weights = [0.0; 3];
+        let mut coverage: f32 = 1.0;
+        for i in 0..3 {
+            let a = self.points[i];
+            let b = self.points[(i + 1) % 3];
+            // Positive inside, whichever way the triangle winds
+            let edge = (b - a).perp_dot(p - a) * self.area.signum();
+            if self.outer[i] {
+                let distance = edge / (b - a).length();
+                coverage = coverage.min(distance + 0.5);
+            } else if edge < 0.0 {
+                return None;
+            }
+            // The weight of the vertex opposite this edge
+            weights[(i + 2) % 3] = edge / self.area.abs();
+        }
+        if coverage <= 0.0 {
+            return None;
+        }
+
+        // Perspective-correct: weigh each vertex by its magnification
+        let weights: [f32; 3] = std::array::from_fn(|i| weights[i] * self.scales[i]);
+        let total = weights[0] + weights[1] + weights[2];
+        let uv = (self.uvs[0] * weights[0] + self.uvs[1] * weights[1] + self.uvs[2] * weights[2])
+            / total;
+        Some((uv.clamp(Vec2::ZERO, Vec2::ONE), coverage.min(1.0)))
+    }
+}
+
+/// A `cols` x `rows` grid over the window, moved by `D` every frame.
+pub struct MeshRenderer<D> {
+    deform: D,
+    cols: usize,
+    rows: usize,
+    /// Camera distance in window sizes, `None` for no perspective
+    focal: Option<f32>,
+    /// Premultiplied color of faces turned away, `None` to show the window
+    back: Option<Vec4>,
+}
+
+impl<D: Deform> MeshRenderer<D> {
+    pub fn new(deform: D, (cols, rows): (u32, u32)) -> Self {
+        Self {
+            deform,
+            cols: cols.max(1) as usize,
+            rows: rows.max(1) as usize,
+            focal: None,
+            back: None,
+        }
+    }
+
+    /// View from `focal` window sizes in front of the window's middle, so
+    /// whatever leaves the window's plane grows or shrinks with distance.
+    pub fn with_perspective(mut self, focal: f32) -> Self {
+        self.focal = Some(focal);
+        self
+    }
+
+    /// Draw faces turned away from the viewer in premultiplied `color`,
+    /// cut to the window's shape, instead of showing the window through.
+    pub fn with_back(mut self, color: Vec4) -> Self {
+        self.back = Some(color);
+        self
+    }
+
+    /// Camera position in window pixels, if there's perspective.
+    fn eye(&self, window: &Rect) -> Option<Vec3> {
+        let size = Vec2::new(window.width as f32, window.height as f32);
+        self.focal
+            .map(|focal| (size * 0.5).extend(focal * size.max_element().max(1.0)))
+    }
+
+    /// Every grid vertex at `progress`, projected onto the surface, row by row.
+    fn vertices(&self, window: &Rect, progress: f32) -> Vec<Projected> {
+        let origin = Vec2::new(window.x as f32, window.y as f32);
+        let grid = Vec2::new(self.cols as f32, self.rows as f32);
+        let eye = self.eye(window);
+        (0..=self.rows)
+            .flat_map(|row| (0..=self.cols).map(move |col| (col, row)))
+            .map(|(col, row)| {
+                let uv = Vec2::new(col as f32, row as f32) / grid;
+                let position = self.deform.vertex(uv, progress);
+                let (point, scale) = match eye {
+                    Some(eye) => {
+                        let scale = eye.z / (eye.z - position.z).max(eye.z * NEAR);
+                        (eye.truncate() + (position.truncate() - eye.truncate()) * scale, scale)
+                    }
+                    None => (position.truncate(), 1.0),
+                };
+                Projected {
+                    point: origin + point,
+                    scale,
+                    position,
+                }
+            })
+            .collect()
+    }
+
+    /// Two triangles per grid cell, far to near.
+    fn triangles(&self, window: &Rect, texture: &Texture, progress: f32) -> Vec<Triangle> {
+        let vertices = self.vertices(window, progress);
+        let grid = Vec2::new(self.cols as f32, self.rows as f32);
+        let texels = Vec2::new(texture.width() as f32, texture.height() as f32);
+        let stride = self.cols + 1;
+        let eye = self.eye(window);
+
+        let mut triangles = Vec::with_capacity(self.cols * self.rows * 2);
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let corner = |dx: usize, dy: usize| {
+                    let uv = Vec2::new((col + dx) as f32, (row + dy) as f32) / grid;
+                    (vertices[(row + dy) * stride + col + dx], uv)
+                };
+                let (p00, uv00) = corner(0, 0);
+                let (p10, uv10) = corner(1, 0);
+                let (p01, uv01) = corner(0, 1);
+                let (p11, uv11) = corner(1, 1);
+                let top = row == 0;
+                let bottom = row + 1 == self.rows;
+                let left = col == 0;
+                let right = col + 1 == self.cols;
+                // The middle itself; crease vertices are shared with the cell it folds over
+                let middle = Vec2::new(col as f32 + 0.5, row as f32 + 0.5) / grid;
+                let depth = self.deform.vertex(middle, progress).z;
+
+                // Split along the 10-01 diagonal, which is never on the outline
+                triangles.extend(Triangle::new(
+                    [p00, p10, p01],
+                    [uv00, uv10, uv01],
+                    [top, false, left],
+                    texels,
+                    eye,
+                    depth,
+                ));
+                triangles.extend(Triangle::new(
+                    [p10, p11, p01],
+                    [uv10, uv11, uv01],
+                    [right, bottom, false],
+                    texels,
+                    eye,
+                    depth,
+                ));
+            }
+        }
+        // Stable, so a flat mesh keeps grid order
+        triangles.sort_by(|a, b| a.depth.total_cmp(&b.depth));
+        triangles
+    }
+}
+
+impl<D: Deform> CpuRenderer for MeshRenderer<D> {
+    fn bounds(&self, window: &Rect, progress: f32) -> Rect {
+        let (min, max) = self.vertices(window, progress).iter().fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), v| (min.min(v.point), max.max(v.point)),
+        );
+        // A pixel of slack for the antialiased outline
+        let min = min.floor() - Vec2::ONE;
+        let max = max.ceil() + Vec2::ONE;
+        Rect::new(
+            min.x as i32,
+            min.y as i32,
+            (max.x - min.x) as i32,
+            (max.y - min.y) as i32,
+        )
+    }
+
+    fn render(&self, target: &mut Target, texture: &Texture, progress: f32) {
+        let bounds = target.bounds;
+        let opacity = self.deform.opacity(progress);
+        if bounds.is_empty() || opacity <= 0.0 {
+            return;
+        }
+
+        let triangles = self.triangles(&target.window, texture, progress);
+        let row_bytes = target.width * 4;
+        let row_start = bounds.y as usize;
+        let row_end = bounds.bottom() as usize;
+
+        target.canvas[row_start * row_bytes..row_end * row_bytes]
+            .par_chunks_mut(row_bytes)
+            .enumerate()
+            .for_each(|(row_index, row)| {
+                let y = (row_start + row_index) as i32;
+                let covering = triangles
+                    .iter()
+                    .filter(|triangle| (triangle.bounds.y..triangle.bounds.bottom()).contains(&y));
+                for triangle in covering {
+                    let span = triangle.bounds.intersect(&bounds);
+                    for x in span.x..span.right() {
+                        let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5);
+                        let Some((uv, coverage)) = triangle.shade(p) else {
+                            continue;
+                        };
+                        let mut color = Vec4::from(texture.sample(uv.x, uv.y, triangle.lod));
+                        if let (true, Some(back)) = (triangle.back, self.back) {
+                            color = back * color.w;
+                        }
+                        let color = (color.truncate() * triangle.light).extend(color.w);
+                        let px = &mut row[x as usize * 4..x as usize * 4 + 4];
+                        let alpha = coverage * opacity;
+                        if alpha >= 1.0 {
+                            write_bgra(px, color.into());
+                        } else {
+                            blend_bgra(px, color.into(), alpha);
+                        }
+                    }
+                }
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixel::{Image, PixelFormat};
+
+    const WIDTH: usize = 64;
+    const HEIGHT: usize = 48;
+
+    /// Leaves every vertex where the grid puts it.
+    struct Flat(Vec2);
+
+    impl Deform for Flat {
+        fn vertex(&self, uv: Vec2, _progress: f32) -> Vec3 {
+            (uv * self.0).extend(0.0)
+        }
+    }
+
+    #[test]
+    fn flat_mesh_reproduces_texture() {
+        let pixels: Vec<u8> = (0..WIDTH * HEIGHT * 4).map(|i| (i * 7 % 253) as u8).collect();
+        let texture = Texture::new(Image::new(pixels, WIDTH, HEIGHT, PixelFormat::Rgba8));
+        let size = Vec2::new(WIDTH as f32, HEIGHT as f32);
+
+        for perspective in [false, true] {
+            let mut renderer = MeshRenderer::new(Flat(size), (4, 3));
+            if perspective {
+                renderer = renderer.with_perspective(2.0);
+            }
+            let window = Rect::new(0, 0, WIDTH as i32, HEIGHT as i32);
+            let mut canvas = vec![0u8; WIDTH * HEIGHT * 4];
+            let mut target = Target {
+                canvas: &mut canvas,
+                width: WIDTH,
+                window,
+                bounds: renderer.bounds(&window, 0.5).intersect(&window),
+            };
+            renderer.render(&mut target, &texture, 0.5);
+
+            let (_, _, texels) = texture.levels().next().unwrap();
+            for (i, (px, texel)) in canvas.chunks_exact(4).zip(texels.chunks_exact(4)).enumerate() {
+                let rgba = [px[2], px[1], px[0], px[3]];
+                let close = rgba.iter().zip(texel).all(|(a, b)| a.abs_diff(*b) <= 1);
+                assert!(close, "pixel {i}: {rgba:?} vs {texel:?} (perspective {perspective})");
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Renderers for the SHM overlay: CPU ones, plus a GPU one for animations
+//! that only exist as shaders, which reads its frames back.
+
+mod burn;
+mod crt;
+mod dust;
+mod fade;
+mod fastmath;
+mod fold;
+mod fragments;
+mod genie;
+mod glitch;
+mod gpu;
+mod mesh;
+mod particles;
+mod portal;
+mod sampling;
+mod shatter;
+mod shrink;
+mod simd;
+mod slide;
+mod strands;
+mod vortex;
+
+pub use burn::{BurnParams, BurnRenderer};
+pub use crt::{CrtParams, CrtRenderer};
+pub use dust::{DustParams, DustRenderer};
+pub use fade::FadeRenderer;
+pub use fold::FoldDeform;
+pub use fragments::{voronoi_cells, Fragment, FragmentSim, Pose};
+pub use genie::GenieDeform;
+pub use glitch::{GlitchParams, GlitchRenderer};
+pub use gpu::GpuRenderer;
+pub use mesh::{Deform, MeshRenderer};
+pub use particles::{Flow, Particle, ParticleSystem};
+pub use portal::{PortalParams, PortalRenderer};
+pub use sampling::Texture;
+pub use shatter::{ShatterParams, ShatterRenderer};
+pub use shrink::ShrinkRenderer;
+pub use simd::SimdLevel;
+pub use slide::SlideRenderer;
+pub use strands::{StrandFrame, StrandTable};
+pub use vortex::{VortexParams, VortexRenderer};
+
+use crate::damage::Rect;
+
+/// Surface canvas to draw into (ARGB8888, i.e. BGRA bytes).
+pub struct Target<'a> {
+    pub canvas: &'a mut [u8],
+    /// Surface width in pixels; rows are `width * 4` bytes
+    pub width: usize,
+    /// Where the window sits on the surface
+    pub window: Rect,
+    /// Only pixels inside this rectangle are written
+    pub bounds: Rect,
+}
+
+/// Draws one close of an animation into the CPU-side canvas.
+pub trait CpuRenderer: Send {
+    /// Surface-space bounding box of everything `render` can draw at `progress`.
+    fn bounds(&self, window: &Rect, progress: f32) -> Rect;
+
+    /// Draw the frame at `progress`; only pixels inside `target.bounds` are written.
+    fn render(&self, target: &mut Target, texture: &Texture, progress: f32);
+}
@@ -0,0 +1,272 @@
+//! Particles: the window crumbling into specks that ride a turbulent flow.
+//!
+//! `ParticleSystem::emit` cuts the window into `grain`-pixel clusters, one
+//! particle each, and every particle takes its color from the screenshot
+//! under its cluster. Until its release time a cluster is still part of the
+//! window and is drawn at full resolution; afterwards it drifts and swirls
+//! through a curl-noise field, shrinking and fading until its life is over.
+//!
+//! Positions are integrated from the release in a few fixed steps on every
+//! frame, so like the fragments they only depend on the time asked for.
+
+use glam::Vec2;
+use rayon::prelude::*;
+
+use super::{Target, Texture};
+use crate::damage::Rect;
+use crate::noise;
+use crate::pixel::{blend_bgra, write_bgra};
+
+/// Integration steps from release to the current age.
+const STEPS: usize = 3;
+
+/// Rows per band when splatting particles in parallel.
+const BAND_ROWS: usize = 32;
+
+/// One cluster of window pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct Particle {
+    /// Top-left corner of the cluster in window pixels
+    pub home: Vec2,
+    /// Seconds until it breaks off the window
+    pub release: f32,
+    /// Seconds from release until it's gone
+    pub life: f32,
+    /// Drift velocity in pixels per second
+    pub velocity: Vec2,
+}
+
+/// The field every particle moves through.
+#[derive(Debug, Clone, Copy)]
+pub struct Flow {
+    /// Constant acceleration in pixels per second squared, +y is down
+    pub lift: Vec2,
+    /// Size of a turbulent eddy in pixels
+    pub scale: f32,
+    /// Top turbulence speed in pixels per second
+    pub strength: f32,
+    /// Picks the noise pattern
+    pub seed: u32,
+}
+
+impl Flow {
+    /// Turbulence velocity at `p` (pixels) `t` seconds in.
+    ///
+    /// The curl of a scalar noise potential has no divergence, so the specks
+    /// swirl around each other instead of bunching up or thinning out.
+    pub fn curl(&self, p: Vec2, t: f32) -> Vec2 {
+        // The eddies themselves drift slowly so the flow isn't frozen
+        let q = p / self.scale + Vec2::new(0.0, 0.4 * t);
+        let grad = noise::value_grad(q, self.seed);
+        (Vec2::new(grad.y, -grad.x) * self.strength).clamp_length_max(self.strength)
+    }
+}
+
+/// A released particle placed on the surface for one frame.
+struct Speck {
+    min: Vec2,
+    max: Vec2,
+    color: [f32; 4],
+    alpha: f32,
+}
+
+/// Every cluster of one window and the flow they ride.
+pub struct ParticleSystem {
+    /// One per cluster, row by row
+    particles: Vec<Particle>,
+    /// Clusters per row
+    cols: usize,
+    grain: usize,
+    flow: Flow,
+    /// Fastest launch speed of any particle, for bounds
+    max_speed: f32,
+}
+
+impl ParticleSystem {
+    /// One particle per `grain` x `grain` cluster of a `width` x `height`
+    /// window; `spawn` gets each cluster's home and decides how it flies.
+    pub fn emit(
+        width: u32,
+        height: u32,
+        grain: u32,
+        flow: Flow,
+        mut spawn: impl FnMut(Vec2) -> Particle,
+    ) -> Self {
+        let grain = grain.max(1) as usize;
+        let cols = (width as usize).div_ceil(grain);
+        let rows = (height as usize).div_ceil(grain);
+
+        let particles: Vec<Particle> = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (col, row)))
+            .map(|(col, row)| spawn(Vec2::new((col * grain) as f32, (row * grain) as f32)))
+            .collect();
+        let max_speed = particles
+            .iter()
+            .map(|particle| particle.velocity.length())
+            .fold(0.0, f32::max);
+
+        Self {
+            particles,
+            cols,
+            grain,
+            flow,
+            max_speed,
+        }
+    }
+
+    pub fn particles(&self) -> &[Particle] {
+        &self.particles
+    }
+
+    /// Window-pixel top-left corner and age (0 at release, 1 at the end of
+    /// its life) of a particle in flight `t` seconds in.
+    pub fn position(&self, particle: &Particle, t: f32) -> Option<(Vec2, f32)> {
+        let age = t - particle.release;
+        if age < 0.0 || age >= particle.life {
+            return None;
+        }
+
+        // Midpoint steps: exact for the constant lift, close enough for the eddies
+        let dt = age / STEPS as f32;
+        let mut p = particle.home;
+        for step in 0..STEPS {
+            let s = (step as f32 + 0.5) * dt;
+            let center = p + self.grain as f32 * 0.5;
+            let velocity = particle.velocity
+                + self.flow.lift * s
+                + self.flow.curl(center, particle.release + s);
+            p += velocity * dt;
+        }
+        Some((p, age / particle.life))
+    }
+
+    /// Surface-space region `render` can draw `t` seconds in: the window,
+    /// grown by the farthest any particle can have travelled.
+    pub fn bounds(&self, window: &Rect, t: f32) -> Rect {
+        let reach = (self.max_speed + self.flow.strength) * t
+            + 0.5 * self.flow.lift.length() * t * t
+            + self.grain as f32;
+        let reach = reach.ceil() as i32 + 1;
+        Rect::new(
+            window.x - reach,
+            window.y - reach,
+            window.width + 2 * reach,
+            window.height + 2 * reach,
+        )
+    }
+
+    /// Draw the still-attached part of the window and every particle in
+    /// flight `t` seconds in. Only pixels inside `target.bounds` are written.
+    pub fn render(&self, target: &mut Target, texture: &Texture, t: f32) {
+        let bounds = target.bounds;
+        if bounds.is_empty() {
+            return;
+        }
+        let window = target.window;
+        let size = Vec2::new(window.width.max(1) as f32, window.height.max(1) as f32);
+        let texel_scale = texture.width() as f32 / size.x;
+        let row_bytes = target.width * 4;
+
+        // Clusters that haven't broken off: the screenshot at full resolution
+        let intact = window.intersect(&bounds);
+        if !intact.is_empty() {
+            let lod = texel_scale.log2().max(0.0);
+            target.canvas[intact.y as usize * row_bytes..intact.bottom() as usize * row_bytes]
+                .par_chunks_mut(row_bytes)
+                .enumerate()
+                .for_each(|(row_index, row)| {
+                    let wy = (intact.y - window.y) as usize + row_index;
+                    let cluster_row = wy / self.grain * self.cols;
+                    for x in intact.x..intact.right() {
+                        let wx = (x - window.x) as usize;
+                        let attached = self
+                            .particles
+                            .get(cluster_row + wx / self.grain)
+                            .is_some_and(|particle| particle.release > t);
+                        if !attached {
+                            continue;
+                        }
+                        let uv = (Vec2::new(wx as f32, wy as f32) + 0.5) / size;
+                        let px = &mut row[x as usize * 4..x as usize * 4 + 4];
+                        write_bgra(px, texture.sample(uv.x, uv.y, lod));
+                    }
+                });
+        }
+
+        // Particles in flight, each the average color of its cluster
+        let origin = Vec2::new(window.x as f32, window.y as f32);
+        let grain = self.grain as f32;
+        let cluster_lod = (grain * texel_scale).log2().max(0.0);
+        let specks: Vec<Speck> = self
+            .particles
+            .par_iter()
+            .filter_map(|particle| {
+                let (p, age) = self.position(particle, t)?;
+                let uv = (particle.home + grain * 0.5) / size;
+                let color = texture.sample(uv.x, uv.y, cluster_lod);
+                if color[3] < 1.0 / 255.0 {
+                    return None;
+                }
+                // Shrink about the center while fading out
+                let side = grain * (1.0 - 0.5 * age);
+                let min = origin + p + (grain - side) * 0.5;
+                Some(Speck {
+                    min,
+                    max: min + side,
+                    color,
+                    alpha: 1.0 - age,
+                })
+            })
+            .collect();
+
+        // Bin specks by band so bands can be drawn in parallel
+        let band_count = (bounds.height as usize).div_ceil(BAND_ROWS);
+        let mut bands: Vec<Vec<usize>> = vec![Vec::new(); band_count];
+        for (index, speck) in specks.iter().enumerate() {
+            let top = (speck.min.y.floor() as i32).max(bounds.y) - bounds.y;
+            let bottom = (speck.max.y.ceil() as i32).min(bounds.bottom()) - bounds.y;
+            if top >= bottom {
+                continue;
+            }
+            let spanned = top as usize / BAND_ROWS..=(bottom - 1) as usize / BAND_ROWS;
+            for band in &mut bands[spanned] {
+                band.push(index);
+            }
+        }
+
+        target.canvas[bounds.y as usize * row_bytes..bounds.bottom() as usize * row_bytes]
+            .par_chunks_mut(row_bytes * BAND_ROWS)
+            .zip(bands.par_iter())
+            .enumerate()
+            .for_each(|(band, (canvas, indices))| {
+                let band_y = bounds.y + (band * BAND_ROWS) as i32;
+                let rows = (canvas.len() / row_bytes) as i32;
+                let clip = Rect::new(bounds.x, band_y, bounds.width, rows).intersect(&bounds);
+                for &index in indices {
+                    splat(canvas, row_bytes, band_y, &clip, &specks[index]);
+                }
+            });
+    }
+}
+
+/// Draw one speck into a band of rows starting at surface row `band_y`, with
+/// coverage-based antialiasing so slow specks glide instead of snapping.
+fn splat(canvas: &mut [u8], row_bytes: usize, band_y: i32, clip: &Rect, speck: &Speck) {
+    let overlap = |pixel: i32, min: f32, max: f32| {
+        (max.min(pixel as f32 + 1.0) - min.max(pixel as f32)).clamp(0.0, 1.0)
+    };
+    let top = (speck.min.y.floor() as i32).max(clip.y);
+    let bottom = (speck.max.y.ceil() as i32).min(clip.bottom());
+    let left = (speck.min.x.floor() as i32).max(clip.x);
+    let right = (speck.max.x.ceil() as i32).min(clip.right());
+
+    for y in top..bottom {
+        let coverage_y = overlap(y, speck.min.y, speck.max.y);
+        let row = &mut canvas[(y - band_y) as usize * row_bytes..][..row_bytes];
+        for x in left..right {
+            let coverage = coverage_y * overlap(x, speck.min.x, speck.max.x);
+            let px = &mut row[x as usize * 4..x as usize * 4 + 4];
+            blend_bgra(px, speck.color, speck.alpha * coverage);
+        }
+    }
+}
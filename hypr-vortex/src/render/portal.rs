@@ -0,0 +1,240 @@
+//! Portal: a swirling disc of colored noise opens behind the window, the
+//! window tilts and shrinks into it, and the portal snaps shut.
+//!
+//! A port of the hypr-portal-effect plugin's shader, itself adapted from
+//! Burn My Windows by Simon Schneegans (GPL-3.0-or-later). The portal is
+//! soft noise, so it is evaluated on a coarse grid and upsampled; only the
+//! window itself is sampled per pixel.
+
+use glam::{Vec2, Vec3, Vec4};
+use rayon::prelude::*;
+
+use super::{CpuRenderer, Target, Texture};
+use crate::damage::Rect;
+use crate::easing::lerp;
+use crate::noise;
+use crate::pixel::write_bgra;
+
+const PORTAL_WOBBLE_TIME: f32 = 0.8;
+const PORTAL_WOBBLE_STRENGTH: f32 = 1.2;
+const GLOW_EDGE_WIDTH: f32 = 5.0;
+const WINDOW_SCALE: f32 = 0.3;
+const WINDOW_SQUISH: f32 = 1.0;
+const WINDOW_TILT: f32 = -1.0;
+const PORTAL_OPEN_TIME: f32 = 0.4;
+const PORTAL_CLOSE_TIME: f32 = 0.4;
+const WINDOW_OPEN_TIME: f32 = 0.35;
+
+/// Portal samples per frame, whatever the window size.
+const PORTAL_SAMPLES: f32 = 30_000.0;
+
+/// The plugin's settings.
+#[derive(Debug, Clone, Copy)]
+pub struct PortalParams {
+    /// Straight-alpha RGB
+    pub color: Vec3,
+    pub rotation_speed: f32,
+    pub whirling: f32,
+    /// Picks the noise pattern
+    pub seed: u32,
+    /// Animation length in seconds; the swirl turns with real time
+    pub duration: f32,
+}
+
+/// Per-close portal state.
+pub struct PortalRenderer {
+    params: PortalParams,
+}
+
+impl PortalRenderer {
+    pub fn new(params: PortalParams) -> Self {
+        Self { params }
+    }
+
+    /// Portal color at window `uv`, straight alpha, for a `size` window.
+    fn portal(&self, uv: Vec2, size: Vec2, progress: f32) -> Vec4 {
+        let p = &self.params;
+        let mut coords = (uv - 0.5) * 2.0 * 1.5;
+        let scale = portal_scale(progress);
+        coords /= (scale * 0.5 + 0.5).max(0.01);
+
+        let wobble = portal_wobble(coords, progress);
+        let detail_scale = 10000.0 / (size.x + size.y) * 0.5;
+        let whirled = |coords: Vec2, speed: f32, warp: f32| {
+            let rotation = p.rotation_speed * progress * p.duration * speed;
+            let warping = p.whirling * (6.0 + 1.5 * progress) * warp;
+            whirl(coords, warping, rotation)
+        };
+        let displace = |coords: Vec2, scale: f32| {
+            Vec2::new(
+                simplex01(coords * scale, p.seed) - 0.5,
+                simplex01(coords * scale + Vec2::new(7.89, 123.0), p.seed) - 0.5,
+            )
+        };
+
+        // Background gradient
+        let mut layer_coords = whirled(coords - wobble, 0.25, 1.0);
+        let offset = displace(layer_coords, 2.1);
+        layer_coords += offset * 0.1;
+        let dist = layer_coords.length();
+        let alpha = if dist > 1.0 { 0.0 } else { 1.0 };
+        let mut color = darken(p.color, 0.8)
+            .lerp(darken(p.color, 0.2), dist.powi(5))
+            .extend(alpha);
+        let rand = offset.dot(offset);
+
+        // First whirled band
+        let noise = simplex01(layer_coords / detail_scale + Vec2::new(12.3, 56.4), p.seed);
+        let band = if noise > 0.6 { alpha } else { 0.0 };
+        color = alpha_over(color, darken(p.color, 0.3).extend(band));
+
+        // Second, faster band
+        let mut layer_coords = whirled(coords - wobble * 1.5, 0.75, 0.5);
+        layer_coords += displace(layer_coords, 12.2) * 0.1;
+        let noise = simplex01(layer_coords / detail_scale * 1.3, p.seed);
+        let band = if noise > 0.6 { alpha } else { 0.0 };
+        color = alpha_over(color, p.color.extend(band));
+        color = color.clamp(Vec4::ZERO, Vec4::ONE);
+
+        // Glowing rim
+        let edge =
+            (1.0 + 4.0 * rand) * GLOW_EDGE_WIDTH * detail_scale - 150.0 * (dist - 1.0).abs();
+        color = alpha_over(color, p.color.extend(edge.clamp(0.0, 1.0)));
+
+        // Sparkles
+        let layer_coords = whirled(coords - wobble * 1.8, 1.25, 0.0);
+        let noise = simplex01(layer_coords / detail_scale * 3.0, p.seed);
+        let sparkle = (noise * rand + 0.9).powf(50.0).clamp(0.0, 1.0);
+        color += (lighten(p.color, 0.8) * sparkle).extend(0.0);
+
+        color.w *= scale.clamp(0.0, 1.0).powi(2);
+        color.clamp(Vec4::ZERO, Vec4::ONE)
+    }
+}
+
+impl CpuRenderer for PortalRenderer {
+    fn bounds(&self, window: &Rect, _progress: f32) -> Rect {
+        *window
+    }
+
+    fn render(&self, target: &mut Target, texture: &Texture, progress: f32) {
+        let bounds = target.bounds.intersect(&target.window);
+        if bounds.is_empty() {
+            return;
+        }
+        let window = target.window;
+        let size = Vec2::new(window.width.max(1) as f32, window.height.max(1) as f32);
+
+        // The portal on a coarse grid, premultiplied, one sample past each edge
+        let step = (size.x * size.y / PORTAL_SAMPLES).sqrt().max(1.0);
+        let grid_w = (size.x / step).ceil() as usize + 1;
+        let grid_h = (size.y / step).ceil() as usize + 1;
+        let grid: Vec<Vec4> = (0..grid_w * grid_h)
+            .into_par_iter()
+            .map(|i| {
+                let cell = Vec2::new((i % grid_w) as f32, (i / grid_w) as f32);
+                let color = self.portal(cell * step / size, size, progress);
+                (color.truncate() * color.w).extend(color.w)
+            })
+            .collect();
+        let portal_at = |p: Vec2| {
+            let g = (p / step).min(Vec2::new((grid_w - 1) as f32, (grid_h - 1) as f32) - 0.001);
+            let (x, y) = (g.x as usize, g.y as usize);
+            let f = g - g.floor();
+            let at = |dx: usize, dy: usize| grid[(y + dy) * grid_w + x + dx];
+            at(0, 0).lerp(at(1, 0), f.x).lerp(at(0, 1).lerp(at(1, 1), f.x), f.y)
+        };
+
+        // The window falling in
+        let fall = ease_in_back((progress / WINDOW_OPEN_TIME).clamp(0.0, 1.0), 1.2);
+        let window_alpha = ((1.0 - fall) * 3.0).clamp(0.0, 1.0);
+        let lod = (texture.width() as f32 / size.x / lerp(1.0, WINDOW_SCALE, fall))
+            .log2()
+            .max(0.0);
+
+        let row_bytes = target.width * 4;
+        target.canvas[bounds.y as usize * row_bytes..bounds.bottom() as usize * row_bytes]
+            .par_chunks_mut(row_bytes)
+            .enumerate()
+            .for_each(|(row_index, row)| {
+                let y = bounds.y + row_index as i32;
+                for x in bounds.x..bounds.right() {
+                    let p = Vec2::new((x - window.x) as f32 + 0.5, (y - window.y) as f32 + 0.5);
+                    let portal = portal_at(p);
+
+                    let mut coords = p / size * 2.0 - 1.0;
+                    coords /= lerp(1.0, WINDOW_SCALE, fall);
+                    coords.y /= lerp(1.0, 1.0 - 0.2 * WINDOW_SQUISH, fall);
+                    coords.x /= lerp(1.0, 1.0 - 0.1 * WINDOW_TILT * coords.y, fall);
+                    let uv = coords * 0.5 + 0.5;
+                    let inside = (0.0..=1.0).contains(&uv.x) && (0.0..=1.0).contains(&uv.y);
+
+                    let color = if inside && window_alpha > 0.0 {
+                        let win = Vec4::from(texture.sample(uv.x, uv.y, lod)) * window_alpha;
+                        win + portal * (1.0 - win.w)
+                    } else {
+                        portal
+                    };
+                    let px = &mut row[x as usize * 4..x as usize * 4 + 4];
+                    write_bgra(px, color.into());
+                }
+            });
+    }
+}
+
+fn ease_in_back(x: f32, s: f32) -> f32 {
+    (s + 1.0) * x * x * x - s * x * x
+}
+
+fn ease_out_back(x: f32, s: f32) -> f32 {
+    1.0 + (s + 1.0) * (x - 1.0).powi(3) + s * (x - 1.0).powi(2)
+}
+
+fn darken(color: Vec3, amount: f32) -> Vec3 {
+    color * (1.0 - amount)
+}
+
+fn lighten(color: Vec3, amount: f32) -> Vec3 {
+    color + (Vec3::ONE - color) * amount
+}
+
+/// Straight-alpha "over".
+fn alpha_over(background: Vec4, foreground: Vec4) -> Vec4 {
+    let alpha = foreground.w + background.w * (1.0 - foreground.w);
+    let color = (foreground.truncate() * foreground.w
+        + background.truncate() * background.w * (1.0 - foreground.w))
+        / alpha.max(0.001);
+    color.extend(alpha)
+}
+
+/// Rotate `coords` about the center, more strongly near it.
+fn whirl(coords: Vec2, warping: f32, rotation: f32) -> Vec2 {
+    let dist = coords.length();
+    let angle = coords.y.atan2(coords.x) + rotation + warping * (-dist).exp();
+    Vec2::from_angle(angle) * dist
+}
+
+/// The portal pops open with an overshoot and closes the same way.
+fn portal_scale(progress: f32) -> f32 {
+    if progress < PORTAL_OPEN_TIME {
+        ease_out_back(progress / PORTAL_OPEN_TIME, 1.5)
+    } else if progress > 1.0 - PORTAL_CLOSE_TIME {
+        ease_out_back(1.0 - (progress - 1.0 + PORTAL_CLOSE_TIME) / PORTAL_CLOSE_TIME, 1.5)
+    } else {
+        1.0
+    }
+}
+
+/// The portal bulges as the window goes in.
+fn portal_wobble(coords: Vec2, progress: f32) -> Vec2 {
+    let t = progress / WINDOW_OPEN_TIME;
+    let t = ease_in_back((1.0 - ((t - 1.0) / PORTAL_WOBBLE_TIME).abs()).clamp(0.0, 1.0), 1.7);
+    let dist = coords.length();
+    coords * (1.0 - dist) * (-dist).exp() * t * PORTAL_WOBBLE_STRENGTH
+}
+
+/// Simplex noise remapped to about 0..1, the range Burn My Windows' effect
+/// expects; the plugin's copy used -1..1 and washed the portal out.
+fn simplex01(p: Vec2, seed: u32) -> f32 {
+    noise::simplex(p, seed) * 0.5 + 0.5
+}
@@ -0,0 +1,149 @@
+//! Filtered texture sampling for the CPU renderers.
+//!
+//! Mirrors what the GPU path gets from `textureSample`: bilinear filtering
+//! within a mip level and linear blending between levels, so content that the
+//! effect squeezes into thin strands averages out instead of shimmering.
+//!
+//! Texels are premultiplied, so filtering next to transparent pixels never
+//! drags in their (meaningless) color.
+
+use rayon::prelude::*;
+
+use crate::pixel::{Image, PixelFormat};
+
+/// One level of the mip chain (premultiplied RGBA8, tightly packed).
+struct MipLevel {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+/// Window screenshot with a precomputed mip chain.
+pub struct Texture {
+    levels: Vec<MipLevel>,
+}
+
+impl Texture {
+    /// Build a texture from a screenshot, generating every mip level down to 1x1.
+    ///
+    /// Short pixel buffers are padded with transparent black.
+    pub fn new(image: Image) -> Self {
+        let image = image.into_format(PixelFormat::Rgba8Premultiplied);
+        let width = image.width.max(1);
+        let height = image.height.max(1);
+        let mut pixels = image.pixels;
+        pixels.resize(width * height * PixelFormat::BYTES_PER_PIXEL, 0);
+
+        let mut levels = vec![MipLevel {
+            width,
+            height,
+            pixels,
+        }];
+        while let Some(next) = levels.last().and_then(downsample) {
+            levels.push(next);
+        }
+
+        Self { levels }
+    }
+
+    /// Full-resolution width in texels.
+    pub fn width(&self) -> usize {
+        self.levels[0].width
+    }
+
+    /// Full-resolution height in texels.
+    pub fn height(&self) -> usize {
+        self.levels[0].height
+    }
+
+    /// Every mip level as (width, height, premultiplied RGBA8 texels), full
+    /// resolution first, for uploading to the GPU.
+    pub fn levels(&self) -> impl Iterator<Item = (usize, usize, &[u8])> {
+        self.levels
+            .iter()
+            .map(|level| (level.width, level.height, level.pixels.as_slice()))
+    }
+
+    /// Trilinear sample at normalized `(u, v)`, premultiplied RGBA in `0.0..=1.0`.
+    ///
+    /// `lod` 0 is full resolution and each step halves it; pass the log2 of
+    /// how many texels one output pixel covers.
+    #[inline]
+    pub fn sample(&self, u: f32, v: f32, lod: f32) -> [f32; 4] {
+        let max_level = (self.levels.len() - 1) as f32;
+        let lod = lod.clamp(0.0, max_level);
+        let base = lod.floor();
+        let blend = lod - base;
+
+        let near = self.sample_level(u, v, base as usize);
+        if blend <= f32::EPSILON {
+            return near;
+        }
+        let far = self.sample_level(u, v, base as usize + 1);
+        std::array::from_fn(|c| near[c] + (far[c] - near[c]) * blend)
+    }
+
+    /// Bilinear sample from a single mip level, clamping to the edges.
+    #[inline]
+    pub fn sample_level(&self, u: f32, v: f32, level: usize) -> [f32; 4] {
+        let level = &self.levels[level.min(self.levels.len() - 1)];
+
+        // Texel centers sit at half-integer coordinates
+        let x = (u * level.width as f32 - 0.5).clamp(0.0, (level.width - 1) as f32);
+        let y = (v * level.height as f32 - 0.5).clamp(0.0, (level.height - 1) as f32);
+        let x0 = x as usize;
+        let y0 = y as usize;
+        let x1 = (x0 + 1).min(level.width - 1);
+        let y1 = (y0 + 1).min(level.height - 1);
+        let fx = x - x0 as f32;
+        let fy = y - y0 as f32;
+
+        let texel = |tx: usize, ty: usize| {
+            let i = (ty * level.width + tx) * 4;
+            &level.pixels[i..i + 4]
+        };
+        let (t00, t10, t01, t11) = (texel(x0, y0), texel(x1, y0), texel(x0, y1), texel(x1, y1));
+
+        std::array::from_fn(|c| {
+            let top = t00[c] as f32 + (t10[c] as f32 - t00[c] as f32) * fx;
+            let bottom = t01[c] as f32 + (t11[c] as f32 - t01[c] as f32) * fx;
+            (top + (bottom - top) * fy) / 255.0
+        })
+    }
+}
+
+/// Half-resolution 2x2 box-filtered copy, or `None` once the level is 1x1.
+fn downsample(level: &MipLevel) -> Option<MipLevel> {
+    if level.width == 1 && level.height == 1 {
+        return None;
+    }
+    let width = (level.width / 2).max(1);
+    let height = (level.height / 2).max(1);
+    let mut pixels = vec![0u8; width * height * 4];
+
+    pixels
+        .par_chunks_mut(width * 4)
+        .enumerate()
+        .for_each(|(y, row)| {
+            // Sizes round down like GPU mips; `min` covers a dimension already at 1
+            let sy0 = (y * 2).min(level.height - 1);
+            let sy1 = (y * 2 + 1).min(level.height - 1);
+            for x in 0..width {
+                let sx0 = (x * 2).min(level.width - 1);
+                let sx1 = (x * 2 + 1).min(level.width - 1);
+                for c in 0..4 {
+                    let sum: u32 = [(sx0, sy0), (sx1, sy0), (sx0, sy1), (sx1, sy1)]
+                        .iter()
+                        .map(|&(sx, sy)| level.pixels[(sy * level.width + sx) * 4 + c] as u32)
+                        .sum();
+                    row[x * 4 + c] = ((sum + 2) / 4) as u8;
+                }
+            }
+        });
+
+    Some(MipLevel {
+        width,
+        height,
+        pixels,
+    })
+}
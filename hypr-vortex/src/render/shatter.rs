@@ -0,0 +1,101 @@
+//! Shatter: the window cracks into Voronoi shards that burst away from an
+//! impact point, tumble and fall.
+
+use glam::Vec2;
+
+use super::fragments::{voronoi_cells, Fragment, FragmentSim};
+use super::{CpuRenderer, Target, Texture};
+use crate::damage::Rect;
+use crate::rng::Rng;
+
+/// How far sites stray from their grid cell centers, in cells.
+const JITTER: f32 = 0.4;
+
+/// Seconds for the crack to travel from the impact point to the far corner.
+const CRACK_DELAY: f32 = 0.12;
+
+/// Shards fade out over the last part of the animation.
+const FADE_START: f32 = 0.7;
+
+/// Tunables, in units of the window diagonal so every window size looks alike.
+#[derive(Debug, Clone, Copy)]
+pub struct ShatterParams {
+    /// Roughly how many shards to cut
+    pub shards: usize,
+    /// Outward launch speed, diagonals per second
+    pub burst: f32,
+    /// Downward acceleration, diagonals per second squared
+    pub gravity: f32,
+    /// Peak tumbling speed, radians per second
+    pub spin: f32,
+    /// Animation length in seconds
+    pub duration: f32,
+}
+
+/// Per-close shatter state: the shards and how each one flies.
+pub struct ShatterRenderer {
+    sim: FragmentSim,
+    duration: f32,
+}
+
+impl ShatterRenderer {
+    /// Cut a `width` x `height` window into shards; `seed` picks the pattern.
+    pub fn new(width: u32, height: u32, params: ShatterParams, seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let size = Vec2::new(width.max(1) as f32, height.max(1) as f32);
+        let diagonal = size.length();
+
+        // A jittered grid keeps shards similar in size, unlike uniform sites
+        let cols = ((params.shards as f32 * size.x / size.y).sqrt().round() as usize).max(1);
+        let rows = params.shards.div_ceil(cols).max(1);
+        let cell = size / Vec2::new(cols as f32, rows as f32);
+        let sites: Vec<Vec2> = (0..rows)
+            .flat_map(|row| (0..cols).map(move |col| (col, row)))
+            .map(|(col, row)| {
+                let jitter = Vec2::new(rng.range(-JITTER, JITTER), rng.range(-JITTER, JITTER));
+                (Vec2::new(col as f32, row as f32) + 0.5 + jitter) * cell
+            })
+            .collect();
+
+        let impact = size * Vec2::new(rng.range(0.35, 0.65), rng.range(0.35, 0.65));
+        let reach = (size - impact).max(impact).length();
+
+        let fragments = voronoi_cells(size.x, size.y, &sites)
+            .into_iter()
+            .filter_map(Fragment::new)
+            .map(|fragment| {
+                let away = fragment.centroid() - impact;
+                let closeness = 1.0 - away.length() / reach;
+                let direction = away.try_normalize().unwrap_or(Vec2::NEG_Y);
+
+                // Shards near the impact fly hardest; all get a little lift
+                let speed = params.burst * diagonal * (0.4 + 0.8 * closeness) * rng.range(0.7, 1.3);
+                let lift = Vec2::new(0.0, -0.3 * params.burst * diagonal * rng.next_f32());
+                let spin = params.spin * rng.range(-1.0, 1.0);
+                let delay = CRACK_DELAY * (1.0 - closeness);
+                fragment.with_motion(direction * speed + lift, spin, delay)
+            })
+            .collect();
+
+        Self {
+            sim: FragmentSim::new(fragments, Vec2::new(0.0, params.gravity * diagonal)),
+            duration: params.duration,
+        }
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.sim.fragments().len()
+    }
+}
+
+impl CpuRenderer for ShatterRenderer {
+    fn bounds(&self, window: &Rect, progress: f32) -> Rect {
+        self.sim.bounds(window, progress * self.duration)
+    }
+
+    fn render(&self, target: &mut Target, texture: &Texture, progress: f32) {
+        let fade = ((progress - FADE_START) / (1.0 - FADE_START)).clamp(0.0, 1.0);
+        let opacity = 1.0 - fade * fade * (3.0 - 2.0 * fade);
+        self.sim.render(target, texture, progress * self.duration, opacity);
+    }
+}
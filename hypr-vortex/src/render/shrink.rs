@@ -0,0 +1,71 @@
+//! Shrink: the window scales down into its middle, fading out over the last
+//! stretch.
+
+use glam::Vec2;
+use rayon::prelude::*;
+
+use super::{CpuRenderer, Target, Texture};
+use crate::damage::Rect;
+use crate::easing::smoothstep;
+use crate::pixel::write_bgra;
+
+/// The fade starts at this progress.
+const FADE_START: f32 = 0.7;
+
+/// Smallest scale sampled, so the last frames don't divide by zero.
+const MIN_SCALE: f32 = 0.001;
+
+/// Per-close shrink state; there's nothing to vary.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ShrinkRenderer;
+
+impl ShrinkRenderer {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl CpuRenderer for ShrinkRenderer {
+    fn bounds(&self, window: &Rect, progress: f32) -> Rect {
+        let scale = (1.0 - progress).clamp(0.0, 1.0);
+        // One pixel of slack for rounding
+        let half_w = (window.width as f32 * scale / 2.0).ceil() as i32 + 1;
+        let half_h = (window.height as f32 * scale / 2.0).ceil() as i32 + 1;
+        let center_x = window.x + window.width / 2;
+        let center_y = window.y + window.height / 2;
+        Rect::new(center_x - half_w, center_y - half_h, half_w * 2, half_h * 2).intersect(window)
+    }
+
+    fn render(&self, target: &mut Target, texture: &Texture, progress: f32) {
+        let window = target.window;
+        let bounds = target.bounds.intersect(&self.bounds(&window, progress));
+        if bounds.is_empty() {
+            return;
+        }
+        let size = Vec2::new(window.width as f32, window.height as f32);
+        let scale = (1.0 - progress).max(MIN_SCALE);
+        let lod = (texture.width() as f32 / (size.x * scale)).log2().max(0.0);
+        let fade = (progress - FADE_START) / (1.0 - FADE_START);
+        let opacity = 1.0 - smoothstep(fade);
+
+        let row_bytes = target.width * 4;
+        target.canvas[bounds.y as usize * row_bytes..bounds.bottom() as usize * row_bytes]
+            .par_chunks_mut(row_bytes)
+            .enumerate()
+            .for_each(|(row_index, row)| {
+                let y = bounds.y + row_index as i32;
+                // Expand from the middle: the inverse of the shrink
+                let v = 0.5 + (((y - window.y) as f32 + 0.5) / size.y - 0.5) / scale;
+                for x in bounds.x..bounds.right() {
+                    let u = 0.5 + (((x - window.x) as f32 + 0.5) / size.x - 0.5) / scale;
+                    let px = &mut row[x as usize * 4..x as usize * 4 + 4];
+                    if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+                        px.fill(0);
+                        continue;
+                    }
+                    // Premultiplied: fading scales color along with alpha
+                    write_bgra(px, texture.sample(u, v, lod).map(|c| c * opacity));
+                }
+            });
+    }
+}
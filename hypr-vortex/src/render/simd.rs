@@ -0,0 +1,49 @@
+//! Runtime selection of the pixel loop implementation.
+
+use tracing::debug;
+
+/// Which pixel loop the CPU renderers run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SimdLevel {
+    /// One pixel at a time with std math
+    Scalar,
+    /// 8 pixels at a time with the baseline vector ISA (SSE2 / NEON)
+    Simd128,
+    /// 8 pixels at a time compiled for AVX2 + FMA
+    Avx2,
+}
+
+impl SimdLevel {
+    /// Pick the fastest level this CPU supports.
+    ///
+    /// `VORTEX_SIMD=off` forces the scalar path for debugging.
+    pub fn detect() -> Self {
+        let level = Self::detect_cpu();
+        if std::env::var("VORTEX_SIMD").is_ok_and(|v| v == "off" || v == "0") {
+            debug!("SIMD disabled via VORTEX_SIMD (CPU supports {:?})", level);
+            return SimdLevel::Scalar;
+        }
+        level
+    }
+
+    fn detect_cpu() -> Self {
+        #[cfg(target_arch = "x86_64")]
+        {
+            if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("fma") {
+                return SimdLevel::Avx2;
+            }
+            SimdLevel::Simd128
+        }
+
+        #[cfg(target_arch = "aarch64")]
+        {
+            SimdLevel::Simd128
+        }
+
+        // No vector unit `wide` can use; its fallback would be slower than scalar
+        #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+        {
+            SimdLevel::Scalar
+        }
+    }
+}
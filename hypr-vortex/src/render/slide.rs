@@ -0,0 +1,93 @@
+//! Slide: the window accelerates off the screen along a fixed offset,
+//! smeared along its path like a long exposure.
+//!
+//! The blur averages copies of the window spread back along the last stretch
+//! of travel, with more copies the longer the streak gets.
+
+use glam::{Vec2, Vec4};
+use rayon::prelude::*;
+
+use super::{CpuRenderer, Target, Texture};
+use crate::damage::Rect;
+use crate::pixel::write_bgra;
+
+/// Streak length at full blur, as a fraction of the offset travelled so far.
+const STREAK: f32 = 0.1;
+
+/// Most copies averaged into the streak.
+const MAX_SAMPLES: usize = 16;
+
+/// Pixels of streak per copy.
+const PX_PER_SAMPLE: f32 = 4.0;
+
+/// Per-close slide state.
+pub struct SlideRenderer {
+    /// Full offset in pixels at the end of the animation
+    exit: Vec2,
+    /// Motion blur strength, 0..1
+    blur: f32,
+}
+
+impl SlideRenderer {
+    pub fn new(exit: Vec2, blur: f32) -> Self {
+        Self { exit, blur }
+    }
+
+    /// Window offset and streak length at `progress`, in pixels.
+    fn motion(&self, progress: f32) -> (Vec2, Vec2) {
+        let offset = self.exit * progress;
+        (offset, offset * self.blur * STREAK * progress.sqrt().max(0.0))
+    }
+}
+
+impl CpuRenderer for SlideRenderer {
+    fn bounds(&self, window: &Rect, progress: f32) -> Rect {
+        let (offset, streak) = self.motion(progress);
+        let moved = |d: Vec2| {
+            Rect::new(
+                window.x + d.x.floor() as i32,
+                window.y + d.y.floor() as i32,
+                window.width + 1,
+                window.height + 1,
+            )
+        };
+        moved(offset).union(&moved(offset - streak))
+    }
+
+    fn render(&self, target: &mut Target, texture: &Texture, progress: f32) {
+        let bounds = target.bounds.intersect(&self.bounds(&target.window, progress));
+        if bounds.is_empty() {
+            return;
+        }
+        let window = target.window;
+        let size = Vec2::new(window.width.max(1) as f32, window.height.max(1) as f32);
+        let lod = (texture.width() as f32 / size.x).log2().max(0.0);
+
+        let (offset, streak) = self.motion(progress);
+        let samples = ((streak.length() / PX_PER_SAMPLE).ceil() as usize).clamp(1, MAX_SAMPLES);
+        let weight = 1.0 / samples as f32;
+        let origin = Vec2::new(window.x as f32, window.y as f32) + offset;
+
+        let row_bytes = target.width * 4;
+        target.canvas[bounds.y as usize * row_bytes..bounds.bottom() as usize * row_bytes]
+            .par_chunks_mut(row_bytes)
+            .enumerate()
+            .for_each(|(row_index, row)| {
+                let y = bounds.y + row_index as i32;
+                for x in bounds.x..bounds.right() {
+                    let p = Vec2::new(x as f32 + 0.5, y as f32 + 0.5) - origin;
+                    let mut color = Vec4::ZERO;
+                    for i in 0..samples {
+                        // Copies trail behind the window, back toward where it was
+                        let t = (i as f32 + 0.5) * weight;
+                        let uv = (p + streak * t) / size;
+                        if (0.0..1.0).contains(&uv.x) && (0.0..1.0).contains(&uv.y) {
+                            color += Vec4::from(texture.sample(uv.x, uv.y, lod));
+                        }
+                    }
+                    let px = &mut row[x as usize * 4..x as usize * 4 + 4];
+                    write_bgra(px, (color * weight).into());
+                }
+            });
+    }
+}
@@ -0,0 +1,233 @@
+//! Accretion-disk strands for the vortex.
+//!
+//! Strand parameters are generated once per close from a seed. Each frame a
+//! small lookup grid is built over (radial ring, angle bucket) listing the
+//! strands that can touch that cell, so the per-pixel loop only tests one or
+//! two candidates instead of all 32.
+
+use std::f32::consts::TAU;
+
+use crate::rng::Rng;
+
+/// Number of light strands around the singularity.
+pub const NUM_STRANDS: usize = 32;
+
+/// Radial extent of the disk beyond the singularity (normalized units).
+pub const DISK_WIDTH: f32 = 0.06;
+
+/// Radial rings across the disk in the lookup grid.
+const RINGS: usize = 32;
+
+/// Angle buckets around the circle in the lookup grid.
+const BUCKETS: usize = 256;
+
+/// Slack added to strand coverage so float rounding never drops a candidate.
+const COVERAGE_EPSILON: f32 = 1e-4;
+
+/// One wispy light strand spiraling into the singularity.
+#[derive(Debug, Clone, Copy)]
+pub struct Strand {
+    /// Angle at which the strand leaves the singularity (before spiral/rotation)
+    pub base_angle: f32,
+    /// Peak brightness, 0.3 to 1.0
+    pub brightness: f32,
+    /// How far it extends past the singularity
+    pub length: f32,
+    /// Angular half-width of the strand core
+    pub width: f32,
+    /// Radians of twist per unit distance, 12 to 20
+    pub spiral_tight: f32,
+}
+
+/// Per-close strand parameters.
+pub struct StrandTable {
+    strands: [Strand; NUM_STRANDS],
+}
+
+impl StrandTable {
+    /// Generate strand parameters for one close animation.
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let strands = std::array::from_fn(|i| {
+            let rand1 = rng.next_f32();
+            let rand2 = rng.next_f32();
+            let rand3 = rng.next_f32();
+            Strand {
+                base_angle: (i as f32 / NUM_STRANDS as f32) * TAU,
+                brightness: 0.3 + rand1 * 0.7,
+                length: 0.02 + rand2 * 0.04,
+                width: 0.008 + rand3 * 0.015,
+                // Brighter strands wind tighter
+                spiral_tight: 12.0 + rand1 * 8.0,
+            }
+        });
+        Self { strands }
+    }
+
+    /// Build the candidate lookup for one frame.
+    pub fn frame(&self, singularity_radius: f32, rotation: f32) -> StrandFrame<'_> {
+        let mut offsets = vec![0u32; RINGS * BUCKETS + 1];
+
+        // Counting pass, then fill pass (compressed sparse rows)
+        self.for_each_cell(singularity_radius, rotation, |cell, _| offsets[cell + 1] += 1);
+        for i in 1..offsets.len() {
+            offsets[i] += offsets[i - 1];
+        }
+        let mut cursor = offsets.clone();
+        let mut candidates = vec![0u8; offsets[RINGS * BUCKETS] as usize];
+        self.for_each_cell(singularity_radius, rotation, |cell, strand| {
+            candidates[cursor[cell] as usize] = strand as u8;
+            cursor[cell] += 1;
+        });
+
+        StrandFrame {
+            strands: &self.strands,
+            singularity_radius,
+            rotation,
+            offsets,
+            candidates,
+        }
+    }
+
+    /// Visit every (grid cell, strand) pair where the strand may be visible.
+    fn for_each_cell(&self, singularity_radius: f32, rotation: f32, mut visit: impl FnMut(usize, usize)) {
+        let ring_width = DISK_WIDTH / RINGS as f32;
+        let bucket_width = TAU / BUCKETS as f32;
+
+        for (index, strand) in self.strands.iter().enumerate() {
+            for ring in 0..RINGS {
+                let inner = ring as f32 * ring_width;
+                if inner >= strand.length {
+                    break;
+                }
+                let outer = ((ring + 1) as f32 * ring_width).min(strand.length);
+
+                // Angle swept by the strand center across this ring, widened by its core
+                let start = strand.base_angle + strand.spiral_tight * (singularity_radius + inner) + rotation;
+                let end = strand.base_angle + strand.spiral_tight * (singularity_radius + outer) + rotation;
+                let lo = start - strand.width - COVERAGE_EPSILON;
+                let hi = end + strand.width + COVERAGE_EPSILON;
+
+                let first = (lo / bucket_width).floor() as i64;
+                let last = (hi / bucket_width).floor() as i64;
+                let span = ((last - first + 1) as usize).min(BUCKETS);
+                for step in 0..span as i64 {
+                    let bucket = (first + step).rem_euclid(BUCKETS as i64) as usize;
+                    visit(ring * BUCKETS + bucket, index);
+                }
+            }
+        }
+    }
+}
+
+/// Strand lookup for a single frame.
+pub struct StrandFrame<'a> {
+    strands: &'a [Strand; NUM_STRANDS],
+    singularity_radius: f32,
+    rotation: f32,
+    offsets: Vec<u32>,
+    candidates: Vec<u8>,
+}
+
+impl StrandFrame<'_> {
+    /// Combined light of all strands at a point, `angle` in radians from `atan2`.
+    pub fn intensity(&self, dist: f32, angle: f32) -> f32 {
+        let rel = dist - self.singularity_radius;
+        if rel <= 0.0 || rel >= DISK_WIDTH {
+            return 0.0;
+        }
+
+        let ring = ((rel / DISK_WIDTH * RINGS as f32) as usize).min(RINGS - 1);
+        let bucket = ((angle.rem_euclid(TAU) / TAU * BUCKETS as f32) as usize).min(BUCKETS - 1);
+        let cell = ring * BUCKETS + bucket;
+        let range = self.offsets[cell] as usize..self.offsets[cell + 1] as usize;
+
+        self.candidates[range]
+            .iter()
+            .map(|&i| strand_intensity(&self.strands[i as usize], rel, dist, angle, self.rotation))
+            .sum()
+    }
+}
+
+/// Light contributed by one strand at `dist` (`rel` past the singularity).
+#[inline]
+fn strand_intensity(strand: &Strand, rel: f32, dist: f32, angle: f32, rotation: f32) -> f32 {
+    if rel >= strand.length {
+        return 0.0;
+    }
+
+    let expected_angle = strand.base_angle + strand.spiral_tight * dist + rotation;
+    let mut angle_diff = (angle - expected_angle).rem_euclid(TAU);
+    if angle_diff > TAU / 2.0 {
+        angle_diff = TAU - angle_diff;
+    }
+    if angle_diff >= strand.width {
+        return 0.0;
+    }
+
+    let core = 1.0 - angle_diff / strand.width;
+    // Fade at the outer tip
+    let tip_fade = 1.0 - (rel / strand.length).powi(2);
+    core * core * tip_fade * strand.brightness
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Strands lighting the point, by checking every one of them.
+    fn brute_force(table: &StrandTable, frame: &StrandFrame, dist: f32, angle: f32) -> Vec<usize> {
+        let rel = dist - frame.singularity_radius;
+        (0..NUM_STRANDS)
+            .filter(|&i| {
+                let strand = &table.strands[i];
+                strand_intensity(strand, rel, dist, angle, frame.rotation) > 0.0
+            })
+            .collect()
+    }
+
+    /// Strands lighting the point among the grid's candidates for its cell.
+    fn from_grid(frame: &StrandFrame, dist: f32, angle: f32) -> Vec<usize> {
+        let rel = dist - frame.singularity_radius;
+        let ring = ((rel / DISK_WIDTH * RINGS as f32) as usize).min(RINGS - 1);
+        let bucket = ((angle.rem_euclid(TAU) / TAU * BUCKETS as f32) as usize).min(BUCKETS - 1);
+        let cell = ring * BUCKETS + bucket;
+        let range = frame.offsets[cell] as usize..frame.offsets[cell + 1] as usize;
+        let mut lit: Vec<usize> = frame.candidates[range]
+            .iter()
+            .map(|&i| i as usize)
+            .filter(|&i| {
+                let strand = &frame.strands[i];
+                strand_intensity(strand, rel, dist, angle, frame.rotation) > 0.0
+            })
+            .collect();
+        lit.sort_unstable();
+        lit
+    }
+
+    #[test]
+    fn grid_finds_the_same_strands_as_a_full_scan() {
+        let mut rng = Rng::new(29);
+        let mut lit_points = 0;
+        for seed in 0..4 {
+            let table = StrandTable::new(seed);
+            // Rotations well past a full turn and either way round
+            for (radius, rotation) in [(0.0, 0.0), (0.1, 2.5), (0.35, -7.0), (0.02, 40.0)] {
+                let frame = table.frame(radius, rotation);
+                for _ in 0..5000 {
+                    let dist = radius + rng.range(0.0, DISK_WIDTH);
+                    let angle = rng.range(-std::f32::consts::PI, std::f32::consts::PI);
+                    let expected = brute_force(&table, &frame, dist, angle);
+                    assert_eq!(
+                        from_grid(&frame, dist, angle),
+                        expected,
+                        "seed {seed}, frame ({radius}, {rotation}), point ({dist}, {angle})"
+                    );
+                    lit_points += !expected.is_empty() as usize;
+                }
+            }
+        }
+        // Enough points landed on strands for the comparison to mean something
+        assert!(lit_points > 1000, "only {lit_points} lit points");
+    }
+}
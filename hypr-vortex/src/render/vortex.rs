@@ -0,0 +1,456 @@
+//! CPU vortex renderer: spaghettification into a black hole.
+
+use std::f32::consts::TAU;
+use std::ops::Range;
+
+use rayon::prelude::*;
+use wide::f32x8;
+
+use super::fastmath;
+use super::simd::SimdLevel;
+use super::strands::{StrandFrame, StrandTable, DISK_WIDTH};
+use super::{CpuRenderer, Target, Texture};
+use crate::damage::Rect;
+use crate::easing::Easing;
+use crate::pixel::write_bgra;
+
+/// Normalized coordinates of the vortex center.
+const CENTER: f32 = 0.5;
+
+/// How pull and spin build up over the (already eased) progress.
+const ACCELERATION: Easing = Easing::In(4);
+
+/// Extra radians the spiral unwinds per unit of `accel * spiral_tightness`
+/// on top of the spin, stretching content along it.
+const TANGENTIAL_STRETCH: f32 = 0.4;
+
+/// Tunable settings.
+#[derive(Debug, Clone, Copy)]
+pub struct VortexParams {
+    /// Turns the spiral and the disk make by the end
+    pub spin_speed: f32,
+    /// How hard content is pulled in: the spiral samples from up to
+    /// `1 + pull_strength * spiral_tightness` times farther out
+    pub pull_strength: f32,
+}
+
+impl Default for VortexParams {
+    /// The vortex animation's defaults.
+    fn default() -> Self {
+        Self {
+            spin_speed: 3.0,
+            pull_strength: 2.0,
+        }
+    }
+}
+
+/// Per-close vortex renderer state.
+pub struct VortexRenderer {
+    params: VortexParams,
+    strands: StrandTable,
+    simd: SimdLevel,
+}
+
+impl VortexRenderer {
+    /// Create a renderer whose accretion-disk strands are derived from `seed`.
+    pub fn new(params: VortexParams, seed: u64) -> Self {
+        Self {
+            params,
+            strands: StrandTable::new(seed),
+            simd: SimdLevel::detect(),
+        }
+    }
+
+    /// Force a specific pixel loop (benchmarks compare them).
+    pub fn with_simd(mut self, simd: SimdLevel) -> Self {
+        self.simd = simd;
+        self
+    }
+
+    /// Surface-space bounding box of everything `render` can draw at `progress`.
+    ///
+    /// Content at distance `d` from the center samples from `d * radial_compression`,
+    /// which grows with `d`, so the visible disk ends where that reaches the farthest
+    /// window corner. The singularity and accretion disk are always inside.
+    pub fn bounds(&self, window: &Rect, progress: f32) -> Rect {
+        let accel = ACCELERATION.apply(progress);
+        let singularity_radius = 0.03 + progress * 0.02;
+        let corner = std::f32::consts::FRAC_1_SQRT_2;
+
+        // Solve d + pull * accel * d / (d + 0.05) = corner for d
+        let b = 0.05 + self.params.pull_strength * accel - corner;
+        let c = 0.05 * corner;
+        let content_radius = (-b + (b * b + 4.0 * c).sqrt()) / 2.0;
+        let radius = content_radius.max(singularity_radius + DISK_WIDTH);
+
+        // One pixel of slack for truncation when mapping back to pixels
+        let half_w = (radius * window.width as f32).ceil() as i32 + 1;
+        let half_h = (radius * window.height as f32).ceil() as i32 + 1;
+        let center_x = window.x + window.width / 2;
+        let center_y = window.y + window.height / 2;
+
+        Rect::new(center_x - half_w, center_y - half_h, half_w * 2, half_h * 2).intersect(window)
+    }
+
+    /// CPU rendering for vortex effect using rayon for parallelization.
+    /// Spaghettification: content stretches into thin strands that spiral into the black hole.
+    ///
+    /// Only pixels inside `target.bounds` are written.
+    pub fn render(&self, target: &mut Target, texture: &Texture, progress: f32) {
+        let bounds = target.bounds;
+        if bounds.is_empty() {
+            return;
+        }
+
+        // The singularity - black void at center
+        let singularity_radius = 0.03 + progress * 0.02;
+
+        // Quartic acceleration - slow start, fast end
+        let accel = ACCELERATION.apply(progress);
+
+        // Accretion disk - many thin wispy light strands, looked up per frame
+        let rotation = accel * TAU * self.params.spin_speed;
+        let frame = VortexFrame {
+            texture,
+            window_width: target.window.width as f32,
+            texel_scale: texture.width() as f32 / target.window.width.max(1) as f32,
+            disk: self.strands.frame(singularity_radius, rotation),
+            singularity_radius,
+            disk_outer: singularity_radius + DISK_WIDTH,
+            accel,
+            spin: self.params.spin_speed * TAU,
+            pull: self.params.pull_strength,
+        };
+
+        // Window coordinates are signed: the window may hang off the surface
+        let window_x = target.window.x;
+        let window_y = target.window.y;
+        let row_bytes = target.width * 4;
+        let columns = bounds.x..bounds.right();
+
+        // Process rows in parallel
+        target.canvas[bounds.y as usize * row_bytes..bounds.bottom() as usize * row_bytes]
+            .par_chunks_mut(row_bytes)
+            .enumerate()
+            .for_each(|(row_index, row)| {
+                let y = bounds.y + row_index as i32 - window_y;
+                let v = y as f32 / target.window.height as f32;
+                let columns = columns.clone();
+
+                match self.simd {
+                    SimdLevel::Scalar => frame.row_scalar(row, v, columns, window_x),
+                    SimdLevel::Simd128 => frame.row_simd(row, v, columns, window_x),
+                    #[cfg(target_arch = "x86_64")]
+                    // SAFETY: `SimdLevel::Avx2` is only selected after detecting AVX2 and FMA
+                    SimdLevel::Avx2 => unsafe { frame.row_avx2(row, v, columns, window_x) },
+                    #[cfg(not(target_arch = "x86_64"))]
+                    SimdLevel::Avx2 => frame.row_simd(row, v, columns, window_x),
+                }
+            });
+    }
+}
+
+/// Per-frame values shared by every pixel.
+struct VortexFrame<'a> {
+    texture: &'a Texture,
+    /// Window width on the surface (the texture may be captured at a higher scale)
+    window_width: f32,
+    /// Texels per window pixel
+    texel_scale: f32,
+    disk: StrandFrame<'a>,
+    singularity_radius: f32,
+    disk_outer: f32,
+    accel: f32,
+    /// Radians the spiral turns per unit of `accel * spiral_tightness`
+    spin: f32,
+    /// Radial compression per unit of `accel * spiral_tightness`
+    pull: f32,
+}
+
+impl VortexFrame<'_> {
+    /// One row, one pixel at a time with std math. `columns` are surface x
+    /// coordinates, `window_x` is where the window's left edge is.
+    fn row_scalar(&self, row: &mut [u8], v: f32, columns: Range<i32>, window_x: i32) {
+        let dy = v - CENTER;
+        for surf_x in columns {
+            let u = (surf_x - window_x) as f32 / self.window_width;
+            let dx = u - CENTER;
+
+            let dist = (dx * dx + dy * dy).sqrt();
+            let angle = dy.atan2(dx);
+
+            // Unwind the spiral: go backwards along the spiral path
+            let (sample_angle, sample_dist) = self.spiral(dist, angle);
+            let sample_u = CENTER + sample_angle.cos() * sample_dist;
+            let sample_v = CENTER + sample_angle.sin() * sample_dist;
+            let footprint = self.footprint(dist, sample_dist);
+
+            let px = &mut row[surf_x as usize * 4..surf_x as usize * 4 + 4];
+            self.shade(px, dist, angle, sample_u, sample_v, footprint);
+        }
+    }
+
+    /// One row, 8 pixels at a time: the polar transform and spiral sample
+    /// position are vectorized, shading stays per pixel.
+    #[inline(always)]
+    fn row_simd(&self, row: &mut [u8], v: f32, columns: Range<i32>, window_x: i32) {
+        let dy = f32x8::splat(v - CENTER);
+        let lanes = f32x8::from([0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+        let win_w = f32x8::splat(self.window_width);
+        let center = f32x8::splat(CENTER);
+        let accel = f32x8::splat(self.accel);
+        let spin = f32x8::splat(self.spin);
+        let pull = f32x8::splat(self.pull);
+        let twist = f32x8::splat(self.spin + TANGENTIAL_STRETCH);
+
+        let mut surf_x = columns.start;
+        while surf_x + 8 <= columns.end {
+            let u = (lanes + f32x8::splat((surf_x - window_x) as f32)) / win_w;
+            let dx = u - center;
+
+            let dist = (dx * dx + dy * dy).sqrt();
+            let angle = fastmath::atan2(dy, dx);
+
+            // Same spiral as `spiral`, 8 lanes wide
+            let spiral_tightness = f32x8::ONE / (dist + f32x8::splat(0.05));
+            let total_rotation = accel * spiral_tightness * spin;
+            let tangential_stretch = accel * spiral_tightness * f32x8::splat(TANGENTIAL_STRETCH);
+            let radial_compression = f32x8::ONE + accel * spiral_tightness * pull;
+            let sample_angle = angle - total_rotation - tangential_stretch;
+            let sample_dist = dist * radial_compression;
+
+            // Same footprint as `footprint`
+            let k = accel * spiral_tightness * spiral_tightness;
+            let radial_u = f32x8::ONE + pull * f32x8::splat(0.05) * k;
+            let radial_v = sample_dist * twist * k;
+            let radial = (radial_u * radial_u + radial_v * radial_v).sqrt();
+            let tangential = sample_dist / dist.max(f32x8::splat(f32::MIN_POSITIVE));
+            let footprint = radial.max(tangential).to_array();
+
+            let (sin, cos) = fastmath::sin_cos(sample_angle);
+            let sample_u = (center + cos * sample_dist).to_array();
+            let sample_v = (center + sin * sample_dist).to_array();
+            let dist = dist.to_array();
+            let angle = angle.to_array();
+
+            let pixels = &mut row[surf_x as usize * 4..(surf_x as usize + 8) * 4];
+            for (lane, px) in pixels.chunks_exact_mut(4).enumerate() {
+                let (u, v) = (sample_u[lane], sample_v[lane]);
+                self.shade(px, dist[lane], angle[lane], u, v, footprint[lane]);
+            }
+            surf_x += 8;
+        }
+
+        // Leftover pixels at the end of the row
+        self.row_scalar(row, v, surf_x..columns.end, window_x);
+    }
+
+    /// `row_simd` compiled with AVX2 and FMA enabled.
+    #[cfg(target_arch = "x86_64")]
+    #[target_feature(enable = "avx2,fma")]
+    unsafe fn row_avx2(&self, row: &mut [u8], v: f32, columns: Range<i32>, window_x: i32) {
+        self.row_simd(row, v, columns, window_x)
+    }
+
+    /// Polar position (angle, distance) that the spiral pulls this pixel from.
+    ///
+    /// The key: stretch ALONG the spiral (tangentially), compress ACROSS it (radially).
+    /// This creates thin strands that wind around the center.
+    #[inline(always)]
+    fn spiral(&self, dist: f32, angle: f32) -> (f32, f32) {
+        // How much to wind around - more rotations as we approach center
+        // and as animation progresses
+        let spiral_tightness = 1.0 / (dist + 0.05);
+        let total_rotation = self.accel * spiral_tightness * self.spin;
+
+        // Tangential stretch: sample from positions that are "behind" on the spiral
+        // This elongates content along the spiral path
+        let tangential_stretch = self.accel * spiral_tightness * TANGENTIAL_STRETCH;
+
+        // Radial compression: as things stretch tangentially, they thin radially
+        // Sample from further out = content compressing inward
+        let radial_compression = 1.0 + self.accel * spiral_tightness * self.pull;
+
+        (angle - total_rotation - tangential_stretch, dist * radial_compression)
+    }
+
+    /// How many window pixels' worth of content one output pixel covers at this
+    /// point of the spiral.
+    ///
+    /// Largest stretch of the Jacobian of the sample position: radially it is
+    /// d(sample_dist)/d(dist) plus the twist the spiral adds per unit of
+    /// distance, tangentially it is sample_dist / dist.
+    #[inline(always)]
+    fn footprint(&self, dist: f32, sample_dist: f32) -> f32 {
+        let spiral_tightness = 1.0 / (dist + 0.05);
+        let k = self.accel * spiral_tightness * spiral_tightness;
+        let twist = self.spin + TANGENTIAL_STRETCH;
+        let radial = (1.0 + self.pull * 0.05 * k).hypot(sample_dist * twist * k);
+        let tangential = sample_dist / dist.max(f32::MIN_POSITIVE);
+        radial.max(tangential)
+    }
+
+    /// Color one pixel given its polar position, spiral sample point and
+    /// sampling footprint in texels.
+    #[inline(always)]
+    fn shade(
+        &self,
+        px: &mut [u8],
+        dist: f32,
+        angle: f32,
+        sample_u: f32,
+        sample_v: f32,
+        footprint: f32,
+    ) {
+        // Inside the singularity = SOLID BLACK
+        if dist < self.singularity_radius {
+            px.copy_from_slice(&[0, 0, 0, 255]);
+            return;
+        }
+
+        if dist < self.disk_outer {
+            let total_intensity = self.disk.intensity(dist, angle);
+
+            if total_intensity > 0.01 {
+                let i = total_intensity.min(1.0);
+                // Purple with brightness variation
+                let r = (90.0 + 90.0 * i) * i;
+                let g = (20.0 + 35.0 * i) * i;
+                let b = (150.0 + 70.0 * i) * i;
+
+                write_bgra(px, [r / 255.0, g / 255.0, b / 255.0, 1.0]);
+                return;
+            }
+        }
+
+        // === SPIRAL SPAGHETTIFICATION ===
+        // Outside bounds = that strand has been consumed
+        if !(0.0..=1.0).contains(&sample_u) || !(0.0..=1.0).contains(&sample_v) {
+            px.fill(0);
+            return;
+        }
+
+        // Filtered sample, blurred just enough for how much the spiral squeezes it
+        let texel = self.texture.sample(sample_u, sample_v, (footprint * self.texel_scale).log2());
+
+        // Darken as it approaches the hole; coverage (alpha) is unchanged
+        let near_hole = (0.2 - dist).max(0.0) / 0.2;
+        let darkness = 1.0 - near_hole * 0.5;
+
+        let [r, g, b, a] = texel;
+        write_bgra(px, [r * darkness, g * darkness, b * darkness, a]);
+    }
+}
+
+impl CpuRenderer for VortexRenderer {
+    fn bounds(&self, window: &Rect, progress: f32) -> Rect {
+        VortexRenderer::bounds(self, window, progress)
+    }
+
+    fn render(&self, target: &mut Target, texture: &Texture, progress: f32) {
+        VortexRenderer::render(self, target, texture, progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pixel::{Image, PixelFormat};
+
+    const WIDTH: usize = 640;
+    const HEIGHT: usize = 360;
+    const SEED: u64 = 0x5EED;
+
+    /// Largest fraction of pixels allowed to differ between the scalar and SIMD paths.
+    /// The approximations move sample points by well under a texel and filtering
+    /// smooths the rest, so differences only show up at the disk and window edges.
+    const MAX_MISMATCH_RATIO: f64 = 0.001;
+
+    fn texture() -> Texture {
+        let pixels: Vec<u8> = (0..WIDTH * HEIGHT * 4).map(|i| (i % 251) as u8).collect();
+        Texture::new(Image::new(pixels, WIDTH, HEIGHT, PixelFormat::Rgba8))
+    }
+
+    /// Render onto a `WIDTH` x `HEIGHT` surface with the window's top left at `(x, y)`.
+    fn render_at(
+        renderer: &VortexRenderer,
+        texture: &Texture,
+        progress: f32,
+        x: i32,
+        y: i32,
+    ) -> Vec<u8> {
+        let mut canvas = vec![0u8; WIDTH * HEIGHT * 4];
+        let surface = Rect::new(0, 0, WIDTH as i32, HEIGHT as i32);
+        let window = Rect::new(x, y, WIDTH as i32, HEIGHT as i32);
+        let mut target = Target {
+            canvas: &mut canvas,
+            width: WIDTH,
+            window,
+            bounds: renderer.bounds(&window, progress).intersect(&surface),
+        };
+        renderer.render(&mut target, texture, progress);
+        canvas
+    }
+
+    fn render(renderer: &VortexRenderer, texture: &Texture, progress: f32) -> Vec<u8> {
+        render_at(renderer, texture, progress, 0, 0)
+    }
+
+    /// Fraction of pixels where any channel differs by more than one step.
+    fn mismatch_ratio(a: &[u8], b: &[u8]) -> f64 {
+        let differing = a
+            .chunks_exact(4)
+            .zip(b.chunks_exact(4))
+            .filter(|(pa, pb)| pa.iter().zip(pb.iter()).any(|(x, y)| x.abs_diff(*y) > 1))
+            .count();
+        differing as f64 / (a.len() / 4) as f64
+    }
+
+    #[test]
+    fn simd_matches_scalar() {
+        let texture = texture();
+        let params = VortexParams::default();
+        let scalar = VortexRenderer::new(params, SEED).with_simd(SimdLevel::Scalar);
+
+        // AVX2 only runs where the CPU has it
+        let mut levels = vec![SimdLevel::Simd128];
+        if SimdLevel::detect() == SimdLevel::Avx2 {
+            levels.push(SimdLevel::Avx2);
+        }
+        for simd in levels {
+            let vectorized = VortexRenderer::new(params, SEED).with_simd(simd);
+            for progress in [0.2f32, 0.5, 0.9] {
+                let ratio = mismatch_ratio(
+                    &render(&scalar, &texture, progress),
+                    &render(&vectorized, &texture, progress),
+                );
+                assert!(
+                    ratio <= MAX_MISMATCH_RATIO,
+                    "{simd:?} output differs from scalar in {:.4}% of pixels at progress {progress}",
+                    ratio * 100.0
+                );
+            }
+        }
+    }
+    #[test]
+    fn window_above_and_left_of_the_surface() {
+        let texture = texture();
+        let renderer =
+            VortexRenderer::new(VortexParams::default(), SEED).with_simd(SimdLevel::Scalar);
+        let (dx, dy) = (WIDTH / 4, HEIGHT / 3);
+        let row_bytes = WIDTH * 4;
+        for progress in [0.2f32, 0.7] {
+            let whole = render(&renderer, &texture, progress);
+            let shifted = render_at(&renderer, &texture, progress, -(dx as i32), -(dy as i32));
+            // What's on the surface is the window's bottom right, unchanged
+            for y in 0..HEIGHT - dy {
+                let on_surface = &shifted[y * row_bytes..y * row_bytes + (WIDTH - dx) * 4];
+                let source = (y + dy) * row_bytes + dx * 4;
+                assert!(
+                    on_surface == &whole[source..source + (WIDTH - dx) * 4],
+                    "row {y} differs at progress {progress}"
+                );
+            }
+        }
+    }
+}
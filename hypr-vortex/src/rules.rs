@@ -0,0 +1,326 @@
+//! Per-window animation rules, matched like Hyprland's `windowrulev2`.
+//!
+//! A rule is a comma-separated list of `field:value` conditions that must all
+//! hold. Regex fields must match the whole string (Hyprland uses RE2 full
+//! matches) and take a `negative:` prefix to invert them. As with window
+//! rules, every matching rule applies in order, so the last match wins.
+//!
+//! ```toml
+//! [[rules]]
+//! match = "class:^(firefox|chromium)$"
+//! animation = "vortex"
+//!
+//! [[rules]]
+//! match = "class:^(kitty|foot)$, floating:0"
+//! animation = "fade"
+//! params = { duration_ms = 120 }
+//!
+//! [[rules]]
+//! match = "class:^(rofi|pinentry-.*)$"
+//! animation = "none"
+//!
+//! [[rules]]
+//! match = "floating:1"
+//! animation = "random"
+//! ```
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+use crate::animation::Animation;
+use crate::window::WindowInfo;
+
+/// Animation name that closes the window without any effect.
+pub const NO_ANIMATION: &str = "none";
+
+/// What a matching rule does.
+#[derive(Clone)]
+pub enum RuleAction {
+    /// Play this (already configured) animation
+    Animate(Arc<dyn Animation>),
+    /// Let a selector (`random` or `cycle`) pick the animation at close time
+    Pick(&'static str),
+    /// Close immediately
+    Skip,
+}
+
+/// A regex condition, possibly negated.
+#[derive(Debug)]
+struct Pattern {
+    regex: Regex,
+    negative: bool,
+}
+
+impl Pattern {
+    fn parse(value: &str) -> Result<Self> {
+        let (negative, source) = match value.strip_prefix("negative:") {
+            Some(rest) => (true, rest),
+            None => (false, value),
+        };
+        // Anchor the whole pattern: RE2's FullMatch semantics
+        let regex = Regex::new(&format!("^(?:{})$", source))
+            .with_context(|| format!("invalid regex '{}'", source))?;
+        Ok(Self { regex, negative })
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        self.regex.is_match(text) != self.negative
+    }
+}
+
+/// Which workspace a `workspace:` condition accepts.
+#[derive(Debug)]
+enum WorkspaceSelector {
+    Id(i64),
+    Name(String),
+}
+
+/// One `field:value` condition.
+#[derive(Debug)]
+enum Condition {
+    Class(Pattern),
+    Title(Pattern),
+    InitialClass(Pattern),
+    InitialTitle(Pattern),
+    Floating(bool),
+    Fullscreen(bool),
+    Pinned(bool),
+    Xwayland(bool),
+    Workspace(WorkspaceSelector),
+}
+
+impl Condition {
+    fn parse(field: &str) -> Result<Self> {
+        let (key, value) = field
+            .split_once(':')
+            .with_context(|| format!("'{}' is not of the form field:value", field))?;
+        let value = value.trim();
+
+        let flag = || match value {
+            "1" | "true" | "yes" | "on" => Ok(true),
+            "0" | "false" | "no" | "off" => Ok(false),
+            _ => anyhow::bail!("{}: expected 0 or 1, got '{}'", key, value),
+        };
+
+        Ok(match key.trim() {
+            "class" => Condition::Class(Pattern::parse(value)?),
+            "title" => Condition::Title(Pattern::parse(value)?),
+            "initialClass" => Condition::InitialClass(Pattern::parse(value)?),
+            "initialTitle" => Condition::InitialTitle(Pattern::parse(value)?),
+            "floating" => Condition::Floating(flag()?),
+            "fullscreen" => Condition::Fullscreen(flag()?),
+            "pinned" => Condition::Pinned(flag()?),
+            "xwayland" => Condition::Xwayland(flag()?),
+            "workspace" => Condition::Workspace(match value.strip_prefix("name:") {
+                Some(name) => WorkspaceSelector::Name(name.to_string()),
+                None => WorkspaceSelector::Id(
+                    value
+                        .parse()
+                        .with_context(|| format!("workspace: expected an id or name:..., got '{}'", value))?,
+                ),
+            }),
+            other => anyhow::bail!(
+                "unknown field '{}' (supported: class, title, initialClass, initialTitle, \
+                 floating, fullscreen, pinned, xwayland, workspace)",
+                other
+            ),
+        })
+    }
+
+    fn matches(&self, window: &WindowInfo) -> bool {
+        match self {
+            Condition::Class(p) => p.matches(&window.class),
+            Condition::Title(p) => p.matches(&window.title),
+            Condition::InitialClass(p) => p.matches(&window.initial_class),
+            Condition::InitialTitle(p) => p.matches(&window.initial_title),
+            Condition::Floating(want) => window.floating == *want,
+            Condition::Fullscreen(want) => window.fullscreen.is_fullscreen() == *want,
+            Condition::Pinned(want) => window.pinned == *want,
+            Condition::Xwayland(want) => window.xwayland == *want,
+            Condition::Workspace(WorkspaceSelector::Id(id)) => window.workspace.id == *id,
+            Condition::Workspace(WorkspaceSelector::Name(name)) => window.workspace.name == *name,
+        }
+    }
+}
+
+/// Parsed conditions of one rule.
+#[derive(Debug)]
+pub struct WindowMatcher {
+    conditions: Vec<Condition>,
+}
+
+impl WindowMatcher {
+    /// Parse `"class:^(kitty)$, floating:1"`.
+    ///
+    /// Commas inside a regex would be ambiguous, so, as in Hyprland, a field
+    /// only starts after a comma followed by a known `field:` prefix.
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut fields: Vec<String> = Vec::new();
+        for piece in source.split(',') {
+            match fields.last_mut() {
+                Some(last) if !starts_field(piece) => {
+                    last.push(',');
+                    last.push_str(piece);
+                }
+                _ => fields.push(piece.to_string()),
+            }
+        }
+
+        let conditions = fields
+            .iter()
+            .map(|f| f.trim())
+            .filter(|f| !f.is_empty())
+            .map(Condition::parse)
+            .collect::<Result<Vec<_>>>()?;
+        if conditions.is_empty() {
+            anyhow::bail!("no conditions");
+        }
+        Ok(Self { conditions })
+    }
+
+    pub fn matches(&self, window: &WindowInfo) -> bool {
+        self.conditions.iter().all(|c| c.matches(window))
+    }
+}
+
+/// Whether a comma-separated piece begins a new `field:` condition.
+fn starts_field(piece: &str) -> bool {
+    const FIELDS: [&str; 9] = [
+        "class:",
+        "title:",
+        "initialClass:",
+        "initialTitle:",
+        "floating:",
+        "fullscreen:",
+        "pinned:",
+        "xwayland:",
+        "workspace:",
+    ];
+    let piece = piece.trim_start();
+    FIELDS.iter().any(|f| piece.starts_with(f))
+}
+
+/// A compiled rule.
+pub struct Rule {
+    pub matcher: WindowMatcher,
+    pub action: RuleAction,
+}
+
+/// Ordered rule list.
+#[derive(Default)]
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+impl Rules {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        Self { rules }
+    }
+
+    /// Action of the last rule matching `window`, if any.
+    pub fn select(&self, window: &WindowInfo) -> Option<&RuleAction> {
+        self.rules
+            .iter()
+            .rev()
+            .find(|rule| rule.matcher.matches(window))
+            .map(|rule| &rule.action)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(class: &str, title: &str, floating: bool) -> WindowInfo {
+        serde_json::from_value(serde_json::json!({
+            "address": "0x1",
+            "class": class,
+            "title": title,
+            "initialClass": class,
+            "initialTitle": title,
+            "at": [0, 0],
+            "size": [800, 600],
+            "workspace": { "id": 2, "name": "web" },
+            "floating": floating,
+            "fullscreen": 0,
+        }))
+        .unwrap()
+    }
+
+    fn matches(rule: &str, window: &WindowInfo) -> bool {
+        WindowMatcher::parse(rule).unwrap().matches(window)
+    }
+
+    #[test]
+    fn regexes_match_the_whole_string() {
+        let kitty = window("kitty", "~", false);
+        assert!(matches("class:kitty", &kitty));
+        assert!(matches("class:kit.*", &kitty));
+        assert!(!matches("class:kit", &kitty));
+        assert!(!matches("class:itty", &kitty));
+        // Alternatives are anchored as a group, not just the first and last
+        assert!(!matches("class:foot|kit", &kitty));
+    }
+
+    #[test]
+    fn negative_prefix_inverts() {
+        let kitty = window("kitty", "~", false);
+        assert!(!matches("class:negative:kitty", &kitty));
+        assert!(matches("class:negative:foot", &kitty));
+        assert!(matches("class:negative:^(foot|alacritty)$", &kitty));
+    }
+
+    #[test]
+    fn every_condition_must_hold() {
+        let kitty = window("kitty", "~", true);
+        assert!(matches("class:^(kitty|foot)$, floating:1", &kitty));
+        assert!(!matches("class:^(kitty|foot)$, floating:0", &kitty));
+        assert!(matches("workspace:2, workspace:name:web", &kitty));
+        assert!(!matches("workspace:3", &kitty));
+    }
+
+    #[test]
+    fn commas_inside_a_regex_stay_in_it() {
+        let editor = window("code", "a, b", false);
+        assert!(matches("title:a, b, class:code", &editor));
+        assert!(matches("title:^(.{1,4})$", &editor));
+    }
+
+    #[test]
+    fn rejects_malformed_rules() {
+        assert!(WindowMatcher::parse("").is_err());
+        assert!(WindowMatcher::parse("colour:red").is_err());
+        assert!(WindowMatcher::parse("floating:maybe").is_err());
+        assert!(WindowMatcher::parse("class:(unclosed").is_err());
+        assert!(WindowMatcher::parse("workspace:first").is_err());
+    }
+
+    #[test]
+    fn last_match_wins() {
+        let rule = |source: &str, action| Rule {
+            matcher: WindowMatcher::parse(source).unwrap(),
+            action,
+        };
+        let rules = Rules::new(vec![
+            rule("class:.*", RuleAction::Pick("random")),
+            rule("class:kitty", RuleAction::Skip),
+            rule("class:foot", RuleAction::Pick("cycle")),
+        ]);
+
+        let selected = |class| rules.select(&window(class, "", false)).cloned();
+        assert!(matches!(selected("kitty"), Some(RuleAction::Skip)));
+        assert!(matches!(selected("foot"), Some(RuleAction::Pick("cycle"))));
+        assert!(matches!(selected("firefox"), Some(RuleAction::Pick("random"))));
+        assert!(Rules::default().select(&window("kitty", "", false)).is_none());
+    }
+}
@@ -3,15 +3,22 @@
 //! Future: implement wlr-screencopy protocol directly for GPU-GPU transfer.
 
 use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
 
 use anyhow::{Context, Result};
 use tracing::debug;
 
 use crate::animation::WindowGeometry;
+use crate::pixel::{Image, PixelFormat};
+
+/// PNG captures taken so far, to name their temp files apart.
+static PNG_CAPTURES: AtomicU64 = AtomicU64::new(0);
 
 /// Capture a screenshot of the specified region.
-/// Returns RGBA pixel data.
-pub fn capture_region(geometry: &WindowGeometry) -> Result<Vec<u8>> {
+///
+/// grim's PPM output is the composited screen, so it is opaque by construction
+/// (a translucent window already has the wallpaper behind it blended in).
+pub fn capture_region(geometry: &WindowGeometry) -> Result<Image> {
     let region = format!(
         "{},{} {}x{}",
         geometry.x, geometry.y, geometry.width, geometry.height
@@ -35,48 +42,37 @@ pub fn capture_region(geometry: &WindowGeometry) -> Result<Vec<u8>> {
 
     // Parse PPM format (P6 header + raw RGB)
     let data = output.stdout;
-    parse_ppm_to_rgba(&data, geometry.width as usize, geometry.height as usize)
+    parse_ppm(&data)
 }
 
-/// Parse PPM (P6) format to RGBA.
-fn parse_ppm_to_rgba(data: &[u8], width: usize, height: usize) -> Result<Vec<u8>> {
-    // PPM P6 format:
-    // P6\n
-    // width height\n
-    // maxval\n
-    // <raw RGB data>
-
+/// Parse PPM (P6) format to opaque RGBA.
+///
+/// The size comes from the header: on scaled outputs grim captures at the
+/// output's resolution, which is larger than the logical window size.
+/// PPM has no alpha channel, so every pixel gets alpha 255; opaque pixels are
+/// the same premultiplied or not, and are tagged premultiplied to skip the
+/// conversion later.
+fn parse_ppm(data: &[u8]) -> Result<Image> {
+    // PPM P6 format: "P6", width, height, maxval separated by whitespace
+    // (with optional # comments), one whitespace byte, then raw samples
     let mut i = 0;
-
-    // Skip "P6\n"
-    while i < data.len() && data[i] != b'\n' {
-        i += 1;
-    }
-    i += 1;
-
-    // Skip comments
-    while i < data.len() && data[i] == b'#' {
-        while i < data.len() && data[i] != b'\n' {
-            i += 1;
-        }
-        i += 1;
-    }
-
-    // Skip "width height\n"
-    while i < data.len() && data[i] != b'\n' {
-        i += 1;
+    let magic = ppm_token(data, &mut i)?;
+    if magic != b"P6" {
+        anyhow::bail!("Not a binary PPM: {:?}", String::from_utf8_lossy(magic));
     }
-    i += 1;
-
-    // Skip "maxval\n"
-    while i < data.len() && data[i] != b'\n' {
-        i += 1;
+    let width = ppm_number(data, &mut i, "width")?;
+    let height = ppm_number(data, &mut i, "height")?;
+    let maxval = ppm_number(data, &mut i, "maxval")?;
+    if !(1..=65535).contains(&maxval) {
+        anyhow::bail!("Invalid PPM maxval: {}", maxval);
     }
+    // Single whitespace byte after maxval
     i += 1;
 
-    // Rest is raw RGB data
-    let rgb_data = &data[i..];
-    let expected_size = width * height * 3;
+    // Samples above 255 take two bytes, big-endian
+    let sample_bytes = if maxval > 255 { 2 } else { 1 };
+    let rgb_data = data.get(i..).unwrap_or_default();
+    let expected_size = width * height * 3 * sample_bytes;
 
     if rgb_data.len() < expected_size {
         anyhow::bail!(
@@ -86,22 +82,64 @@ fn parse_ppm_to_rgba(data: &[u8], width: usize, height: usize) -> Result<Vec<u8>
         );
     }
 
-    // Convert RGB to RGBA
+    // Convert RGB to RGBA, rescaling to 8 bits if needed
     let mut rgba = Vec::with_capacity(width * height * 4);
-    for pixel in rgb_data[..expected_size].chunks(3) {
-        rgba.push(pixel[0]); // R
-        rgba.push(pixel[1]); // G
-        rgba.push(pixel[2]); // B
-        rgba.push(255);      // A (opaque)
+    for pixel in rgb_data[..expected_size].chunks_exact(3 * sample_bytes) {
+        for sample in pixel.chunks_exact(sample_bytes) {
+            let value = sample.iter().fold(0usize, |acc, &b| acc << 8 | b as usize);
+            rgba.push(if maxval == 255 {
+                value as u8
+            } else {
+                ((value.min(maxval) * 255 + maxval / 2) / maxval) as u8
+            });
+        }
+        rgba.push(255); // A (PPM has no alpha)
     }
 
     debug!("Captured {}x{} = {} bytes RGBA", width, height, rgba.len());
-    Ok(rgba)
+    Ok(Image::new(rgba, width, height, PixelFormat::Rgba8Premultiplied))
+}
+
+/// Next whitespace-separated PPM header token, skipping `#` comments.
+fn ppm_token<'a>(data: &'a [u8], i: &mut usize) -> Result<&'a [u8]> {
+    loop {
+        while *i < data.len() && data[*i].is_ascii_whitespace() {
+            *i += 1;
+        }
+        if *i >= data.len() || data[*i] != b'#' {
+            break;
+        }
+        while *i < data.len() && data[*i] != b'\n' {
+            *i += 1;
+        }
+    }
+
+    let start = *i;
+    while *i < data.len() && !data[*i].is_ascii_whitespace() {
+        *i += 1;
+    }
+    if start == *i {
+        anyhow::bail!("PPM header truncated");
+    }
+    Ok(&data[start..*i])
+}
+
+/// Next PPM header token as a decimal number.
+fn ppm_number(data: &[u8], i: &mut usize, what: &str) -> Result<usize> {
+    let token = ppm_token(data, i)?;
+    std::str::from_utf8(token)
+        .ok()
+        .and_then(|t| t.parse().ok())
+        .with_context(|| format!("Invalid PPM {}: {:?}", what, String::from_utf8_lossy(token)))
 }
 
 /// Capture using PNG (slower but more reliable fallback).
-pub fn capture_region_png(geometry: &WindowGeometry) -> Result<Vec<u8>> {
-    let tmp_path = format!("/tmp/vortex-{}.png", std::process::id());
+///
+/// Keeps whatever alpha the PNG carries, as straight alpha.
+pub fn capture_region_png(geometry: &WindowGeometry) -> Result<Image> {
+    // Opens and closes capture concurrently, so each capture gets its own file
+    let n = PNG_CAPTURES.fetch_add(1, Ordering::Relaxed);
+    let tmp_path = format!("/tmp/vortex-{}-{}.png", std::process::id(), n);
     let region = format!(
         "{},{} {}x{}",
         geometry.x, geometry.y, geometry.width, geometry.height
@@ -123,5 +161,51 @@ pub fn capture_region_png(geometry: &WindowGeometry) -> Result<Vec<u8>> {
     // Cleanup
     let _ = std::fs::remove_file(&tmp_path);
 
-    Ok(rgba.into_raw())
+    let (width, height) = rgba.dimensions();
+    Ok(Image::new(
+        rgba.into_raw(),
+        width as usize,
+        height as usize,
+        PixelFormat::Rgba8,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ppm_header_with_comments() {
+        let mut data = b"P6\n# grim\n2 # width\n1\n# maxval next\n255\n".to_vec();
+        data.extend_from_slice(&[10, 20, 30, 40, 50, 60]);
+        let image = parse_ppm(&data).unwrap();
+        assert_eq!((image.width, image.height), (2, 1));
+        assert_eq!(image.pixels, [10, 20, 30, 255, 40, 50, 60, 255]);
+    }
+
+    #[test]
+    fn ppm_maxval_is_rescaled_to_8_bits() {
+        let mut data = b"P6 1 1 15 ".to_vec();
+        data.extend_from_slice(&[0, 15, 5]);
+        assert_eq!(parse_ppm(&data).unwrap().pixels, [0, 255, 85, 255]);
+
+        // Two bytes per sample, big-endian
+        let mut data = b"P6 1 1 65535\n".to_vec();
+        data.extend_from_slice(&[0, 0, 0xff, 0xff, 0x80, 0x00]);
+        assert_eq!(parse_ppm(&data).unwrap().pixels, [0, 255, 128, 255]);
+    }
+
+    #[test]
+    fn bad_ppm_is_rejected() {
+        for data in [
+            &b"P3 1 1 255\n0 0 0"[..],
+            b"P6 1 1",
+            b"P6 1 1 0\n\0\0\0",
+            b"P6 1 1 70000\n\0\0\0",
+            b"P6 x 1 255\n\0\0\0",
+            b"P6 2 1 255\n\0\0\0",
+        ] {
+            assert!(parse_ppm(data).is_err(), "{:?}", String::from_utf8_lossy(data));
+        }
+    }
 }
@@ -0,0 +1,216 @@
+//! WGSL validation with naga, so broken shaders are caught when they're
+//! loaded rather than when a window closes.
+//!
+//! Besides parsing and validating, this checks the contract with the
+//! renderer: a `fs_main` fragment entry point (or `vs_main` vertex entry
+//! point for mesh animations) and a uniform struct at `@group(0) @binding(0)`
+//! laid out like `AnimationUniforms`.
+
+use std::collections::BTreeMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::mem::{offset_of, size_of};
+use std::sync::Mutex;
+
+use anyhow::Result;
+use naga::valid::{Capabilities, ValidationFlags, Validator};
+pub use naga::ShaderStage;
+use naga::{AddressSpace, ResourceBinding, Scalar, Span, TypeInner};
+use tracing::error;
+
+use crate::animation::{AnimationUniforms, PARAM_SLOTS};
+
+/// Fragment entry point every animation shader must define.
+pub const ENTRY_POINT: &str = "fs_main";
+
+/// Vertex entry point of a mesh animation's vertex shader.
+pub const VERTEX_ENTRY_POINT: &str = "vs_main";
+
+/// Uniform fields every shader starts with, and their byte offsets.
+const FIXED_FIELDS: [(&str, usize); 5] = [
+    ("progress", offset_of!(AnimationUniforms, progress)),
+    ("time", offset_of!(AnimationUniforms, time)),
+    ("width", offset_of!(AnimationUniforms, width)),
+    ("height", offset_of!(AnimationUniforms, height)),
+    ("seed", offset_of!(AnimationUniforms, seed)),
+];
+
+/// Parse and validate a fragment shader; `path` labels the diagnostics,
+/// which point at the offending line and column.
+pub fn validate(source: &str, path: &str) -> Result<naga::Module> {
+    validate_stage(source, path, ShaderStage::Fragment)
+}
+
+/// Parse and validate a mesh animation's vertex shader.
+pub fn validate_vertex(source: &str, path: &str) -> Result<naga::Module> {
+    validate_stage(source, path, ShaderStage::Vertex)
+}
+
+fn validate_stage(source: &str, path: &str, stage: ShaderStage) -> Result<naga::Module> {
+    let module = naga::front::wgsl::parse_str(source)
+        .map_err(|e| diagnostic(e.emit_to_string_with_path(source, path)))?;
+
+    Validator::new(ValidationFlags::all(), Capabilities::empty())
+        .validate(&module)
+        .map_err(|e| diagnostic(e.emit_to_string_with_path(source, path)))?;
+
+    let (attribute, entry_point) = match stage {
+        ShaderStage::Vertex => ("vertex", VERTEX_ENTRY_POINT),
+        _ => ("fragment", ENTRY_POINT),
+    };
+    let has_entry_point = module
+        .entry_points
+        .iter()
+        .any(|ep| ep.name == entry_point && ep.stage == stage);
+    if !has_entry_point {
+        anyhow::bail!("{}: no @{} fn {}", path, attribute, entry_point);
+    }
+
+    check_uniforms(&module, source, path)?;
+    Ok(module)
+}
+
+/// `validate` (or `validate_vertex`), remembering the outcome by the
+/// source's hash so rebuilding a registry doesn't parse (or report) the
+/// same shader again.
+pub fn validate_once(source: &str, path: &str, stage: ShaderStage) -> Result<(), String> {
+    static CHECKED: Mutex<BTreeMap<(u64, usize), Result<(), String>>> =
+        Mutex::new(BTreeMap::new());
+
+    let mut hasher = DefaultHasher::new();
+    source.hash(&mut hasher);
+    let key = (hasher.finish(), source.len());
+    let mut checked = CHECKED.lock().unwrap_or_else(|e| e.into_inner());
+    checked
+        .entry(key)
+        .or_insert_with(|| {
+            validate_stage(source, path, stage).map(drop).map_err(|e| {
+                let report = format!("{:#}", e);
+                error!("Invalid shader:\n{}", report);
+                report
+            })
+        })
+        .clone()
+}
+
+/// Check the uniform buffer against `AnimationUniforms`: same size, and the
+/// fixed fields as f32 at the same offsets, under their own names so two
+/// swapped ones don't pass. The padding and parameter area
+/// after them can be declared any way that fills it (`array<vec4<f32>, 3>`,
+/// named floats plus padding, ...).
+fn check_uniforms(module: &naga::Module, source: &str, path: &str) -> Result<()> {
+    let uniform_binding = ResourceBinding { group: 0, binding: 0 };
+    let Some((handle, var)) = module
+        .global_variables
+        .iter()
+        .find(|(_, var)| var.binding.as_ref() == Some(&uniform_binding))
+    else {
+        anyhow::bail!("{}: no uniform buffer at @group(0) @binding(0)", path);
+    };
+    let at = location(module.global_variables.get_span(handle), source, path);
+
+    if var.space != AddressSpace::Uniform {
+        anyhow::bail!("{}: @group(0) @binding(0) must be a var<uniform>", at);
+    }
+    let TypeInner::Struct { members, span } = &module.types[var.ty].inner else {
+        anyhow::bail!("{}: the uniform buffer must be a struct like AnimationUniforms", at);
+    };
+
+    let expected = size_of::<AnimationUniforms>();
+    if *span as usize != expected {
+        anyhow::bail!(
+            "{}: uniform struct is {} bytes, AnimationUniforms is {} \
+             (progress, time, width, height, seed, exit x and y, duration, \
+             then {} parameter floats)",
+            at,
+            span,
+            expected,
+            PARAM_SLOTS
+        );
+    }
+    for (index, (name, offset)) in FIXED_FIELDS.iter().enumerate() {
+        let matches = members.get(index).is_some_and(|member| {
+            member.name.as_deref() == Some(*name)
+                && member.offset as usize == *offset
+                && module.types[member.ty].inner == TypeInner::Scalar(Scalar::F32)
+        });
+        if !matches {
+            anyhow::bail!(
+                "{}: uniform field {} must be `{}: f32` at byte {}",
+                at,
+                index,
+                name,
+                offset
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `path:line:column` of `span`, or just `path` if naga didn't record one.
+fn location(span: Span, source: &str, path: &str) -> String {
+    if !span.is_defined() {
+        return path.to_string();
+    }
+    let location = span.location(source);
+    format!("{}:{}:{}", path, location.line_number, location.line_position)
+}
+
+/// naga's rendered report, minus its trailing blank lines.
+fn diagnostic(report: String) -> anyhow::Error {
+    anyhow::anyhow!(report.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fragment shader whose uniform struct starts with `fields`, then
+    /// fills the rest of the buffer with `rest`.
+    fn shader(fields: &str, rest: &str) -> String {
+        format!(
+            "struct Uniforms {{ {fields} {rest} }}
+            @group(0) @binding(0) var<uniform> u: Uniforms;
+            @fragment
+            fn fs_main() -> @location(0) vec4<f32> {{
+                return vec4<f32>(u.progress);
+            }}"
+        )
+    }
+
+    const FIELDS: &str = "progress: f32, time: f32, width: f32, height: f32, seed: f32,";
+    /// Exit, duration and the parameters: 3 + 12 floats
+    const REST: &str = "exit_x: f32, exit_y: f32, duration: f32, params: array<vec4<f32>, 3>,";
+
+    fn check(source: &str) -> Result<()> {
+        validate(source, "test.wgsl").map(drop)
+    }
+
+    #[test]
+    fn uniforms_like_animation_uniforms_pass() {
+        check(&shader(FIELDS, REST)).unwrap();
+    }
+
+    #[test]
+    fn swapped_fields_are_rejected() {
+        let swapped = "time: f32, progress: f32, width: f32, height: f32, seed: f32,";
+        let message = check(&shader(swapped, REST)).unwrap_err().to_string();
+        assert!(message.contains("`progress: f32` at byte 0"), "{message}");
+    }
+
+    #[test]
+    fn misplaced_fields_are_rejected() {
+        // A vec2 up front pushes everything after it along
+        let fields =
+            "progress: f32, time: f32, size: vec2<f32>, width: f32, height: f32, seed: f32,";
+        let rest = "duration: f32, params: array<vec4<f32>, 3>,";
+        let message = check(&shader(fields, rest)).unwrap_err().to_string();
+        assert!(message.contains("uniform field 2"), "{message}");
+    }
+
+    #[test]
+    fn wrong_size_is_rejected() {
+        let short = "exit_x: f32, exit_y: f32, duration: f32, params: array<vec4<f32>, 2>,";
+        let message = check(&shader(FIELDS, short)).unwrap_err().to_string();
+        assert!(message.contains("is 64 bytes, AnimationUniforms is 80"), "{message}");
+    }
+}
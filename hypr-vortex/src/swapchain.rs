@@ -0,0 +1,186 @@
+//! Fixed-size SHM swapchain for the software renderer.
+//!
+//! Buffers are allocated once per surface size and reused. A buffer attached
+//! to the surface stays busy until the compositor sends `wl_buffer.release`,
+//! so we never draw into memory the compositor may still be reading.
+//!
+//! Each buffer remembers the region it last drew, so the next frame drawn
+//! into it only has to clear what is actually stale.
+
+use anyhow::{Context, Result};
+use smithay_client_toolkit::shm::{
+    slot::{Buffer, SlotPool},
+    Shm,
+};
+
+use crate::damage::Rect;
+use crate::pixel::PixelFormat;
+
+/// Number of buffers in the swapchain (double buffering).
+pub const SWAPCHAIN_LEN: usize = 2;
+
+/// Layout the renderers write into the buffers.
+pub const SURFACE_FORMAT: PixelFormat = PixelFormat::Bgra8Premultiplied;
+
+/// A released buffer ready to be drawn into.
+pub struct BackBuffer<'a> {
+    pub buffer: &'a Buffer,
+    pub canvas: &'a mut [u8],
+    /// Region still holding pixels from the last frame drawn into this buffer
+    pub drawn: &'a mut Rect,
+}
+
+struct SwapSlot<B = Buffer> {
+    buffer: B,
+    drawn: Rect,
+}
+
+/// A buffer the compositor may still be reading from.
+trait Busy {
+    fn is_busy(&self) -> bool;
+}
+
+impl Busy for Buffer {
+    fn is_busy(&self) -> bool {
+        self.slot().has_active_buffers()
+    }
+}
+
+/// Index of the first slot whose buffer the compositor has released.
+fn released<B: Busy>(slots: &[SwapSlot<B>]) -> Option<usize> {
+    slots.iter().position(|slot| !slot.buffer.is_busy())
+}
+
+pub struct Swapchain {
+    pool: SlotPool,
+    slots: Vec<SwapSlot>,
+    width: u32,
+    height: u32,
+    /// Frames skipped because every buffer was still held by the compositor
+    busy_frames: u32,
+}
+
+impl Swapchain {
+    /// Allocate `SWAPCHAIN_LEN` buffers of the given size in `SURFACE_FORMAT`.
+    pub fn new(shm: &Shm, width: u32, height: u32) -> Result<Self> {
+        let format = SURFACE_FORMAT
+            .shm_format()
+            .context("Surface format has no wl_shm equivalent")?;
+        let stride = width as i32 * 4;
+        let frame_len = stride as usize * height as usize;
+
+        let mut pool =
+            SlotPool::new(frame_len * SWAPCHAIN_LEN, shm).context("Failed to create SHM pool")?;
+        // Fresh SHM memory is zero-filled, so nothing is stale yet
+        let slots = (0..SWAPCHAIN_LEN)
+            .map(|_| {
+                pool.create_buffer(width as i32, height as i32, stride, format)
+                    .map(|(buffer, _)| SwapSlot {
+                        buffer,
+                        drawn: Rect::EMPTY,
+                    })
+                    .context("Failed to create SHM buffer")
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            pool,
+            slots,
+            width,
+            height,
+            busy_frames: 0,
+        })
+    }
+
+    /// Whether the swapchain was allocated for this surface size.
+    pub fn matches(&self, width: u32, height: u32) -> bool {
+        self.width == width && self.height == height
+    }
+
+    /// Get a buffer the compositor has released, along with its pixels.
+    ///
+    /// Returns `None` if every buffer is still in use; the caller should skip
+    /// this frame and try again on the next frame callback.
+    pub fn acquire(&mut self) -> Option<BackBuffer<'_>> {
+        let Some(index) = released(&self.slots) else {
+            self.busy_frames += 1;
+            return None;
+        };
+
+        let slot = &mut self.slots[index];
+        let canvas = slot.buffer.canvas(&mut self.pool)?;
+        Some(BackBuffer {
+            buffer: &slot.buffer,
+            canvas,
+            drawn: &mut slot.drawn,
+        })
+    }
+
+    pub fn busy_frames(&self) -> u32 {
+        self.busy_frames
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Stands in for a `wl_buffer`: busy from attach until release.
+    #[derive(Default)]
+    struct FakeBuffer {
+        attached: Cell<bool>,
+    }
+
+    impl Busy for FakeBuffer {
+        fn is_busy(&self) -> bool {
+            self.attached.get()
+        }
+    }
+
+    fn slots() -> Vec<SwapSlot<FakeBuffer>> {
+        (0..SWAPCHAIN_LEN)
+            .map(|_| SwapSlot {
+                buffer: FakeBuffer::default(),
+                drawn: Rect::EMPTY,
+            })
+            .collect()
+    }
+
+    /// Draw `rect` into the first released slot and attach it, like a frame does.
+    fn draw(slots: &mut [SwapSlot<FakeBuffer>], rect: Rect) -> Option<(usize, Rect)> {
+        let index = released(slots)?;
+        let slot = &mut slots[index];
+        let stale = std::mem::replace(&mut slot.drawn, rect);
+        slot.buffer.attached.set(true);
+        Some((index, stale))
+    }
+
+    #[test]
+    fn frames_skip_while_every_buffer_is_busy() {
+        let mut slots = slots();
+        let a = Rect::new(0, 0, 10, 10);
+        assert_eq!(draw(&mut slots, a), Some((0, Rect::EMPTY)));
+        assert_eq!(draw(&mut slots, a), Some((1, Rect::EMPTY)));
+        assert_eq!(draw(&mut slots, a), None);
+
+        slots[1].buffer.attached.set(false);
+        assert_eq!(released(&slots), Some(1));
+    }
+
+    #[test]
+    fn each_buffer_clears_what_it_drew_itself() {
+        let mut slots = slots();
+        let first = Rect::new(0, 0, 100, 100);
+        let second = Rect::new(10, 10, 80, 80);
+        let third = Rect::new(20, 20, 60, 60);
+        draw(&mut slots, first).unwrap();
+        draw(&mut slots, second).unwrap();
+
+        // The first buffer comes back holding the first frame, not the second
+        slots[0].buffer.attached.set(false);
+        assert_eq!(draw(&mut slots, third), Some((0, first)));
+        slots[1].buffer.attached.set(false);
+        assert_eq!(draw(&mut slots, third), Some((1, second)));
+    }
+}
@@ -0,0 +1,103 @@
+//! Directory watching for hot reload, on inotify.
+//!
+//! Watches the directory rather than the file: editors save by writing a temp
+//! file and renaming it over the original, which replaces the inode a
+//! file-level watch would be attached to.
+
+use std::ffi::OsStr;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use inotify::{EventMask, Inotify, WatchMask};
+use tracing::{debug, warn};
+
+/// How long to wait for an editor's burst of events to finish.
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// Call `on_change` from a background thread whenever a file in `dir`
+/// matching `filter` is written, created, renamed or deleted.
+///
+/// Deleting or moving `dir` itself also calls it, once, and ends the watch.
+pub fn spawn<F, C>(dir: &Path, filter: F, mut on_change: C) -> Result<()>
+where
+    F: Fn(&OsStr) -> bool + Send + 'static,
+    C: FnMut() + Send + 'static,
+{
+    let mut inotify = Inotify::init().context("Failed to initialize inotify")?;
+    inotify
+        .watches()
+        .add(
+            dir,
+            WatchMask::CLOSE_WRITE
+                | WatchMask::CREATE
+                | WatchMask::MOVED_TO
+                | WatchMask::MOVED_FROM
+                | WatchMask::DELETE
+                | WatchMask::DELETE_SELF
+                | WatchMask::MOVE_SELF,
+        )
+        .with_context(|| format!("Failed to watch {}", dir.display()))?;
+
+    debug!("Watching {} for changes", dir.display());
+    let dir = dir.to_path_buf();
+
+    thread::spawn(move || {
+        let mut buffer = [0u8; 4096];
+        loop {
+            let events = match inotify.read_events_blocking(&mut buffer) {
+                Ok(events) => events,
+                Err(e) => {
+                    warn!("Stopped watching {}: {}", dir.display(), e);
+                    return;
+                }
+            };
+            let mut changed = false;
+            let mut gone = false;
+            for event in events {
+                gone |= event.mask.intersects(EventMask::DELETE_SELF | EventMask::MOVE_SELF);
+                changed |= event.name.is_some_and(&filter);
+            }
+            if gone {
+                warn!("{} was deleted or moved, stopped watching it", dir.display());
+                on_change();
+                return;
+            }
+            if !changed {
+                continue;
+            }
+
+            // Let the rest of the save land, then swallow its events
+            thread::sleep(DEBOUNCE);
+            while inotify.read_events(&mut buffer).is_ok_and(|mut e| e.next().is_some()) {}
+
+            on_change();
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn renames_away_and_deleting_the_directory_are_changes() {
+        let dir = std::env::temp_dir().join(format!("hypr-vortex-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("config.toml");
+        std::fs::write(&file, "").unwrap();
+
+        let (sender, changes) = mpsc::channel();
+        spawn(&dir, |name| name == "config.toml", move || sender.send(()).unwrap()).unwrap();
+        let changed = || changes.recv_timeout(Duration::from_secs(2)).is_ok();
+
+        std::fs::rename(&file, dir.join("config.toml.bak")).unwrap();
+        assert!(changed(), "moving the file away");
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(changed(), "deleting the directory");
+    }
+}
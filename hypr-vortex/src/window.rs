@@ -0,0 +1,80 @@
+//! Window properties from Hyprland, looked up by address.
+
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::animation::WindowGeometry;
+
+/// Workspace a window is on.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Workspace {
+    pub id: i64,
+    pub name: String,
+}
+
+/// The parts of a `hyprctl -j clients` entry that rules can match on.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowInfo {
+    pub address: String,
+    pub class: String,
+    pub title: String,
+    pub initial_class: String,
+    pub initial_title: String,
+    /// Top left in layout pixels
+    pub at: (i32, i32),
+    pub size: (u32, u32),
+    pub workspace: Workspace,
+    pub floating: bool,
+    pub fullscreen: Fullscreen,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub xwayland: bool,
+}
+
+/// Fullscreen state: a bool before Hyprland 0.42, a mode number after.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(untagged)]
+pub enum Fullscreen {
+    Flag(bool),
+    Mode(u8),
+}
+
+impl Fullscreen {
+    pub fn is_fullscreen(self) -> bool {
+        match self {
+            Fullscreen::Flag(flag) => flag,
+            Fullscreen::Mode(mode) => mode != 0,
+        }
+    }
+}
+
+impl WindowInfo {
+    /// Where the window is, in the form the close script reports it.
+    pub fn geometry(&self) -> WindowGeometry {
+        WindowGeometry {
+            x: self.at.0,
+            y: self.at.1,
+            width: self.size.0,
+            height: self.size.1,
+        }
+    }
+
+    /// Find the window with `address` among Hyprland's clients.
+    pub fn lookup(address: &str) -> Result<Option<Self>> {
+        let output = Command::new("hyprctl")
+            .args(["-j", "clients"])
+            .output()
+            .context("Failed to run hyprctl")?;
+        if !output.status.success() {
+            anyhow::bail!("hyprctl clients failed: {}", String::from_utf8_lossy(&output.stderr));
+        }
+
+        let clients: Vec<WindowInfo> =
+            serde_json::from_slice(&output.stdout).context("Failed to parse hyprctl clients")?;
+        Ok(clients.into_iter().find(|w| w.address == address))
+    }
+}
@@ -1,7 +1,8 @@
 #!/bin/bash
 # Vortex close script - sends window geometry to daemon, waits, then closes
 
-SOCKET="/tmp/hypr-vortex.sock"
+# Must match socket_path in ~/.config/hypr-vortex/config.toml
+SOCKET="${VORTEX_SOCKET:-/tmp/hypr-vortex.sock}"
 
 # Check if daemon is running
 if [ ! -S "$SOCKET" ]; then